@@ -17,8 +17,14 @@
 
 #define MIST_STEAM_INPUT_MAX_COUNT 16
 
+/**
+ * Deprecated, the action capacity follows the action manifest and this is only the capacity without one
+ */
 #define MIST_STEAM_INPUT_MAX_ANALOG_ACTIONS 16
 
+/**
+ * Deprecated, the action capacity follows the action manifest and this is only the capacity without one
+ */
 #define MIST_STEAM_INPUT_MAX_DIGITAL_ACTIONS 128
 
 #define MIST_STEAM_INPUT_MAX_ORIGINS 8
@@ -312,6 +318,29 @@ enum MistInputActionOrigin {
 };
 typedef uint32_t MistInputActionOrigin;
 
+/**
+ * Kind of the action handle passed to mist_steam_input_get_action_prompt
+ */
+typedef enum MistInputActionType {
+  MistInputActionType_Analog,
+  MistInputActionType_Digital,
+} MistInputActionType;
+
+typedef enum MistInterface {
+  MistInterface_SteamApps = 0,
+  MistInterface_SteamFriends = 1,
+  MistInterface_SteamInput = 2,
+  MistInterface_SteamRemoteStorage = 3,
+  MistInterface_SteamUtils = 4,
+} MistInterface;
+
+typedef enum MistLogLevel {
+  MistLogLevel_Error = 0,
+  MistLogLevel_Warning = 1,
+  MistLogLevel_Info = 2,
+  MistLogLevel_Debug = 3,
+} MistLogLevel;
+
 typedef enum MistSteamControllerLEDFlag {
   MistSteamControllerLEDFlag_SetColor = 0,
   MistSteamControllerLEDFlag_RestoreUserDefault = 1,
@@ -352,6 +381,51 @@ typedef enum MistSteamInputType {
   MistSteamInputType_MaximumPossibleValue = 255,
 } MistSteamInputType;
 
+typedef enum MistXboxOrigin {
+  MistXboxOrigin_A = 0,
+  MistXboxOrigin_B,
+  MistXboxOrigin_X,
+  MistXboxOrigin_Y,
+  MistXboxOrigin_LeftBumper,
+  MistXboxOrigin_RightBumper,
+  MistXboxOrigin_Menu,
+  MistXboxOrigin_View,
+  MistXboxOrigin_LeftTrigger_Pull,
+  MistXboxOrigin_LeftTrigger_Click,
+  MistXboxOrigin_RightTrigger_Pull,
+  MistXboxOrigin_RightTrigger_Click,
+  MistXboxOrigin_LeftStick_Move,
+  MistXboxOrigin_LeftStick_Click,
+  MistXboxOrigin_LeftStick_DPadNorth,
+  MistXboxOrigin_LeftStick_DPadSouth,
+  MistXboxOrigin_LeftStick_DPadWest,
+  MistXboxOrigin_LeftStick_DPadEast,
+  MistXboxOrigin_RightStick_Move,
+  MistXboxOrigin_RightStick_Click,
+  MistXboxOrigin_RightStick_DPadNorth,
+  MistXboxOrigin_RightStick_DPadSouth,
+  MistXboxOrigin_RightStick_DPadWest,
+  MistXboxOrigin_RightStick_DPadEast,
+  MistXboxOrigin_DPad_North,
+  MistXboxOrigin_DPad_South,
+  MistXboxOrigin_DPad_West,
+  MistXboxOrigin_DPad_East,
+  MistXboxOrigin_Count,
+} MistXboxOrigin;
+
+/**
+ * Owns a subprocess and all of its state, opaque to C
+ * The global functions use a default context, further contexts are created with mist_context_create
+ * The log callback, call timeouts and last error message are shared by all contexts
+ */
+typedef struct MistContext MistContext;
+
+/**
+ * Log callback, msg is only valid for the duration of the call
+ * It can be called from any thread which uses mist as well as the mist reader thread
+ */
+typedef void (*MistLogCallback)(enum MistLogLevel level, const char *msg, void *user_data);
+
 typedef uint32_t MistResult;
 
 typedef int32_t SteamUser;
@@ -362,6 +436,12 @@ typedef struct MistCallbackMsg {
   const void *data;
 } MistCallbackMsg;
 
+/**
+ * Handler called from mist_poll for registered callbacks
+ * The callback msg and the data it points to are only valid for the duration of the call
+ */
+typedef void (*MistCallbackHandler)(const struct MistCallbackMsg *callback, void *user_data);
+
 typedef uint32_t AppId;
 
 typedef int32_t BuildId;
@@ -374,6 +454,24 @@ typedef uint64_t MistInputHandle;
 
 typedef uint64_t MistInputActionSetHandle;
 
+/**
+ * What to show the user for an action, the strings stay valid until the subprocess is deinitialized
+ */
+typedef struct MistInputActionPrompt {
+  /**
+   * The primary origin of the action, None if the action is not bound
+   */
+  MistInputActionOrigin origin;
+  /**
+   * Localized name of the origin, NULL if the action is not bound
+   */
+  const char *origin_string;
+  /**
+   * Path of the png glyph of the origin, NULL if the action is not bound or the origin has no glyph
+   */
+  const char *glyph_path;
+} MistInputActionPrompt;
+
 typedef struct MistInputAnalogActionData {
   enum MistControllerSourceMode mode;
   float x;
@@ -403,36 +501,369 @@ typedef struct MistInputMotionData {
   float rot_vel_z;
 } MistInputMotionData;
 
+/**
+ * A press or release of a digital action
+ */
+typedef struct MistInputActionEvent {
+  MistInputHandle input_handle;
+  MistInputDigitalActionHandle digital_action_handle;
+  /**
+   * True when the action was pressed, false when it was released
+   */
+  bool pressed;
+  /**
+   * Microseconds of a monotonic clock that starts with the subprocess
+   */
+  uint64_t timestamp_us;
+} MistInputActionEvent;
+
+/**
+ * A controller was assigned to or removed from a slot of the gamepad mapping
+ */
+typedef struct MistInputSlotEvent {
+  /**
+   * Index into the gamepad mapping
+   */
+  uint32_t slot;
+  /**
+   * The controller now in the slot, 0 if the slot was freed
+   */
+  MistInputHandle input_handle;
+  /**
+   * The controller previously in the slot, 0 if the slot was free
+   */
+  MistInputHandle previous_input_handle;
+  /**
+   * Microseconds of a monotonic clock that starts with the subprocess
+   */
+  uint64_t timestamp_us;
+} MistInputSlotEvent;
+
+/**
+ * Settings turning the gyro of a gamepad into a pointer
+ */
+typedef struct MistGyroPointerConfig {
+  /**
+   * Pointer movement per degree of rotation, the screen spans -1 to 1 so 2 / sensitivity is the degrees to cross it
+   */
+  float sensitivity;
+  /**
+   * Extra gain per 100 degrees per second of rotation, 0 disables acceleration
+   */
+  float acceleration;
+  /**
+   * Rotation in degrees per second below which the gamepad is considered still and the gyro drift is learned
+   */
+  float recenter_threshold;
+  /**
+   * Fraction of the drift learned per second while the gamepad is still
+   */
+  float recenter_rate;
+  /**
+   * How far the pointer can move past the edges of the screen, where a lightgun is offscreen
+   */
+  float offscreen_margin;
+} MistGyroPointerConfig;
+
+/**
+ * Pointer driven by the gyro, -1 is the left or top edge of the screen and 1 the right or bottom edge
+ */
+typedef struct MistGyroPointerState {
+  float x;
+  float y;
+  /**
+   * Movement since the previous snapshot
+   */
+  float delta_x;
+  float delta_y;
+} MistGyroPointerState;
+
+typedef struct MistVector3 {
+  float x;
+  float y;
+  float z;
+} MistVector3;
+
+/**
+ * Motor speeds of a haptic pattern at a point in time, speeds between keyframes are interpolated linearly
+ */
+typedef struct MistHapticKeyframe {
+  /**
+   * Time since the start of the pattern, increasing with every keyframe
+   */
+  uint32_t time_ms;
+  unsigned short left_speed;
+  unsigned short right_speed;
+  unsigned short left_trigger_speed;
+  unsigned short right_trigger_speed;
+} MistHapticKeyframe;
+
+/**
+ * Sets the callback which receives all log messages from mist and the subprocess
+ * Passing NULL as callback restores logging to stderr
+ * This can be called before mist_subprocess_init
+ */
+void mist_set_log_callback(MistLogCallback callback, void *user_data);
+
+/**
+ * Sets the timeout in ms for calls to the subprocess which have no interface timeout
+ * Calls known to be slow are never given less than their built-in timeout
+ * Passing 0 restores the default timeouts, this can be called before mist_subprocess_init
+ * Returns MistResult
+ */
+MistResult mist_set_call_timeout(uint32_t timeout_ms);
+
+/**
+ * Sets the timeout in ms for calls to the subprocess for a single interface, interface is a MistInterface value
+ * Passing 0 makes the interface use the timeout from mist_set_call_timeout again
+ * Returns MistResult
+ */
+MistResult mist_set_interface_call_timeout(int interface,
+                                           uint32_t timeout_ms);
+
+/**
+ * Sets the time in ms without any message from the subprocess after which it is considered hung
+ * Calls to a hung subprocess fail with MistError_SubprocessHung, mist_subprocess_restart replaces it
+ * Passing 0 restores the default of 15 seconds, this can be called before mist_subprocess_init
+ * Returns MistResult
+ */
+MistResult mist_set_hang_timeout(uint32_t timeout_ms);
+
+/**
+ * Creates a context with its own subprocess, the global functions use a separate default context
+ * The subprocess of the context is started with mist_context_subprocess_init
+ * # Safety
+ * context must be valid for writes
+ * Returns MistResult
+ */
+MistResult mist_context_create(struct MistContext **context);
+
+/**
+ * Deinits the subprocess of the context if it is running and frees the context
+ * The default context can not be destroyed
+ * # Safety
+ * context must come from mist_context_create and is invalid afterwards
+ * Returns MistResult
+ */
+MistResult mist_context_destroy(struct MistContext *context);
+
+/**
+ * Variant of mist_subprocess_init operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+MistResult mist_context_subprocess_init(struct MistContext *context);
+
 /**
  * Init mist, this is throwns an error if it was already initialised
+ * On Linux the subprocess is terminated when the thread which started it exits, so call it from a long-lived thread
  * Returns MistResult
  */
 MistResult mist_subprocess_init(void);
 
+/**
+ * Variant of mist_subprocess_restart operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+MistResult mist_context_subprocess_restart(struct MistContext *context);
+
+/**
+ * Kills the subprocess and starts a new one, meant for recovering from MistError_SubprocessHung
+ * Registered callback handlers and callback subscriptions are kept, Steam Input has to be set up again
+ * The previous state is kept if the new subprocess fails to start, so the restart can be retried
+ * On Linux the new subprocess is terminated when the thread which restarted it exits
+ * Returns MistResult
+ */
+MistResult mist_subprocess_restart(void);
+
+/**
+ * Variant of mist_poll operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+MistResult mist_context_poll(struct MistContext *context);
+
 /**
  * Polls the subprocess
+ * Callbacks with a registered handler are dispatched synchronously on the calling thread,
+ * all other callbacks are queued for mist_next_callback
  * Returns MistResult
  */
 MistResult mist_poll(void);
 
+/**
+ * Variant of mist_register_callback_handler operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+MistResult mist_context_register_callback_handler(struct MistContext *context,
+                                                  uint32_t callback_id,
+                                                  MistCallbackHandler handler,
+                                                  void *user_data);
+
+/**
+ * Registers a handler which mist_poll calls for every callback with the callback id
+ * Passing NULL as handler unregisters the handler for the callback id
+ * Callbacks passed to a handler are not returned by mist_next_callback
+ * Safety: The MistCallbackMsg passed to the handler is only valid for the duration of the call
+ * Returns MistResult
+ */
+MistResult mist_register_callback_handler(uint32_t callback_id,
+                                          MistCallbackHandler handler,
+                                          void *user_data);
+
+/**
+ * Variant of mist_register_catch_all_callback_handler operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+MistResult mist_context_register_catch_all_callback_handler(struct MistContext *context,
+                                                            MistCallbackHandler handler,
+                                                            void *user_data);
+
+/**
+ * Registers a handler which mist_poll calls for every callback without a handler for its callback id
+ * Passing NULL as handler unregisters the catch-all handler
+ * Callbacks passed to a handler are not returned by mist_next_callback
+ * Safety: The MistCallbackMsg passed to the handler is only valid for the duration of the call
+ * Returns MistResult
+ */
+MistResult mist_register_catch_all_callback_handler(MistCallbackHandler handler,
+                                                    void *user_data);
+
+/**
+ * Variant of mist_subscribe_callbacks operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_subscribe_callbacks
+ */
+MistResult mist_context_subscribe_callbacks(struct MistContext *context,
+                                            const uint32_t *callback_ids,
+                                            uint32_t count);
+
+/**
+ * Subscribes to the count callback ids in callback_ids, passing NULL as callback_ids subscribes to all callbacks
+ * All callbacks are subscribed to by default, the subprocess only sends subscribed callbacks
+ * # Safety
+ * callback_ids must be NULL or valid for reads of count ids
+ * Returns MistResult
+ */
+MistResult mist_subscribe_callbacks(const uint32_t *callback_ids,
+                                    uint32_t count);
+
+/**
+ * Variant of mist_unsubscribe_callbacks operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_unsubscribe_callbacks
+ */
+MistResult mist_context_unsubscribe_callbacks(struct MistContext *context,
+                                              const uint32_t *callback_ids,
+                                              uint32_t count);
+
+/**
+ * Unsubscribes from the count callback ids in callback_ids, passing NULL as callback_ids unsubscribes from all callbacks
+ * Unsubscribing from all callbacks and subscribing to the needed ones limits the subprocess to those callbacks
+ * Callbacks already received by mist are still returned
+ * # Safety
+ * callback_ids must be NULL or valid for reads of count ids
+ * Returns MistResult
+ */
+MistResult mist_unsubscribe_callbacks(const uint32_t *callback_ids,
+                                      uint32_t count);
+
+/**
+ * Variant of mist_next_callback operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_next_callback
+ */
+MistResult mist_context_next_callback(struct MistContext *context,
+                                      bool *has_callback,
+                                      struct MistCallbackMsg *p_callback);
+
 /**
  * Attempts to return the next callback, if none are left it will set p_callback to NULL
- * Safety: The pointer is only valid until the next call of this function
+ * # Safety
+ * has_callback and p_callback must be valid for writes
+ * The pointer is only valid until the next call of this function
  * Due to this it is not safe to simultaneously access callbacks from two different threads since they might race invalidate the other threads callback
  * Returns MistResult
  */
 MistResult mist_next_callback(bool *has_callback,
                               struct MistCallbackMsg *p_callback);
 
+/**
+ * Variant of mist_wait_for_callback operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_wait_for_callback
+ */
+MistResult mist_context_wait_for_callback(struct MistContext *context,
+                                          uint32_t callback_id,
+                                          uint32_t timeout_ms,
+                                          struct MistCallbackMsg *p_callback);
+
+/**
+ * Blocks until a callback with the callback id is received, fails with MistError_Timeout if none arrives in time
+ * Callbacks received while waiting stay queued for mist_poll and mist_next_callback
+ * Other threads can call into mist while it waits, a callback they take from the queue meanwhile is not returned
+ * # Safety
+ * p_callback must be valid for writes
+ * The pointer is only valid until the next call of this function
+ * Returns MistResult
+ */
+MistResult mist_wait_for_callback(uint32_t callback_id,
+                                  uint32_t timeout_ms,
+                                  struct MistCallbackMsg *p_callback);
+
+/**
+ * Returns a static human-readable string describing the MistResult
+ * Returns const char *
+ */
+const char *mist_result_to_string(MistResult result);
+
+/**
+ * Returns the detailed message of the error logged by the last failed mist call on the calling thread
+ * Returns NULL if the last mist call on the calling thread did not log an error
+ * Safety: The string is only valid until the next mist call on the calling thread
+ * Returns const char *
+ */
+const char *mist_get_last_error_message(void);
+
+/**
+ * Variant of mist_subprocess_deinit operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+MistResult mist_context_subprocess_deinit(struct MistContext *context);
+
 /**
  * Deinits the mist subprocess, returns false on error
  */
 MistResult mist_subprocess_deinit(void);
 
+/**
+ * Variant of mist_steam_apps_get_dlc_data_by_index operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_apps_get_dlc_data_by_index
+ */
+MistResult mist_context_steam_apps_get_dlc_data_by_index(struct MistContext *context,
+                                                         int32_t dlc,
+                                                         AppId *app_id,
+                                                         bool *availiable,
+                                                         char *name,
+                                                         uint32_t name_size);
+
 /**
  * Get the metadata for the dlc by dlc index
  * Returns MistResult
  * dlc_data is only guaranteed to be valid til the next time the function is called
+ * # Safety
+ * app_id and availiable must be valid for writes
+ * name must be valid for writes of name_size bytes
  */
 MistResult mist_steam_apps_get_dlc_data_by_index(int32_t dlc,
                                                  AppId *app_id,
@@ -440,109 +871,303 @@ MistResult mist_steam_apps_get_dlc_data_by_index(int32_t dlc,
                                                  char *name,
                                                  uint32_t name_size);
 
+/**
+ * Variant of mist_steam_apps_is_app_installed operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_apps_is_app_installed
+ */
+MistResult mist_context_steam_apps_is_app_installed(struct MistContext *context,
+                                                    AppId app_id,
+                                                    bool *installed);
+
 /**
  * Checks if an app with the appid is installed
+ * # Safety
+ * installed must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_apps_is_app_installed(AppId app_id, bool *installed);
 
+/**
+ * Variant of mist_steam_apps_is_cybercafe operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_apps_is_cybercafe
+ */
+MistResult mist_context_steam_apps_is_cybercafe(struct MistContext *context, bool *is_cybercafe);
+
 /**
  * Checks if the app is running in a cybercafe
+ * # Safety
+ * is_cybercafe must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_apps_is_cybercafe(bool *is_cybercafe);
 
+/**
+ * Variant of mist_steam_apps_is_dlc_installed operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_apps_is_dlc_installed
+ */
+MistResult mist_context_steam_apps_is_dlc_installed(struct MistContext *context,
+                                                    AppId app_id,
+                                                    bool *installed);
+
 /**
  * Checks if a dlc with the appid is installed
+ * # Safety
+ * installed must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_apps_is_dlc_installed(AppId app_id, bool *installed);
 
+/**
+ * Variant of mist_steam_apps_is_low_violence operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_apps_is_low_violence
+ */
+MistResult mist_context_steam_apps_is_low_violence(struct MistContext *context,
+                                                   bool *is_low_violence);
+
 /**
  * Checks if low violence mode is set
+ * # Safety
+ * is_low_violence must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_apps_is_low_violence(bool *is_low_violence);
 
+/**
+ * Variant of mist_steam_apps_is_subscribed operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_apps_is_subscribed
+ */
+MistResult mist_context_steam_apps_is_subscribed(struct MistContext *context, bool *is_subscribed);
+
 /**
  * Checks if the active user is subscribed to the current app
+ * # Safety
+ * is_subscribed must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_apps_is_subscribed(bool *is_subscribed);
 
+/**
+ * Variant of mist_steam_apps_is_subscribed_app operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_apps_is_subscribed_app
+ */
+MistResult mist_context_steam_apps_is_subscribed_app(struct MistContext *context,
+                                                     AppId app_id,
+                                                     bool *is_subscribed);
+
 /**
  * Checks if the active user is subscribed to the app id
+ * # Safety
+ * is_subscribed must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_apps_is_subscribed_app(AppId app_id, bool *is_subscribed);
 
+/**
+ * Variant of mist_steam_apps_is_subscribed_from_family_sharing operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_apps_is_subscribed_from_family_sharing
+ */
+MistResult mist_context_steam_apps_is_subscribed_from_family_sharing(struct MistContext *context,
+                                                                     bool *is_subscribed_from_family_sharing);
+
 /**
  * Checks if the active user is subscribed from family sharing
+ * # Safety
+ * is_subscribed_from_family_sharing must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_apps_is_subscribed_from_family_sharing(bool *is_subscribed_from_family_sharing);
 
+/**
+ * Variant of mist_steam_apps_is_subscribed_from_free_weekend operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_apps_is_subscribed_from_free_weekend
+ */
+MistResult mist_context_steam_apps_is_subscribed_from_free_weekend(struct MistContext *context,
+                                                                   bool *is_subscribed_from_free_weekend);
+
 /**
  * Checks if the active user is subscribed from free weekend
+ * # Safety
+ * is_subscribed_from_free_weekend must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_apps_is_subscribed_from_free_weekend(bool *is_subscribed_from_free_weekend);
 
+/**
+ * Variant of mist_steam_apps_is_vac_banned operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_apps_is_vac_banned
+ */
+MistResult mist_context_steam_apps_is_vac_banned(struct MistContext *context, bool *is_vac_banned);
+
 /**
  * Checks if the user has a VAC ban
+ * # Safety
+ * is_vac_banned must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_apps_is_vac_banned(bool *is_vac_banned);
 
+/**
+ * Variant of mist_steam_apps_get_app_build_id operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_apps_get_app_build_id
+ */
+MistResult mist_context_steam_apps_get_app_build_id(struct MistContext *context, BuildId *build_id);
+
 /**
  * Get the current build id of the application
+ * # Safety
+ * build_id must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_apps_get_app_build_id(BuildId *build_id);
 
+/**
+ * Variant of mist_steam_apps_get_app_install_dir operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_apps_get_app_install_dir
+ */
+MistResult mist_context_steam_apps_get_app_install_dir(struct MistContext *context,
+                                                       AppId app_id,
+                                                       char *folder,
+                                                       uint32_t folder_size,
+                                                       uint32_t *folder_copied);
+
 /**
  * Get the install dir of the app to the app id provided
  * Returns MistResult
  * app_install_dir is only guaranteed to be valid til the next time the function is called
+ * # Safety
+ * folder_copied must be valid for writes
+ * folder must be valid for writes of folder_size bytes
  */
 MistResult mist_steam_apps_get_app_install_dir(AppId app_id,
                                                char *folder,
                                                uint32_t folder_size,
                                                uint32_t *folder_copied);
 
+/**
+ * Variant of mist_steam_apps_get_app_owner operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_apps_get_app_owner
+ */
+MistResult mist_context_steam_apps_get_app_owner(struct MistContext *context, SteamId *steam_id);
+
 /**
  * Get the steam id of the owner of the application
+ * # Safety
+ * steam_id must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_apps_get_app_owner(SteamId *steam_id);
 
+/**
+ * Variant of mist_steam_apps_get_available_game_languages operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_apps_get_available_game_languages
+ */
+MistResult mist_context_steam_apps_get_available_game_languages(struct MistContext *context,
+                                                                const char **avaliable_languages);
+
 /**
  * Get a comma seperated list of the avaliable game languages
+ * # Safety
+ * avaliable_languages must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_apps_get_available_game_languages(const char **avaliable_languages);
 
+/**
+ * Variant of mist_steam_apps_get_current_beta_name operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_apps_get_current_beta_name
+ */
+MistResult mist_context_steam_apps_get_current_beta_name(struct MistContext *context,
+                                                         bool *on_beta,
+                                                         char *name,
+                                                         uint32_t name_size);
+
 /**
  * Get the name of the current beta, sets it to NULL if on the default beta/branch
  * current_beta_name is only guaranteed to be valid til the next time the function is called
+ * # Safety
+ * on_beta must be valid for writes
+ * name must be valid for writes of name_size bytes
  * Returns MistResult
  */
 MistResult mist_steam_apps_get_current_beta_name(bool *on_beta, char *name, uint32_t name_size);
 
+/**
+ * Variant of mist_steam_apps_get_current_game_language operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_apps_get_current_game_language
+ */
+MistResult mist_context_steam_apps_get_current_game_language(struct MistContext *context,
+                                                             const char **current_game_language);
+
 /**
  * Get the current game language
+ * # Safety
+ * current_game_language must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_apps_get_current_game_language(const char **current_game_language);
 
+/**
+ * Variant of mist_steam_apps_get_dlc_count operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_apps_get_dlc_count
+ */
+MistResult mist_context_steam_apps_get_dlc_count(struct MistContext *context, int32_t *dlc_count);
+
 /**
  * Get the dlc count used for getting the dlc info by index
+ * # Safety
+ * dlc_count must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_apps_get_dlc_count(int32_t *dlc_count);
 
+/**
+ * Variant of mist_steam_apps_get_dlc_download_progress operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_apps_get_dlc_download_progress
+ */
+MistResult mist_context_steam_apps_get_dlc_download_progress(struct MistContext *context,
+                                                             AppId app_id,
+                                                             bool *downloading,
+                                                             uint64_t *bytes_downloaded,
+                                                             uint64_t *bytes_total);
+
 /**
  * Get the download progress of a dlc
+ * # Safety
+ * downloading, bytes_downloaded and bytes_total must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_apps_get_dlc_download_progress(AppId app_id,
@@ -550,14 +1175,41 @@ MistResult mist_steam_apps_get_dlc_download_progress(AppId app_id,
                                                      uint64_t *bytes_downloaded,
                                                      uint64_t *bytes_total);
 
+/**
+ * Variant of mist_steam_apps_get_earliest_purchase_unix_time operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_apps_get_earliest_purchase_unix_time
+ */
+MistResult mist_context_steam_apps_get_earliest_purchase_unix_time(struct MistContext *context,
+                                                                   AppId app_id,
+                                                                   uint32_t *purchase_time);
+
 /**
  * Get earliest purchase time for the application in unix time
+ * # Safety
+ * purchase_time must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_apps_get_earliest_purchase_unix_time(AppId app_id, uint32_t *purchase_time);
 
+/**
+ * Variant of mist_steam_apps_get_installed_depots operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_apps_get_installed_depots
+ */
+MistResult mist_context_steam_apps_get_installed_depots(struct MistContext *context,
+                                                        AppId app_id,
+                                                        DepotId *depots,
+                                                        uint32_t depots_size,
+                                                        uint32_t *installed_depots);
+
 /**
  * Writes the installed depots into a pre-allocated array named depots, sets installed_depots to the amount of depots written
+ * # Safety
+ * installed_depots must be valid for writes
+ * depots must be valid for writes of depots_size elements
  * Returns MistResult
  */
 MistResult mist_steam_apps_get_installed_depots(AppId app_id,
@@ -565,21 +1217,66 @@ MistResult mist_steam_apps_get_installed_depots(AppId app_id,
                                                 uint32_t depots_size,
                                                 uint32_t *installed_depots);
 
-MistResult mist_steam_apps_get_launch_command_line(char *command_line, uint32_t command_line_size);
+/**
+ * Variant of mist_steam_apps_get_launch_command_line operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_apps_get_launch_command_line
+ */
+MistResult mist_context_steam_apps_get_launch_command_line(struct MistContext *context,
+                                                           char *command_line,
+                                                           uint32_t command_line_size);
+
+/**
+ * Copies the command line the game was launched with to `command_line` buffer of `command_line_size`
+ * # Safety
+ * command_line must be valid for writes of command_line_size bytes
+ * Returns MistResult
+ */
+MistResult mist_steam_apps_get_launch_command_line(char *command_line,
+                                                   uint32_t command_line_size);
+
+/**
+ * Variant of mist_steam_apps_get_launch_query_param operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_apps_get_launch_query_param
+ */
+MistResult mist_context_steam_apps_get_launch_query_param(struct MistContext *context,
+                                                          const char *key,
+                                                          const char **value);
 
 /**
  * Get the value of the launch query param, sets it to NULL if it does not exist
  * The value is only guaranteed to be valid til the next time the function is called
+ * # Safety
+ * value must be valid for writes
+ * key must be a NUL-terminated string
  * Returns MistResult
  */
 MistResult mist_steam_apps_get_launch_query_param(const char *key, const char **value);
 
+/**
+ * Variant of mist_steam_apps_install_dlc operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+MistResult mist_context_steam_apps_install_dlc(struct MistContext *context, AppId app_id);
+
 /**
  * Request the dlc for the app id to be installed
  * Returns MistResult
  */
 MistResult mist_steam_apps_install_dlc(AppId app_id);
 
+/**
+ * Variant of mist_steam_apps_mark_content_corrupt operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+MistResult mist_context_steam_apps_mark_content_corrupt(struct MistContext *context,
+                                                        bool missing_files_only);
+
 /**
  * Request a force verify of the game
  * Set missing files only to signal that a update might have been pushed
@@ -587,25 +1284,60 @@ MistResult mist_steam_apps_install_dlc(AppId app_id);
  */
 MistResult mist_steam_apps_mark_content_corrupt(bool missing_files_only);
 
+/**
+ * Variant of mist_steam_apps_uninstall_dlc operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+MistResult mist_context_steam_apps_uninstall_dlc(struct MistContext *context, AppId app_id);
+
 /**
  * Request the dlc for the app id to be uninstalled
  * Returns MistResult
  */
 MistResult mist_steam_apps_uninstall_dlc(AppId app_id);
 
+/**
+ * Variant of mist_steam_friends_clear_rich_presence operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+MistResult mist_context_steam_friends_clear_rich_presence(struct MistContext *context);
+
 /**
  * Clears the rich presence key/value store
  * Returns MistResult
  */
 MistResult mist_steam_friends_clear_rich_presence(void);
 
+/**
+ * Variant of mist_steam_friends_set_rich_presence operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_friends_set_rich_presence
+ */
+MistResult mist_context_steam_friends_set_rich_presence(struct MistContext *context,
+                                                        const char *key,
+                                                        const char *value);
+
 /**
  * Sets the rich presence key/value
  * Value can be NULL to clear the key
+ * # Safety
+ * key must be a NUL-terminated string, value must be NULL or a NUL-terminated string
  * Returns MistResult
  */
 MistResult mist_steam_friends_set_rich_presence(const char *key, const char *value);
 
+/**
+ * Variant of mist_steam_input_activate_action_set operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+MistResult mist_context_steam_input_activate_action_set(struct MistContext *context,
+                                                        MistInputHandle input_handle,
+                                                        MistInputActionSetHandle action_set_handle);
+
 /**
  * Makes the input controller use the action set
  * Returns MistResult
@@ -613,6 +1345,15 @@ MistResult mist_steam_friends_set_rich_presence(const char *key, const char *val
 MistResult mist_steam_input_activate_action_set(MistInputHandle input_handle,
                                                 MistInputActionSetHandle action_set_handle);
 
+/**
+ * Variant of mist_steam_input_activate_action_set_layer operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+MistResult mist_context_steam_input_activate_action_set_layer(struct MistContext *context,
+                                                              MistInputHandle input_handle,
+                                                              MistInputActionSetHandle action_set_layer_handle);
+
 /**
  * Makes the input controller use the action set layer
  * Returns MistResult
@@ -620,6 +1361,15 @@ MistResult mist_steam_input_activate_action_set(MistInputHandle input_handle,
 MistResult mist_steam_input_activate_action_set_layer(MistInputHandle input_handle,
                                                       MistInputActionSetHandle action_set_layer_handle);
 
+/**
+ * Variant of mist_steam_input_deactivate_action_set_layer operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+MistResult mist_context_steam_input_deactivate_action_set_layer(struct MistContext *context,
+                                                                MistInputHandle input_handle,
+                                                                MistInputActionSetHandle action_set_layer_handle);
+
 /**
  * Deactivates the input layer on the controller
  * Returns MistResult
@@ -627,50 +1377,173 @@ MistResult mist_steam_input_activate_action_set_layer(MistInputHandle input_hand
 MistResult mist_steam_input_deactivate_action_set_layer(MistInputHandle input_handle,
                                                         MistInputActionSetHandle action_set_layer_handle);
 
+/**
+ * Variant of mist_steam_input_deactivate_all_action_set_layers operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+MistResult mist_context_steam_input_deactivate_all_action_set_layers(struct MistContext *context,
+                                                                     MistInputHandle input_handle);
+
 /**
  * Deactivates the input layer on the controller
  * Returns MistResult
  */
 MistResult mist_steam_input_deactivate_all_action_set_layers(MistInputHandle input_handle);
 
+/**
+ * Variant of mist_steam_input_get_active_action_set_layers operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_get_active_action_set_layers
+ */
+MistResult mist_context_steam_input_get_active_action_set_layers(struct MistContext *context,
+                                                                 MistInputHandle input_handle,
+                                                                 MistInputActionSetHandle *handles_out,
+                                                                 uintptr_t *handles_count);
+
 /**
  * Get action set handles to the current action set layers for controller
  * Puts the handles in the handles_out parameter which needs to be a array of length MIST_STEAM_INPUT_MAX_COUNT
  * The count will be put in handles_count
+ * # Safety
+ * handles_out must be valid for writes of MIST_STEAM_INPUT_MAX_COUNT handles, handles_count must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_input_get_active_action_set_layers(MistInputHandle input_handle,
                                                          MistInputActionSetHandle *handles_out,
                                                          uintptr_t *handles_count);
 
+/**
+ * Variant of mist_steam_input_get_action_origin_from_xbox_origin operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_get_action_origin_from_xbox_origin
+ */
+MistResult mist_context_steam_input_get_action_origin_from_xbox_origin(struct MistContext *context,
+                                                                       MistInputHandle input_handle,
+                                                                       enum MistXboxOrigin xbox_origin,
+                                                                       MistInputActionOrigin *origin);
+
+/**
+ * Get the origin of the controller matching the Xbox origin
+ * Puts the origin into origin
+ * # Safety
+ * origin must be valid for writes
+ * Returns MistResult
+ */
+MistResult mist_steam_input_get_action_origin_from_xbox_origin(MistInputHandle input_handle,
+                                                               enum MistXboxOrigin xbox_origin,
+                                                               MistInputActionOrigin *origin);
+
+/**
+ * Variant of mist_steam_input_get_action_prompt operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_get_action_prompt
+ */
+MistResult mist_context_steam_input_get_action_prompt(struct MistContext *context,
+                                                      MistInputHandle input_handle,
+                                                      MistInputActionSetHandle action_set_handle,
+                                                      enum MistInputActionType action_type,
+                                                      uint64_t action_handle,
+                                                      enum MistSteamInputGlyphSize size,
+                                                      enum MistSteamInputGlyphStyle flags,
+                                                      struct MistInputActionPrompt *prompt);
+
+/**
+ * Get the primary origin of the analog or digital action with its localized string and png glyph path
+ * The prompt is cached until Steam Input loads a controller configuration or an action set or layer is changed through mist
+ * Puts the prompt into prompt
+ * # Safety
+ * prompt must be valid for writes
+ * Returns MistResult
+ */
+MistResult mist_steam_input_get_action_prompt(MistInputHandle input_handle,
+                                              MistInputActionSetHandle action_set_handle,
+                                              enum MistInputActionType action_type,
+                                              uint64_t action_handle,
+                                              enum MistSteamInputGlyphSize size,
+                                              enum MistSteamInputGlyphStyle flags,
+                                              struct MistInputActionPrompt *prompt);
+
+/**
+ * Variant of mist_steam_input_get_action_set_handle operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_get_action_set_handle
+ */
+MistResult mist_context_steam_input_get_action_set_handle(struct MistContext *context,
+                                                          const char *action_set_name,
+                                                          MistInputActionSetHandle *action_set_handle);
+
 /**
  * Get the action set from name
  * The action set is put in action_set_handle
+ * # Safety
+ * action_set_handle must be valid for writes
+ * action_set_name must be a NUL-terminated string
  * Returns MistResult
  */
 MistResult mist_steam_input_get_action_set_handle(const char *action_set_name,
                                                   MistInputActionSetHandle *action_set_handle);
 
+/**
+ * Variant of mist_steam_input_get_analog_action_data operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+struct MistInputAnalogActionData mist_context_steam_input_get_analog_action_data(struct MistContext *context,
+                                                                                 MistInputHandle input_handle,
+                                                                                 MistInputAnalogActionHandle analog_action_handle);
+
 /**
  * Get the analog action data for a analog action
- * NOTE: This method is NOT thread safe, only call it from the thread used for input init!
  * Returns MistInputAnalogActionData
  */
 struct MistInputAnalogActionData mist_steam_input_get_analog_action_data(MistInputHandle input_handle,
                                                                          MistInputAnalogActionHandle analog_action_handle);
 
+/**
+ * Variant of mist_steam_input_get_analog_action_handle operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_get_analog_action_handle
+ */
+MistResult mist_context_steam_input_get_analog_action_handle(struct MistContext *context,
+                                                             const char *action_name,
+                                                             MistInputAnalogActionHandle *analog_action_handle);
+
 /**
  * Get the analog action handle from name
  * The action handle is put in analog_action_handle
+ * # Safety
+ * analog_action_handle must be valid for writes
+ * action_name must be a NUL-terminated string
  * Returns MistResult
  */
 MistResult mist_steam_input_get_analog_action_handle(const char *action_name,
                                                      MistInputAnalogActionHandle *analog_action_handle);
 
+/**
+ * Variant of mist_steam_input_get_analog_action_origins operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_get_analog_action_origins
+ */
+MistResult mist_context_steam_input_get_analog_action_origins(struct MistContext *context,
+                                                              MistInputHandle input_handle,
+                                                              MistInputActionSetHandle action_set_handle,
+                                                              MistInputAnalogActionHandle analog_action_handle,
+                                                              MistInputHandle *origins_out,
+                                                              uintptr_t *origins_count);
+
 /**
  * Get all the origins for a digital action
  * Puts the origins in the origins_out parameter which needs to be a array of length MIST_STEAM_INPUT_MAX_ORIGINS
  * The count will be put in origins_count
+ * # Safety
+ * origins_out must be valid for writes of MIST_STEAM_INPUT_MAX_ORIGINS origins, origins_count must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_input_get_analog_action_origins(MistInputHandle input_handle,
@@ -679,51 +1552,147 @@ MistResult mist_steam_input_get_analog_action_origins(MistInputHandle input_hand
                                                       MistInputHandle *origins_out,
                                                       uintptr_t *origins_count);
 
+/**
+ * Variant of mist_steam_input_get_connected_controllers operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_get_connected_controllers
+ */
+MistResult mist_context_steam_input_get_connected_controllers(struct MistContext *context,
+                                                              MistInputHandle *handles_out,
+                                                              uintptr_t *handles_count);
+
 /**
  * Get the input handles for all controllers
  * Puts the handles in the handles_out parameter which needs to be a array of length MIST_STEAM_INPUT_MAX_COUNT
  * The count will be put in handles_count
+ * # Safety
+ * handles_out must be valid for writes of MIST_STEAM_INPUT_MAX_COUNT handles, handles_count must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_input_get_connected_controllers(MistInputHandle *handles_out,
                                                       uintptr_t *handles_count);
 
+/**
+ * Variant of mist_steam_input_get_controller_for_gamepad_index operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_get_controller_for_gamepad_index
+ */
+MistResult mist_context_steam_input_get_controller_for_gamepad_index(struct MistContext *context,
+                                                                     int index,
+                                                                     MistInputHandle *input_handle);
+
 /**
  * Get the input handle for a gamepad at index
  * Puts the input handle into input_handle
+ * # Safety
+ * input_handle must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_input_get_controller_for_gamepad_index(int index,
                                                              MistInputHandle *input_handle);
 
+/**
+ * Variant of mist_steam_input_get_current_action_set operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_get_current_action_set
+ */
+MistResult mist_context_steam_input_get_current_action_set(struct MistContext *context,
+                                                           MistInputHandle input_handle,
+                                                           MistInputActionSetHandle *input_action_set_handle);
+
 /**
  * Get the input handle for a gamepad at index
  * Puts the input handle into input_handle
+ * # Safety
+ * input_action_set_handle must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_input_get_current_action_set(MistInputHandle input_handle,
                                                    MistInputActionSetHandle *input_action_set_handle);
 
 /**
- * Get the digital action data for a digital action
- * NOTE: This method is NOT thread safe, only call it from the thread used for input init!
- * Returns MistInputDigitalActionData
+ * Variant of mist_steam_input_get_device_binding_revision operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_get_device_binding_revision
  */
-struct MistInputDigitalActionData mist_steam_input_get_digital_action_data(MistInputHandle input_handle,
-                                                                           MistInputDigitalActionHandle digital_action_handle);
+MistResult mist_context_steam_input_get_device_binding_revision(struct MistContext *context,
+                                                                MistInputHandle input_handle,
+                                                                int *major,
+                                                                int *minor,
+                                                                bool *has_binding);
 
 /**
- * Get digital action handle from name
- * The action handke is put in input_digital_action_handle
+ * Get the revision of the binding of the controller
+ * Puts the revision into major and minor, has_binding is false if the controller has no binding
+ * # Safety
+ * major, minor and has_binding must be valid for writes
  * Returns MistResult
  */
-MistResult mist_steam_input_get_digital_action_handle(const char *action_name,
-                                                      MistInputDigitalActionHandle *input_digital_action_handle);
+MistResult mist_steam_input_get_device_binding_revision(MistInputHandle input_handle,
+                                                        int *major,
+                                                        int *minor,
+                                                        bool *has_binding);
 
 /**
- * Get all the origins for a digital action
- * Puts the origins in the origins_out parameter which needs to be a array of length MIST_STEAM_INPUT_MAX_ORIGINS
- * The count will be put in origins_count
+ * Variant of mist_steam_input_get_digital_action_data operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+struct MistInputDigitalActionData mist_context_steam_input_get_digital_action_data(struct MistContext *context,
+                                                                                   MistInputHandle input_handle,
+                                                                                   MistInputDigitalActionHandle digital_action_handle);
+
+/**
+ * Get the digital action data for a digital action
+ * Returns MistInputDigitalActionData
+ */
+struct MistInputDigitalActionData mist_steam_input_get_digital_action_data(MistInputHandle input_handle,
+                                                                           MistInputDigitalActionHandle digital_action_handle);
+
+/**
+ * Variant of mist_steam_input_get_digital_action_handle operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_get_digital_action_handle
+ */
+MistResult mist_context_steam_input_get_digital_action_handle(struct MistContext *context,
+                                                              const char *action_name,
+                                                              MistInputDigitalActionHandle *input_digital_action_handle);
+
+/**
+ * Get digital action handle from name
+ * The action handke is put in input_digital_action_handle
+ * # Safety
+ * input_digital_action_handle must be valid for writes
+ * action_name must be a NUL-terminated string
+ * Returns MistResult
+ */
+MistResult mist_steam_input_get_digital_action_handle(const char *action_name,
+                                                      MistInputDigitalActionHandle *input_digital_action_handle);
+
+/**
+ * Variant of mist_steam_input_get_digital_action_origins operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_get_digital_action_origins
+ */
+MistResult mist_context_steam_input_get_digital_action_origins(struct MistContext *context,
+                                                               MistInputHandle input_handle,
+                                                               MistInputActionSetHandle action_set_handle,
+                                                               MistInputDigitalActionHandle digital_action_handle,
+                                                               MistInputHandle *origins_out,
+                                                               uintptr_t *origins_count);
+
+/**
+ * Get all the origins for a digital action
+ * Puts the origins in the origins_out parameter which needs to be a array of length MIST_STEAM_INPUT_MAX_ORIGINS
+ * The count will be put in origins_count
+ * # Safety
+ * origins_out must be valid for writes of MIST_STEAM_INPUT_MAX_ORIGINS origins, origins_count must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_input_get_digital_action_origins(MistInputHandle input_handle,
@@ -732,17 +1701,43 @@ MistResult mist_steam_input_get_digital_action_origins(MistInputHandle input_han
                                                        MistInputHandle *origins_out,
                                                        uintptr_t *origins_count);
 
+/**
+ * Variant of mist_steam_input_get_gamepad_index_for_controller operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_get_gamepad_index_for_controller
+ */
+MistResult mist_context_steam_input_get_gamepad_index_for_controller(struct MistContext *context,
+                                                                     MistInputHandle input_handle,
+                                                                     int *index);
+
 /**
  * Get the gamepad index from an input handle.
  * Puts the gamepad index into index
+ * # Safety
+ * index must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_input_get_gamepad_index_for_controller(MistInputHandle input_handle,
                                                              int *index);
 
+/**
+ * Variant of mist_steam_input_get_glyph_png_for_action_origin operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_get_glyph_png_for_action_origin
+ */
+MistResult mist_context_steam_input_get_glyph_png_for_action_origin(struct MistContext *context,
+                                                                    MistInputActionOrigin origin,
+                                                                    enum MistSteamInputGlyphSize size,
+                                                                    enum MistSteamInputGlyphStyle flags,
+                                                                    const char **path);
+
 /**
  * Get the gamepad index from an input handle.
  * Puts the gamepad index into index
+ * # Safety
+ * path must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_input_get_glyph_png_for_action_origin(MistInputActionOrigin origin,
@@ -750,42 +1745,212 @@ MistResult mist_steam_input_get_glyph_png_for_action_origin(MistInputActionOrigi
                                                             enum MistSteamInputGlyphStyle flags,
                                                             const char **path);
 
+/**
+ * Variant of mist_steam_input_get_glyph_svg_for_action_origin operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_get_glyph_svg_for_action_origin
+ */
+MistResult mist_context_steam_input_get_glyph_svg_for_action_origin(struct MistContext *context,
+                                                                    MistInputActionOrigin origin,
+                                                                    enum MistSteamInputGlyphStyle flags,
+                                                                    const char **path);
+
 /**
  * Get the gamepad index from an input handle.
  * Puts the gamepad index into index
+ * # Safety
+ * path must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_input_get_glyph_svg_for_action_origin(MistInputActionOrigin origin,
                                                             enum MistSteamInputGlyphStyle flags,
                                                             const char **path);
 
+/**
+ * Variant of mist_steam_input_get_input_type_for_handle operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_get_input_type_for_handle
+ */
+MistResult mist_context_steam_input_get_input_type_for_handle(struct MistContext *context,
+                                                              MistInputHandle input_handle,
+                                                              enum MistSteamInputType *input_type);
+
 /**
  * Get input type for controller
+ * # Safety
+ * input_type must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_input_get_input_type_for_handle(MistInputHandle input_handle,
                                                       enum MistSteamInputType *input_type);
 
+/**
+ * Variant of mist_steam_input_get_motion_data operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+struct MistInputMotionData mist_context_steam_input_get_motion_data(struct MistContext *context,
+                                                                    MistInputHandle input_handle);
+
 /**
  * Get the motion data for a gamepad
- * NOTE: This method is NOT thread safe, only call it from the thread used for input init!
  * Returns MistInputMotionData
  */
 struct MistInputMotionData mist_steam_input_get_motion_data(MistInputHandle input_handle);
 
+/**
+ * Variant of mist_steam_input_get_remote_play_session_id operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_get_remote_play_session_id
+ */
+MistResult mist_context_steam_input_get_remote_play_session_id(struct MistContext *context,
+                                                               MistInputHandle input_handle,
+                                                               uint32_t *session_id);
+
+/**
+ * Get the Remote Play session of the controller, 0 if it is not used through Remote Play
+ * Puts the session id into session_id
+ * # Safety
+ * session_id must be valid for writes
+ * Returns MistResult
+ */
+MistResult mist_steam_input_get_remote_play_session_id(MistInputHandle input_handle,
+                                                       uint32_t *session_id);
+
+/**
+ * Variant of mist_steam_input_get_session_input_configuration_settings operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_get_session_input_configuration_settings
+ */
+MistResult mist_context_steam_input_get_session_input_configuration_settings(struct MistContext *context,
+                                                                             uint16_t *settings);
+
+/**
+ * Get the controller types the user enabled Steam Input configurations for, for the session of a Remote Play Together host
+ * Puts the MistSteamInputConfigurationEnableType flags into settings
+ * # Safety
+ * settings must be valid for writes
+ * Returns MistResult
+ */
+MistResult mist_steam_input_get_session_input_configuration_settings(uint16_t *settings);
+
+/**
+ * Variant of mist_steam_input_get_string_for_action_origin operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_get_string_for_action_origin
+ */
+MistResult mist_context_steam_input_get_string_for_action_origin(struct MistContext *context,
+                                                                 MistInputActionOrigin origin,
+                                                                 const char **string);
+
 /**
  * Get the string from origin
+ * # Safety
+ * string must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_input_get_string_for_action_origin(MistInputActionOrigin origin,
                                                          const char **string);
 
+/**
+ * Variant of mist_steam_input_get_string_for_xbox_origin operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_get_string_for_xbox_origin
+ */
+MistResult mist_context_steam_input_get_string_for_xbox_origin(struct MistContext *context,
+                                                               enum MistXboxOrigin origin,
+                                                               const char **string);
+
+/**
+ * Get the string from the Xbox origin
+ * # Safety
+ * string must be valid for writes
+ * Returns MistResult
+ */
+MistResult mist_steam_input_get_string_for_xbox_origin(enum MistXboxOrigin origin,
+                                                       const char **string);
+
+/**
+ * Variant of mist_steam_input_init operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_init
+ */
+MistResult mist_context_steam_input_init(struct MistContext *context, bool *initialized);
+
 /**
  * Inits steam input
+ * # Safety
+ * initialized must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_input_init(bool *initialized);
 
+/**
+ * Variant of mist_steam_input_next_action_event operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_next_action_event
+ */
+MistResult mist_context_steam_input_next_action_event(struct MistContext *context,
+                                                      bool *has_event,
+                                                      struct MistInputActionEvent *event);
+
+/**
+ * Takes the oldest digital action press or release event, has_event is set to false once all are drained
+ * Events are recorded by the subprocess as they happen, so presses shorter than a frame are not lost
+ * The queue holds 256 events, newer events are dropped while it is full
+ * NOTE: This method is NOT thread safe, only call it from the thread used for input init!
+ * # Safety
+ * has_event and event must be valid for writes
+ * Returns MistResult
+ */
+MistResult mist_steam_input_next_action_event(bool *has_event,
+                                              struct MistInputActionEvent *event);
+
+/**
+ * Variant of mist_steam_input_prefetch_glyphs operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_prefetch_glyphs
+ */
+MistResult mist_context_steam_input_prefetch_glyphs(struct MistContext *context,
+                                                    const MistInputActionOrigin *origins,
+                                                    uintptr_t origin_count,
+                                                    const enum MistSteamInputGlyphSize *sizes,
+                                                    uintptr_t size_count,
+                                                    const enum MistSteamInputGlyphStyle *styles,
+                                                    uintptr_t style_count);
+
+/**
+ * Resolves the png glyphs of every combination of origin, size and style and the svg glyphs of every origin and style in one call
+ * The paths are cached, so later calls to mist_steam_input_get_glyph_png_for_action_origin and mist_steam_input_get_glyph_svg_for_action_origin do not block
+ * # Safety
+ * origins must be valid for reads of origin_count elements
+ * sizes must be valid for reads of size_count elements
+ * styles must be valid for reads of style_count elements
+ * Returns MistResult
+ */
+MistResult mist_steam_input_prefetch_glyphs(const MistInputActionOrigin *origins,
+                                            uintptr_t origin_count,
+                                            const enum MistSteamInputGlyphSize *sizes,
+                                            uintptr_t size_count,
+                                            const enum MistSteamInputGlyphStyle *styles,
+                                            uintptr_t style_count);
+
+/**
+ * Variant of mist_steam_input_run_frame operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+MistResult mist_context_steam_input_run_frame(struct MistContext *context);
+
 /**
  * Runs the frame
  * NOTE: This method is NOT thread safe, only call it from the thread used for input init!
@@ -793,11 +1958,38 @@ MistResult mist_steam_input_init(bool *initialized);
  */
 MistResult mist_steam_input_run_frame(void);
 
+/**
+ * Variant of mist_steam_input_set_input_action_manifest_file_path operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_set_input_action_manifest_file_path
+ */
+MistResult mist_context_steam_input_set_input_action_manifest_file_path(struct MistContext *context,
+                                                                        const char *path,
+                                                                        bool *set);
+
 /**
  * Manually sets the input action manifest
+ * Slots for all of its actions are allocated, otherwise only 16 analog and 128 digital actions can be requested
+ * # Safety
+ * set must be valid for writes
+ * path must be a NUL-terminated string
  * Returns MistResult
  */
-MistResult mist_steam_input_set_input_action_manifest_file_path(const char *path, bool *set);
+MistResult mist_steam_input_set_input_action_manifest_file_path(const char *path,
+                                                                bool *set);
+
+/**
+ * Variant of mist_steam_input_set_led_color operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+MistResult mist_context_steam_input_set_led_color(struct MistContext *context,
+                                                  MistInputHandle input_handle,
+                                                  uint8_t color_r,
+                                                  uint8_t color_g,
+                                                  uint8_t color_b,
+                                                  enum MistSteamControllerLEDFlag flags);
 
 /**
  * Sets the led color of a controller
@@ -809,18 +2001,49 @@ MistResult mist_steam_input_set_led_color(MistInputHandle input_handle,
                                           uint8_t color_b,
                                           enum MistSteamControllerLEDFlag flags);
 
+/**
+ * Variant of mist_steam_input_show_binding_panel operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_show_binding_panel
+ */
+MistResult mist_context_steam_input_show_binding_panel(struct MistContext *context,
+                                                       MistInputHandle input_handle,
+                                                       bool *overlay_shown);
+
 /**
  * Shows the steam input binding menu for a controller
+ * # Safety
+ * overlay_shown must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_input_show_binding_panel(MistInputHandle input_handle, bool *overlay_shown);
 
+/**
+ * Variant of mist_steam_input_shutdown operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_shutdown
+ */
+MistResult mist_context_steam_input_shutdown(struct MistContext *context, bool *shutdown);
+
 /**
  * Shuts down steam input
+ * # Safety
+ * shutdown must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_input_shutdown(bool *shutdown);
 
+/**
+ * Variant of mist_steam_input_stop_analog_action_momentum operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+MistResult mist_context_steam_input_stop_analog_action_momentum(struct MistContext *context,
+                                                                MistInputHandle input_handle,
+                                                                MistInputAnalogActionHandle action);
+
 /**
  * Stops the virtual analog momentum
  * Returns MistResult
@@ -828,6 +2051,16 @@ MistResult mist_steam_input_shutdown(bool *shutdown);
 MistResult mist_steam_input_stop_analog_action_momentum(MistInputHandle input_handle,
                                                         MistInputAnalogActionHandle action);
 
+/**
+ * Variant of mist_steam_input_trigger_vibration operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+MistResult mist_context_steam_input_trigger_vibration(struct MistContext *context,
+                                                      MistInputHandle input_handle,
+                                                      unsigned short left_speed,
+                                                      unsigned short right_speed);
+
 /**
  * Trigger vibration
  * Returns MistResult
@@ -836,6 +2069,18 @@ MistResult mist_steam_input_trigger_vibration(MistInputHandle input_handle,
                                               unsigned short left_speed,
                                               unsigned short right_speed);
 
+/**
+ * Variant of mist_steam_input_trigger_vibration_extended operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+MistResult mist_context_steam_input_trigger_vibration_extended(struct MistContext *context,
+                                                               MistInputHandle input_handle,
+                                                               unsigned short left_speed,
+                                                               unsigned short right_speed,
+                                                               unsigned short left_trigger_speed,
+                                                               unsigned short right_trigger_speed);
+
 /**
  * Trigger vibration extended
  * Returns MistResult
@@ -846,6 +2091,19 @@ MistResult mist_steam_input_trigger_vibration_extended(MistInputHandle input_han
                                                        unsigned short left_trigger_speed,
                                                        unsigned short right_trigger_speed);
 
+/**
+ * Variant of mist_steam_input_trigger_simple_haptic_event operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+MistResult mist_context_steam_input_trigger_simple_haptic_event(struct MistContext *context,
+                                                                MistInputHandle input_handle,
+                                                                enum MistControllerHapticLocation haptic_location,
+                                                                uint8_t intensity,
+                                                                char gain_db,
+                                                                uint8_t other_intensity,
+                                                                char other_gain_db);
+
 /**
  * Trigger haptic event
  * Returns MistResult
@@ -857,26 +2115,416 @@ MistResult mist_steam_input_trigger_simple_haptic_event(MistInputHandle input_ha
                                                         uint8_t other_intensity,
                                                         char other_gain_db);
 
+/**
+ * Variant of mist_steam_input_translate_action_origin operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_translate_action_origin
+ */
+MistResult mist_context_steam_input_translate_action_origin(struct MistContext *context,
+                                                            enum MistSteamInputType destination_input_type,
+                                                            MistInputActionOrigin source_origin,
+                                                            MistInputActionOrigin *translated_origin);
+
 /**
  * Translate origin to other input type origin
+ * # Safety
+ * translated_origin must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_input_translate_action_origin(enum MistSteamInputType destination_input_type,
                                                     MistInputActionOrigin source_origin,
                                                     MistInputActionOrigin *translated_origin);
 
+/**
+ * Variant of mist_steam_input_ex_query_gamepad operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+bool mist_context_steam_input_ex_query_gamepad(struct MistContext *context, int index);
+
 /**
  * Checks if gamepad at index is not unknown
  * Returns bool
  */
 bool mist_steam_input_ex_query_gamepad(int index);
 
+/**
+ * Variant of mist_steam_input_ex_get_gamepad_mapping operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_ex_get_gamepad_mapping
+ */
+void mist_context_steam_input_ex_get_gamepad_mapping(struct MistContext *context,
+                                                     MistInputHandle (*gamepad_mapping)[MIST_STEAM_INPUT_MAX_COUNT]);
+
 /**
  * Checks if gamepad at index is not unknown
+ * # Safety
+ * gamepad_mapping must be valid for writes
  * Returns bool
  */
 void mist_steam_input_ex_get_gamepad_mapping(MistInputHandle (*gamepad_mapping)[MIST_STEAM_INPUT_MAX_COUNT]);
 
+/**
+ * Variant of mist_steam_input_ex_next_slot_event operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_ex_next_slot_event
+ */
+MistResult mist_context_steam_input_ex_next_slot_event(struct MistContext *context,
+                                                       bool *has_event,
+                                                       struct MistInputSlotEvent *event);
+
+/**
+ * Takes the oldest change of the gamepad mapping, has_event is set to false once all are drained
+ * Every controller assigned to or removed from a slot of mist_steam_input_ex_get_gamepad_mapping is reported
+ * NOTE: This method is NOT thread safe, only call it from the thread used for input init!
+ * # Safety
+ * has_event and event must be valid for writes
+ * Returns MistResult
+ */
+MistResult mist_steam_input_ex_next_slot_event(bool *has_event,
+                                               struct MistInputSlotEvent *event);
+
+/**
+ * Variant of mist_steam_input_ex_get_snapshot_info operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_ex_get_snapshot_info
+ */
+MistResult mist_context_steam_input_ex_get_snapshot_info(struct MistContext *context,
+                                                         uint64_t *frame,
+                                                         uint64_t *timestamp_us);
+
+/**
+ * Gets the frame and time of the snapshot selected by mist_steam_input_run_frame
+ * The frame counts up with every snapshot taken by the subprocess, so gaps are snapshots the library skipped
+ * Both are 0 before the first snapshot, timestamp_us is in microseconds of a monotonic clock that starts with the subprocess
+ * # Safety
+ * frame and timestamp_us must be valid for writes
+ * Returns MistResult
+ */
+MistResult mist_steam_input_ex_get_snapshot_info(uint64_t *frame,
+                                                 uint64_t *timestamp_us);
+
+/**
+ * Variant of mist_steam_input_ex_start_recording operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_ex_start_recording
+ */
+MistResult mist_context_steam_input_ex_start_recording(struct MistContext *context,
+                                                       const char *path);
+
+/**
+ * Records the snapshot selected by every mist_steam_input_run_frame to the file at path, with the names of the actions
+ * A recording already running is stopped
+ * # Safety
+ * path must be a NUL-terminated string
+ * Returns MistResult
+ */
+MistResult mist_steam_input_ex_start_recording(const char *path);
+
+/**
+ * Variant of mist_steam_input_ex_stop_recording operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+MistResult mist_context_steam_input_ex_stop_recording(struct MistContext *context);
+
+/**
+ * Stops the recording started by mist_steam_input_ex_start_recording
+ * Returns MistResult
+ */
+MistResult mist_steam_input_ex_stop_recording(void);
+
+/**
+ * Variant of mist_steam_input_ex_start_replay operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_ex_start_replay
+ */
+MistResult mist_context_steam_input_ex_start_replay(struct MistContext *context, const char *path);
+
+/**
+ * Plays back a recording made with mist_steam_input_ex_start_recording, Steam Input and the subprocess are not needed
+ * Every mist_steam_input_run_frame selects the next recorded snapshot, the last one stays selected once the recording ended
+ * The action handle functions return the recorded handles, 0 for actions not in the recording
+ * # Safety
+ * path must be a NUL-terminated string
+ * Returns MistResult
+ */
+MistResult mist_steam_input_ex_start_replay(const char *path);
+
+/**
+ * Variant of mist_steam_input_ex_stop_replay operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+MistResult mist_context_steam_input_ex_stop_replay(struct MistContext *context);
+
+/**
+ * Stops the replay started by mist_steam_input_ex_start_replay, Steam Input is used again if it was initialized
+ * Returns MistResult
+ */
+MistResult mist_steam_input_ex_stop_replay(void);
+
+/**
+ * Variant of mist_steam_input_ex_is_replaying operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_ex_is_replaying
+ */
+MistResult mist_context_steam_input_ex_is_replaying(struct MistContext *context, bool *replaying);
+
+/**
+ * Checks if a replay is running and has recorded frames left
+ * # Safety
+ * replaying must be valid for writes
+ * Returns MistResult
+ */
+MistResult mist_steam_input_ex_is_replaying(bool *replaying);
+
+/**
+ * Variant of mist_steam_input_ex_set_glyph_cache_path operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_ex_set_glyph_cache_path
+ */
+MistResult mist_context_steam_input_ex_set_glyph_cache_path(struct MistContext *context,
+                                                            const char *path);
+
+/**
+ * Persists the glyph paths to the file at path, the paths in it are used if it was written by the same Steam client version
+ * The file is written after prefetching glyphs, by mist_steam_input_ex_flush_glyph_cache and by mist_subprocess_deinit
+ * # Safety
+ * path must be a NUL-terminated string
+ * Returns MistResult
+ */
+MistResult mist_steam_input_ex_set_glyph_cache_path(const char *path);
+
+/**
+ * Variant of mist_steam_input_ex_flush_glyph_cache operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+MistResult mist_context_steam_input_ex_flush_glyph_cache(struct MistContext *context);
+
+/**
+ * Writes the glyph paths resolved since the last write to the file set with mist_steam_input_ex_set_glyph_cache_path
+ * Returns MistResult
+ */
+MistResult mist_steam_input_ex_flush_glyph_cache(void);
+
+/**
+ * Variant of mist_steam_input_ex_get_glyph_rgba_for_action_origin operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_ex_get_glyph_rgba_for_action_origin
+ */
+MistResult mist_context_steam_input_ex_get_glyph_rgba_for_action_origin(struct MistContext *context,
+                                                                        MistInputActionOrigin origin,
+                                                                        enum MistSteamInputGlyphSize size,
+                                                                        enum MistSteamInputGlyphStyle flags,
+                                                                        uint32_t *width,
+                                                                        uint32_t *height,
+                                                                        const uint8_t **pixels);
+
+/**
+ * Decodes the png glyph of the origin to 8 bit RGBA, rows top to bottom without padding
+ * Puts a pointer to width * height * 4 bytes into pixels, which stays valid until the subprocess is deinitialized
+ * # Safety
+ * width, height and pixels must be valid for writes
+ * Returns MistResult
+ */
+MistResult mist_steam_input_ex_get_glyph_rgba_for_action_origin(MistInputActionOrigin origin,
+                                                                enum MistSteamInputGlyphSize size,
+                                                                enum MistSteamInputGlyphStyle flags,
+                                                                uint32_t *width,
+                                                                uint32_t *height,
+                                                                const uint8_t **pixels);
+
+/**
+ * Variant of mist_steam_input_ex_get_gyro_pointer_config operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_ex_get_gyro_pointer_config
+ */
+MistResult mist_context_steam_input_ex_get_gyro_pointer_config(struct MistContext *context,
+                                                               int index,
+                                                               struct MistGyroPointerConfig *config);
+
+/**
+ * Get the gyro pointer settings of the gamepad at index
+ * Puts the settings into config
+ * # Safety
+ * config must be valid for writes
+ * Returns MistResult
+ */
+MistResult mist_steam_input_ex_get_gyro_pointer_config(int index,
+                                                       struct MistGyroPointerConfig *config);
+
+/**
+ * Variant of mist_steam_input_ex_set_gyro_pointer_config operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_ex_set_gyro_pointer_config
+ */
+MistResult mist_context_steam_input_ex_set_gyro_pointer_config(struct MistContext *context,
+                                                               int index,
+                                                               const struct MistGyroPointerConfig *config);
+
+/**
+ * Set the gyro pointer settings of the gamepad at index
+ * # Safety
+ * config must be valid for reads
+ * Returns MistResult
+ */
+MistResult mist_steam_input_ex_set_gyro_pointer_config(int index,
+                                                       const struct MistGyroPointerConfig *config);
+
+/**
+ * Variant of mist_steam_input_ex_update_gyro_pointer operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_ex_update_gyro_pointer
+ */
+MistResult mist_context_steam_input_ex_update_gyro_pointer(struct MistContext *context,
+                                                           int index,
+                                                           struct MistGyroPointerState *pointer);
+
+/**
+ * Moves the gyro pointer of the gamepad at index by the rotation since the previous snapshot
+ * Call once after every mist_steam_input_run_frame, calls for the same snapshot do not move the pointer
+ * Puts the pointer into pointer
+ * # Safety
+ * pointer must be valid for writes
+ * Returns MistResult
+ */
+MistResult mist_steam_input_ex_update_gyro_pointer(int index,
+                                                   struct MistGyroPointerState *pointer);
+
+/**
+ * Variant of mist_steam_input_ex_recenter_gyro_pointer operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+MistResult mist_context_steam_input_ex_recenter_gyro_pointer(struct MistContext *context,
+                                                             int index);
+
+/**
+ * Moves the gyro pointer of the gamepad at index back to the center of the screen
+ * Returns MistResult
+ */
+MistResult mist_steam_input_ex_recenter_gyro_pointer(int index);
+
+/**
+ * Variant of mist_steam_input_ex_get_gravity operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_ex_get_gravity
+ */
+MistResult mist_context_steam_input_ex_get_gravity(struct MistContext *context,
+                                                   int index,
+                                                   struct MistVector3 *gravity);
+
+/**
+ * Get the direction of gravity relative to the gamepad at index as a unit vector, from its orientation quaternion
+ * Puts the vector into gravity
+ * # Safety
+ * gravity must be valid for writes
+ * Returns MistResult
+ */
+MistResult mist_steam_input_ex_get_gravity(int index,
+                                           struct MistVector3 *gravity);
+
+/**
+ * Converts a gyro pointer position to libretro pointer coordinates, positions past the edges are clamped
+ * Returns MistResult
+ */
+MistResult mist_steam_input_ex_pointer_to_libretro(float x,
+                                                   float y,
+                                                   int16_t *pointer_x,
+                                                   int16_t *pointer_y);
+
+/**
+ * Converts a gyro pointer position to libretro lightgun coordinates, offscreen is true past the edges of the screen
+ * Returns MistResult
+ */
+MistResult mist_steam_input_ex_pointer_to_libretro_lightgun(float x,
+                                                            float y,
+                                                            int16_t *screen_x,
+                                                            int16_t *screen_y,
+                                                            bool *offscreen);
+
+/**
+ * Variant of mist_steam_input_ex_play_haptic_pattern operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_ex_play_haptic_pattern
+ */
+MistResult mist_context_steam_input_ex_play_haptic_pattern(struct MistContext *context,
+                                                           MistInputHandle input_handle,
+                                                           const struct MistHapticKeyframe *keyframes,
+                                                           uintptr_t keyframe_count,
+                                                           uint32_t loops);
+
+/**
+ * Plays the haptic pattern on the controller from the subprocess, replacing the pattern playing on it
+ * The keyframes need increasing times and the last one a time above 0, which is the duration of the pattern
+ * The pattern is played loops times or until it is stopped if loops is 0
+ * Calls to mist_steam_input_trigger_vibration and mist_steam_input_trigger_vibration_extended stop the pattern
+ * # Safety
+ * keyframes must be valid for reads of keyframe_count elements
+ * Returns MistResult
+ */
+MistResult mist_steam_input_ex_play_haptic_pattern(MistInputHandle input_handle,
+                                                   const struct MistHapticKeyframe *keyframes,
+                                                   uintptr_t keyframe_count,
+                                                   uint32_t loops);
+
+/**
+ * Variant of mist_steam_input_ex_stop_haptic_pattern operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+MistResult mist_context_steam_input_ex_stop_haptic_pattern(struct MistContext *context,
+                                                           MistInputHandle input_handle);
+
+/**
+ * Stops the haptic pattern playing on the controller and its motors
+ * Returns MistResult
+ */
+MistResult mist_steam_input_ex_stop_haptic_pattern(MistInputHandle input_handle);
+
+/**
+ * Variant of mist_steam_input_ex_get_gamepad_remote_play_session_id operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_input_ex_get_gamepad_remote_play_session_id
+ */
+MistResult mist_context_steam_input_ex_get_gamepad_remote_play_session_id(struct MistContext *context,
+                                                                          int index,
+                                                                          uint32_t *session_id);
+
+/**
+ * Get the Remote Play session of the gamepad at index from the current snapshot, 0 if it is not used through Remote Play
+ * Puts the session id into session_id
+ * # Safety
+ * session_id must be valid for writes
+ * Returns MistResult
+ */
+MistResult mist_steam_input_ex_get_gamepad_remote_play_session_id(int index,
+                                                                  uint32_t *session_id);
+
+/**
+ * Variant of mist_steam_remote_storage_begin_file_write_batch operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+MistResult mist_context_steam_remote_storage_begin_file_write_batch(struct MistContext *context);
+
 /**
  * Begins a file write batch, use file write batches when saving files that gets stored in Steam Cloud.
  * Will error if there is already a file write batch operation in progress.
@@ -884,6 +2532,13 @@ void mist_steam_input_ex_get_gamepad_mapping(MistInputHandle (*gamepad_mapping)[
  */
 MistResult mist_steam_remote_storage_begin_file_write_batch(void);
 
+/**
+ * Variant of mist_steam_remote_storage_end_file_write_batch operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+MistResult mist_context_steam_remote_storage_end_file_write_batch(struct MistContext *context);
+
 /**
  * Ends a file write batch
  * Will error if there is no file write batch operation in progress.
@@ -891,69 +2546,193 @@ MistResult mist_steam_remote_storage_begin_file_write_batch(void);
  */
 MistResult mist_steam_remote_storage_end_file_write_batch(void);
 
+/**
+ * Variant of mist_steam_utils_get_appid operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_utils_get_appid
+ */
+MistResult mist_context_steam_utils_get_appid(struct MistContext *context, AppId *app_id);
+
 /**
  * Returns the appid of the running application in out ptr
+ * # Safety
+ * app_id must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_utils_get_appid(AppId *app_id);
 
+/**
+ * Variant of mist_steam_utils_get_current_battery_power operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_utils_get_current_battery_power
+ */
+MistResult mist_context_steam_utils_get_current_battery_power(struct MistContext *context,
+                                                              uint8_t *battery_power);
+
 /**
  * Returns the battery percentage in out ptr
+ * # Safety
+ * battery_power must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_utils_get_current_battery_power(uint8_t *battery_power);
 
+/**
+ * Variant of mist_steam_utils_get_entered_gamepad_text_input operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_utils_get_entered_gamepad_text_input
+ */
+MistResult mist_context_steam_utils_get_entered_gamepad_text_input(struct MistContext *context,
+                                                                   char *text,
+                                                                   uint32_t text_size);
+
 /**
  * Copies the entered gamepad text to `text` buffer of `text_size`
+ * # Safety
+ * text must be valid for writes of text_size bytes
  * Returns MistResult
  */
 MistResult mist_steam_utils_get_entered_gamepad_text_input(char *text, uint32_t text_size);
 
+/**
+ * Variant of mist_steam_utils_get_entered_gamepad_text_length operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_utils_get_entered_gamepad_text_length
+ */
+MistResult mist_context_steam_utils_get_entered_gamepad_text_length(struct MistContext *context,
+                                                                    uint32_t *length);
+
 /**
  * Sets the length out ptr to the length of the entered gamepad text
+ * # Safety
+ * length must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_utils_get_entered_gamepad_text_length(uint32_t *length);
 
+/**
+ * Variant of mist_steam_utils_is_overlay_enabled operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_utils_is_overlay_enabled
+ */
+MistResult mist_context_steam_utils_is_overlay_enabled(struct MistContext *context,
+                                                       bool *overlay_enabled);
+
 /**
  * Return if the Steam overlay is enabled in out ptr
+ * # Safety
+ * overlay_enabled must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_utils_is_overlay_enabled(bool *overlay_enabled);
 
+/**
+ * Variant of mist_steam_utils_is_steam_in_big_picture_mode operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_utils_is_steam_in_big_picture_mode
+ */
+MistResult mist_context_steam_utils_is_steam_in_big_picture_mode(struct MistContext *context,
+                                                                 bool *in_big_picture);
+
 /**
  * Return if Steam is running in Big Picture mode in out ptr
+ * # Safety
+ * in_big_picture must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_utils_is_steam_in_big_picture_mode(bool *in_big_picture);
 
+/**
+ * Variant of mist_steam_utils_is_steam_running_in_vr operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_utils_is_steam_running_in_vr
+ */
+MistResult mist_context_steam_utils_is_steam_running_in_vr(struct MistContext *context,
+                                                           bool *running_in_vr);
+
 /**
  * Return if Steam is running in VR mode in out ptr
+ * # Safety
+ * running_in_vr must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_utils_is_steam_running_in_vr(bool *running_in_vr);
 
+/**
+ * Variant of mist_steam_utils_is_vr_headset_streaming_enabled operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_utils_is_vr_headset_streaming_enabled
+ */
+MistResult mist_context_steam_utils_is_vr_headset_streaming_enabled(struct MistContext *context,
+                                                                    bool *vr_streaming_enabled);
+
 /**
  * Return if VR view streaming via Steam Remote Play is enabled in the out ptr
+ * # Safety
+ * vr_streaming_enabled must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_utils_is_vr_headset_streaming_enabled(bool *vr_streaming_enabled);
 
+/**
+ * Variant of mist_steam_utils_is_steam_running_on_steam_deck operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_utils_is_steam_running_on_steam_deck
+ */
+MistResult mist_context_steam_utils_is_steam_running_on_steam_deck(struct MistContext *context,
+                                                                   bool *on_deck);
+
 /**
  * Return if steam is running on a steam deck in the out ptr
+ * # Safety
+ * on_deck must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_utils_is_steam_running_on_steam_deck(bool *on_deck);
 
+/**
+ * Variant of mist_steam_utils_set_vr_headset_streaming_enabled operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+MistResult mist_context_steam_utils_set_vr_headset_streaming_enabled(struct MistContext *context,
+                                                                     bool enabled);
+
 /**
  * Set if Steam Remote Play should be avaliable for HMD content
  * Returns MistResult
  */
 MistResult mist_steam_utils_set_vr_headset_streaming_enabled(bool enabled);
 
+/**
+ * Variant of mist_steam_utils_show_gamepad_text_input operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_utils_show_gamepad_text_input
+ */
+MistResult mist_context_steam_utils_show_gamepad_text_input(struct MistContext *context,
+                                                            enum MistGamepadTextInputMode input_mode,
+                                                            enum MistGamepadTextInputLineMode line_input_mode,
+                                                            const char *description,
+                                                            uint32_t char_max,
+                                                            const char *existing_text,
+                                                            bool *shown);
+
 /**
  * Showing a floating keyboard over the game and sends input directly to it
  * Returns if shown in out ptr
+ * # Safety
+ * shown must be valid for writes
+ * description and existing_text must be NUL-terminated strings
  * Returns MistResult
  */
 MistResult mist_steam_utils_show_gamepad_text_input(enum MistGamepadTextInputMode input_mode,
@@ -963,9 +2742,25 @@ MistResult mist_steam_utils_show_gamepad_text_input(enum MistGamepadTextInputMod
                                                     const char *existing_text,
                                                     bool *shown);
 
+/**
+ * Variant of mist_steam_utils_show_floating_gamepad_text_input operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ * The other arguments have the requirements of mist_steam_utils_show_floating_gamepad_text_input
+ */
+MistResult mist_context_steam_utils_show_floating_gamepad_text_input(struct MistContext *context,
+                                                                     enum MistFloatingGamepadTextInputMode keyboard_mode,
+                                                                     int text_field_x_position,
+                                                                     int text_field_y_position,
+                                                                     int text_field_width,
+                                                                     int text_field_height,
+                                                                     bool *shown);
+
 /**
  * Showing a floating keyboard over the game and sends input directly to it
  * Returns if shown in out ptr
+ * # Safety
+ * shown must be valid for writes
  * Returns MistResult
  */
 MistResult mist_steam_utils_show_floating_gamepad_text_input(enum MistFloatingGamepadTextInputMode keyboard_mode,
@@ -975,12 +2770,27 @@ MistResult mist_steam_utils_show_floating_gamepad_text_input(enum MistFloatingGa
                                                              int text_field_height,
                                                              bool *shown);
 
+/**
+ * Variant of mist_steam_utils_set_game_launcher_mode operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+MistResult mist_context_steam_utils_set_game_launcher_mode(struct MistContext *context,
+                                                           bool launcher_mode);
+
 /**
  * Make Steam translate controller input into mouse/kb for UI that does not support controllers
  * Returns MistResult
  */
 MistResult mist_steam_utils_set_game_launcher_mode(bool launcher_mode);
 
+/**
+ * Variant of mist_steam_utils_start_vr_dashboard operating on the context
+ * # Safety
+ * context must come from mist_context_create and not be destroyed yet
+ */
+MistResult mist_context_steam_utils_start_vr_dashboard(struct MistContext *context);
+
 /**
  * Open the VR dashboard
  * Returns MistResult
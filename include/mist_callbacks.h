@@ -2,6 +2,23 @@ typedef struct MistCallbackDlcInstalled {
   AppId app_id;
 } MistCallbackDlcInstalled;
 
+typedef struct MistCallbackSteamInputDeviceConnected {
+  MistInputHandle input_handle;
+} MistCallbackSteamInputDeviceConnected;
+
+typedef struct MistCallbackSteamInputDeviceDisconnected {
+  MistInputHandle input_handle;
+} MistCallbackSteamInputDeviceDisconnected;
+
+typedef struct MistCallbackSteamInputConfigurationLoaded {
+  AppId app_id;
+  MistInputHandle input_handle;
+  uint32_t major_revision;
+  uint32_t minor_revision;
+  bool uses_steam_input_api;
+  bool uses_gamepad_api;
+} MistCallbackSteamInputConfigurationLoaded;
+
 typedef struct MistCallbackRemoteStorageLocalFileChange {
 
 } MistCallbackRemoteStorageLocalFileChange;
@@ -25,6 +42,9 @@ typedef struct MistCallbackSteamShutdown {
 
 enum {
   MistCallback_DlcInstalled = 1005,
+  MistCallback_SteamInputDeviceConnected = 2801,
+  MistCallback_SteamInputDeviceDisconnected = 2802,
+  MistCallback_SteamInputConfigurationLoaded = 2803,
   MistCallback_RemoteStorageLocalFileChange = 1333,
   MistCallback_GamepadTextInputDismissed = 714,
   MistCallback_FloatingGamepadTextInputDismissed = 738,
@@ -11,6 +11,7 @@ enum {
 enum {
 	MistError_InternalError = 0,
 	MistError_Timeout,
+	MistError_Panic,
 	MistError_SubprocessLost = 10,
 	MistError_SubprocessNotInitialized,
 	MistError_SubprocessAlreadyInitialized,
@@ -18,7 +19,12 @@ enum {
 	MistError_SubprocessInitializationError,
 	MistError_SubprocessUnkillable,
 	MistError_SubprocessNotFound,
-	MistError_InvalidString = 20
+	MistError_SubprocessHung,
+	MistError_InvalidString = 20,
+	MistError_NullPointer,
+	MistError_InvalidIndex,
+	MistError_InvalidContext,
+	MistError_InvalidInterface
 };
 
 enum {
@@ -31,7 +37,13 @@ enum {
 
 enum {
 	SteamInputError_NotInitialized = 0,
-	SteamInputError_ShmemError
+	SteamInputError_ShmemError,
+	SteamInputError_ShmemVersionMismatch,
+	SteamInputError_TooManyActions,
+	SteamInputError_RecordingError,
+	SteamInputError_GlyphError,
+	SteamInputError_InvalidHapticPattern,
+	SteamInputError_SnapshotUnavailable
 };
 
 enum {
@@ -1,42 +1,58 @@
 use std::{ffi::CStr, os::raw::c_char};
 
-use crate::result::{MistResult, Success};
+use crate::{
+    lib_subprocess::MistContext,
+    result::{MistResult, Success},
+};
 
-/// Clears the rich presence key/value store
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_friends_clear_rich_presence() -> MistResult {
-    let mut subprocess = get_subprocess!();
-    unwrap_client_result!(subprocess.client().steam_friends().clear_rich_presence());
+mist_context_export! {
+    /// Clears the rich presence key/value store
+    /// Returns MistResult
+    fn steam_friends_clear_rich_presence(context) -> MistResult {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
 
-    Success
+            let mut subprocess = get_subprocess!(context);
+            unwrap_client_result!(subprocess.client().steam_friends().clear_rich_presence());
+
+            Success
+        })
+    }
 }
 
-/// Sets the rich presence key/value
-/// Value can be NULL to clear the key
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_friends_set_rich_presence(
-    key: *const c_char,
-    value: *const c_char,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
-
-    let key = unsafe { CStr::from_ptr(key) }.to_string_lossy().to_string();
-    let value = if value.is_null() {
-        None
-    } else {
-        Some(
-            unsafe { CStr::from_ptr(value) }
-                .to_string_lossy()
-                .to_string(),
-        )
-    };
-
-    unwrap_client_result!(subprocess
-        .client()
-        .steam_friends()
-        .set_rich_presence(key, value));
-
-    Success
+mist_context_export! {
+    /// Sets the rich presence key/value
+    /// Value can be NULL to clear the key
+    /// # Safety
+    /// key must be a NUL-terminated string, value must be NULL or a NUL-terminated string
+    /// Returns MistResult
+    unsafe fn steam_friends_set_rich_presence(
+        context,
+        key: *const c_char,
+        value: *const c_char,
+    ) -> MistResult {
+        mist_export!([context, key], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            let key = unsafe { CStr::from_ptr(key) }.to_string_lossy().to_string();
+            let value = if value.is_null() {
+                None
+            } else {
+                Some(
+                    unsafe { CStr::from_ptr(value) }
+                        .to_string_lossy()
+                        .to_string(),
+                )
+            };
+
+            unwrap_client_result!(subprocess
+                .client()
+                .steam_friends()
+                .set_rich_presence(key, value));
+
+            Success
+        })
+    }
 }
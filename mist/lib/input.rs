@@ -1,69 +1,352 @@
 use shared_memory::{Shmem, ShmemConf};
 use std::{
+    collections::{HashMap, VecDeque},
     ffi::CStr,
     os::raw::{c_char, c_int, c_ushort},
-    sync::atomic::Ordering,
+    sync::atomic::{fence, Ordering},
+    time::{Duration, Instant},
 };
 
 use crate::{
     consts::*,
-    lib_subprocess::MistSubprocess,
-    result::{Error, MistResult, SteamInputError, Success},
+    glyph_cache::MistGlyphCache,
+    input_recorder::{MistInputRecorder, MistInputReplay},
+    lib_subprocess::{MistContext, MistSubprocess},
+    motion::{to_libretro_lightgun, to_libretro_pointer},
+    result::{Error, MistError, MistResult, SteamInputError, Success},
     types::*,
 };
 
-static mut MIST_INPUT_BUFFER: usize = 0;
-static mut MIST_INPUT_STATE: *mut MistInputStateBuffered = std::ptr::null_mut();
+// The memory stays mapped while the returned guard is alive
+macro_rules! get_input_state_memory {
+    ($context:expr) => {{
+        match MistInputStateGuard::new($context) {
+            Some(guard) => guard,
+            None => {
+                crate::mist_log_error("Steam Input has not been initialized");
+                return crate::MistExportResult::from_error(Error::SteamInput(
+                    SteamInputError::NotInitialized,
+                ));
+            }
+        }
+    }};
+}
+
+// Counts as a reader of the memory input_state points to, so it is not freed when it is replaced meanwhile
+pub(crate) struct MistInputStateGuard<'a> {
+    context: &'a MistContext,
+    memory: MistInputStateMemory,
+}
+
+impl<'a> MistInputStateGuard<'a> {
+    fn new(context: &'a MistContext) -> Option<MistInputStateGuard<'a>> {
+        // Counted before loading, so memory retired after the reader started is kept
+        context.input_readers.fetch_add(1, Ordering::SeqCst);
+
+        let state = context.input_state.load(Ordering::SeqCst);
+        if state.is_null() {
+            context.input_readers.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+
+        Some(MistInputStateGuard {
+            context,
+            memory: unsafe { MistInputStateMemory::from_ptr(state) },
+        })
+    }
+}
+
+impl std::ops::Deref for MistInputStateGuard<'_> {
+    type Target = MistInputStateMemory;
+
+    fn deref(&self) -> &MistInputStateMemory {
+        &self.memory
+    }
+}
 
-macro_rules! get_input_state {
-    () => {
-        &unsafe { &*MIST_INPUT_STATE }.buffer[unsafe { MIST_INPUT_BUFFER }]
+impl Drop for MistInputStateGuard<'_> {
+    fn drop(&mut self) {
+        self.context.input_readers.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+// Writing a snapshot takes microseconds, a write taking longer means the subprocess stopped while writing
+const SNAPSHOT_READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+macro_rules! unwrap_snapshot {
+    ($res:expr) => {
+        match $res {
+            Ok(value) => value,
+            Err(err) => return crate::MistExportResult::from_error(err),
+        }
     };
 }
 
+// Reads from the snapshot selected by the last run frame
+// Retries while the subprocess writes to the buffer, which only happens if another thread ran the frame meanwhile
+pub(crate) fn read_snapshot<T>(
+    context: &MistContext,
+    memory: MistInputStateMemory,
+    read: impl Fn(&MistInputState, usize) -> T,
+) -> Result<T, Error> {
+    let state = unsafe { &*memory.header() };
+    let mut deadline = None;
+
+    loop {
+        let buffer = context.input_buffer.load(Ordering::Acquire);
+        let input_state = &state.buffer[buffer];
+
+        let sequence = input_state.sequence.load(Ordering::Acquire);
+        if sequence % 2 == 0 {
+            let value = read(input_state, buffer);
+
+            fence(Ordering::Acquire);
+            if input_state.sequence.load(Ordering::Relaxed) == sequence {
+                return Ok(value);
+            }
+        }
+
+        let now = Instant::now();
+        if now >= *deadline.get_or_insert(now + SNAPSHOT_READ_TIMEOUT) {
+            crate::mist_log_error(
+                "Timeout waiting for the subprocess to finish writing the snapshot",
+            );
+            return Err(Error::SteamInput(SteamInputError::SnapshotUnavailable));
+        }
+
+        std::hint::spin_loop();
+    }
+}
+
+// Events the library drained from the rings of shared memory replaced by a resize, returned before the events of the new rings
+#[derive(Default)]
+pub struct MistInputEventBacklog {
+    action_events: VecDeque<MistInputActionEvent>,
+    slot_events: VecDeque<MistInputSlotEvent>,
+}
+
+impl MistInputEventBacklog {
+    // The subprocess stopped writing to the rings when it replaced the memory, so they can be drained completely
+    fn drain(&mut self, memory: MistInputStateMemory) {
+        let state = unsafe { &*memory.header() };
+
+        while let Some(event) = state.action_events.pop() {
+            self.action_events.push_back(event);
+        }
+        while let Some(event) = state.slot_events.pop() {
+            self.slot_events.push_back(event);
+        }
+    }
+}
+
+// Checks the gamepad index of the ex functions, logging invalid ones
+fn gamepad_index(index: c_int) -> Option<usize> {
+    let index = usize::try_from(index)
+        .ok()
+        .filter(|index| *index < MIST_MAX_GAMEPADS);
+
+    if index.is_none() {
+        crate::mist_log_error("Invalid gamepad index");
+    }
+
+    index
+}
+
+// A failed write only loses the persisted copy, so it is logged instead of failing the call
+pub(crate) fn flush_glyph_cache(glyphs: &mut MistGlyphCache) {
+    if let Err(err) = glyphs.flush() {
+        crate::mist_log_error(&format!("Error writing the glyph cache: {}", err));
+    }
+}
+
+// Resolves the glyphs missing from the cache in a single call, origins without a glyph are left out
+fn resolve_glyphs(
+    subprocess: &mut MistSubprocess,
+    png: Vec<MistGlyphPngKey>,
+    svg: Vec<MistGlyphSvgKey>,
+) -> MistResult {
+    let glyphs = &subprocess.state().glyphs;
+    let png: Vec<_> = png
+        .into_iter()
+        .filter(|key| glyphs.png(key).is_none())
+        .collect();
+    let svg: Vec<_> = svg
+        .into_iter()
+        .filter(|key| glyphs.svg(key).is_none())
+        .collect();
+
+    if png.is_empty() && svg.is_empty() {
+        return Success;
+    }
+
+    let (png_paths, svg_paths) = unwrap_client_result!(subprocess
+        .client()
+        .steam_input()
+        .get_glyph_paths(png.clone(), svg.clone()));
+
+    let glyphs = &mut subprocess.state_mut().glyphs;
+    for (key, path) in png.into_iter().zip(png_paths) {
+        if let Some(path) = path {
+            glyphs.insert_png(key, path);
+        }
+    }
+    for (key, path) in svg.into_iter().zip(svg_paths) {
+        if let Some(path) = path {
+            glyphs.insert_svg(key, path);
+        }
+    }
+
+    Success
+}
+
+type MistInputActionPromptKey = (
+    MistInputHandle,
+    MistInputActionSetHandle,
+    MistInputActionType,
+    u64,
+    MistSteamInputGlyphSize,
+    MistSteamInputGlyphStyle,
+);
+
 pub struct MistSteamInputClient {
     shmem: Shmem,
+    // The buffer the library reads from, kept here as a replay replaces the one in the context
+    front_buffer: usize,
+    // Names the action handles were requested with, stored in recordings
+    analog_action_names: HashMap<MistInputAnalogActionHandle, String>,
+    digital_action_names: HashMap<MistInputDigitalActionHandle, String>,
+    recorder: Option<MistInputRecorder>,
+    // Primary origins of actions, cleared when the configuration generation changes or an action set is activated
+    action_prompts: HashMap<MistInputActionPromptKey, MistInputActionOrigin>,
+    configuration_generation: u32,
 }
 
 impl MistSteamInputClient {
-    fn setup(subprocess: &mut MistSubprocess, os_id: String) -> MistResult {
-        let shmem = match ShmemConf::new()
-            .os_id(&os_id)
-            .size(std::mem::size_of::<MistInputStateBuffered>())
-            .open()
-        {
+    fn setup(context: &MistContext, subprocess: &mut MistSubprocess, os_id: String) -> MistResult {
+        let shmem = match ShmemConf::new().os_id(&os_id).open() {
             Ok(shmem) => shmem,
             Err(_err) => {
                 return Error::SteamInput(SteamInputError::ShmemError).into();
             }
         };
 
-        unsafe {
-            MIST_INPUT_STATE = shmem.as_ptr() as *mut _;
+        // The header carries the version and size, so a mismatched subprocess can not cause out of bounds reads
+        if shmem.len() < std::mem::size_of::<MistInputStateBuffered>() {
+            return Error::SteamInput(SteamInputError::ShmemError).into();
+        }
+
+        let state = unsafe { &*(shmem.as_ptr() as *const MistInputStateBuffered) };
+        if state.version != MIST_INPUT_STATE_VERSION {
+            crate::mist_log_error(&format!(
+                "Steam Input shared memory version {} does not match {}",
+                state.version, MIST_INPUT_STATE_VERSION
+            ));
+            return Error::SteamInput(SteamInputError::ShmemVersionMismatch).into();
+        }
+        if state.size != state.layout().size() as u64 || state.size > shmem.len() as u64 {
+            crate::mist_log_error("Invalid Steam Input shared memory size");
+            return Error::SteamInput(SteamInputError::ShmemError).into();
+        }
+
+        // The shared memory is set up again when it was resized for the manifest
+        let previous = subprocess.state_mut().input_client.take();
+        if let Some(previous) = &previous {
+            context.input_event_backlog.lock().drain(previous.memory());
+        }
+
+        // A replay keeps its memory until it is stopped
+        if context.input_replay.lock().is_none() {
+            context.input_buffer.store(0, Ordering::Release);
+            context
+                .input_state
+                .store(shmem.as_ptr() as *mut _, Ordering::SeqCst);
         }
 
-        subprocess.state_mut().input_client = Some(MistSteamInputClient { shmem });
+        let (analog_action_names, digital_action_names, recorder) = match previous {
+            Some(previous) => {
+                context.retire_input_memory(previous.shmem);
+
+                (
+                    previous.analog_action_names,
+                    previous.digital_action_names,
+                    previous.recorder,
+                )
+            }
+            None => Default::default(),
+        };
+
+        subprocess.state_mut().input_client = Some(MistSteamInputClient {
+            shmem,
+            front_buffer: 0,
+            analog_action_names,
+            digital_action_names,
+            recorder,
+            action_prompts: HashMap::new(),
+            configuration_generation: state.configuration_generation.load(Ordering::Acquire),
+        });
 
         Success
     }
 
-    fn run_frame(&mut self) -> MistResult {
-        let state = unsafe { &mut *(self.shmem.as_ptr() as *mut MistInputStateBuffered) };
+    fn memory(&self) -> MistInputStateMemory {
+        unsafe { MistInputStateMemory::from_ptr(self.shmem.as_ptr() as *mut _) }
+    }
 
-        let next_library_cursor =
-            (state.library_cursor.load(Ordering::Relaxed) + 1) % MIST_INPUT_STATE_BUFFER_SIZE;
+    /// Hands the shared memory to the context, which unmaps it once no export reads it anymore
+    /// input_state has to point elsewhere before
+    pub fn retire(self, context: &MistContext) {
+        context.retire_input_memory(self.shmem);
+    }
 
-        if next_library_cursor == state.subprocess_cursor.load(Ordering::Relaxed) {
-            return Success;
-        }
+    // Drops the cached prompts if Steam Input loaded a configuration since they were cached
+    fn action_prompts(&mut self) -> &mut HashMap<MistInputActionPromptKey, MistInputActionOrigin> {
+        let configuration_generation = unsafe { &*self.memory().header() }
+            .configuration_generation
+            .load(Ordering::Acquire);
 
-        unsafe {
-            MIST_INPUT_BUFFER = next_library_cursor as _;
+        if configuration_generation != self.configuration_generation {
+            self.configuration_generation = configuration_generation;
+            self.action_prompts.clear();
         }
 
-        state
-            .library_cursor
-            .store(next_library_cursor, Ordering::Relaxed);
+        &mut self.action_prompts
+    }
+
+    fn run_frame(&mut self, context: &MistContext) -> MistResult {
+        let state = unsafe { &*(self.shmem.as_ptr() as *const MistInputStateBuffered) };
+
+        // Keep the current snapshot until the subprocess published a newer one
+        let selected = if state.latest.load(Ordering::Relaxed) & MIST_INPUT_STATE_LATEST_FRESH == 0
+        {
+            None
+        } else {
+            // Hand the current buffer back to the subprocess in exchange for the latest one
+            let latest = state.latest.swap(self.front_buffer as u8, Ordering::AcqRel);
+            self.front_buffer = (latest & !MIST_INPUT_STATE_LATEST_FRESH) as usize;
+
+            context
+                .input_buffer
+                .store(self.front_buffer, Ordering::Release);
+
+            Some(self.front_buffer)
+        };
+
+        let memory = self.memory();
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(err) = recorder.record(
+                context,
+                memory,
+                selected,
+                &self.analog_action_names,
+                &self.digital_action_names,
+            ) {
+                crate::mist_log_error(&format!(
+                    "Error writing the input recording, recording stopped: {}",
+                    err
+                ));
+                self.recorder = None;
+            }
+        }
 
         Success
     }
@@ -72,731 +355,2027 @@ impl MistSteamInputClient {
 // The raw pointer inside shmem *should* be safe
 unsafe impl Send for MistSteamInputClient {}
 
-/// Makes the input controller use the action set
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_input_activate_action_set(
-    input_handle: MistInputHandle,
-    action_set_handle: MistInputActionSetHandle,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
+// Action sets and layers change the origins of the actions
+fn invalidate_action_prompts(subprocess: &mut MistSubprocess) {
+    if let Some(input_client) = &mut subprocess.state_mut().input_client {
+        input_client.action_prompts.clear();
+    }
+}
 
-    unwrap_client_result!(subprocess
-        .client()
-        .steam_input()
-        .activate_action_set(input_handle, action_set_handle));
+mist_context_export! {
+    /// Makes the input controller use the action set
+    /// Returns MistResult
+    fn steam_input_activate_action_set(
+        context,
+        input_handle: MistInputHandle,
+        action_set_handle: MistInputActionSetHandle,
+    ) -> MistResult {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
 
-    Success
-}
+            let mut subprocess = get_subprocess!(context);
 
-/// Makes the input controller use the action set layer
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_input_activate_action_set_layer(
-    input_handle: MistInputHandle,
-    action_set_layer_handle: MistInputActionSetHandle,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
+            unwrap_client_result!(subprocess
+                .client()
+                .steam_input()
+                .activate_action_set(input_handle, action_set_handle));
 
-    unwrap_client_result!(subprocess
-        .client()
-        .steam_input()
-        .activate_action_set_layer(input_handle, action_set_layer_handle));
+            invalidate_action_prompts(&mut subprocess);
 
-    Success
+            Success
+        })
+    }
 }
 
-/// Deactivates the input layer on the controller
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_input_deactivate_action_set_layer(
-    input_handle: MistInputHandle,
-    action_set_layer_handle: MistInputActionSetHandle,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
+mist_context_export! {
+    /// Makes the input controller use the action set layer
+    /// Returns MistResult
+    fn steam_input_activate_action_set_layer(
+        context,
+        input_handle: MistInputHandle,
+        action_set_layer_handle: MistInputActionSetHandle,
+    ) -> MistResult {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
 
-    unwrap_client_result!(subprocess
-        .client()
-        .steam_input()
-        .deactivate_action_set_layer(input_handle, action_set_layer_handle));
+            let mut subprocess = get_subprocess!(context);
 
-    Success
-}
+            unwrap_client_result!(subprocess
+                .client()
+                .steam_input()
+                .activate_action_set_layer(input_handle, action_set_layer_handle));
 
-/// Deactivates the input layer on the controller
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_input_deactivate_all_action_set_layers(
-    input_handle: MistInputHandle,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
-
-    unwrap_client_result!(subprocess
-        .client()
-        .steam_input()
-        .deactivate_all_action_set_layers(input_handle));
+            invalidate_action_prompts(&mut subprocess);
 
-    Success
+            Success
+        })
+    }
 }
 
-/// Get action set handles to the current action set layers for controller
-/// Puts the handles in the handles_out parameter which needs to be a array of length MIST_STEAM_INPUT_MAX_COUNT
-/// The count will be put in handles_count
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_input_get_active_action_set_layers(
-    input_handle: MistInputHandle,
-    handles_out: *mut MistInputActionSetHandle,
-    handles_count: *mut usize,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
+mist_context_export! {
+    /// Deactivates the input layer on the controller
+    /// Returns MistResult
+    fn steam_input_deactivate_action_set_layer(
+        context,
+        input_handle: MistInputHandle,
+        action_set_layer_handle: MistInputActionSetHandle,
+    ) -> MistResult {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
 
-    let handles = unwrap_client_result!(subprocess
-        .client()
-        .steam_input()
-        .get_active_action_set_layers(input_handle));
-    let handles_len = handles.len().min(MIST_STEAM_INPUT_MAX_COUNT);
+            let mut subprocess = get_subprocess!(context);
 
-    for i in 0..handles_len {
-        let handle_out = unsafe { handles_out.add(i) };
+            unwrap_client_result!(subprocess
+                .client()
+                .steam_input()
+                .deactivate_action_set_layer(input_handle, action_set_layer_handle));
 
-        unsafe {
-            *handle_out = handles[i];
-        }
+            invalidate_action_prompts(&mut subprocess);
+
+            Success
+        })
     }
+}
 
-    unsafe { *handles_count = handles_len };
+mist_context_export! {
+    /// Deactivates the input layer on the controller
+    /// Returns MistResult
+    fn steam_input_deactivate_all_action_set_layers(
+        context,
+        input_handle: MistInputHandle,
+    ) -> MistResult {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
 
-    Success
+            let mut subprocess = get_subprocess!(context);
+
+            unwrap_client_result!(subprocess
+                .client()
+                .steam_input()
+                .deactivate_all_action_set_layers(input_handle));
+
+            invalidate_action_prompts(&mut subprocess);
+
+            Success
+        })
+    }
 }
 
-/// Get the action set from name
-/// The action set is put in action_set_handle
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_input_get_action_set_handle(
-    action_set_name: *const c_char,
-    action_set_handle: *mut MistInputActionSetHandle,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
+mist_context_export! {
+    /// Get action set handles to the current action set layers for controller
+    /// Puts the handles in the handles_out parameter which needs to be a array of length MIST_STEAM_INPUT_MAX_COUNT
+    /// The count will be put in handles_count
+    /// # Safety
+    /// handles_out must be valid for writes of MIST_STEAM_INPUT_MAX_COUNT handles, handles_count must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_input_get_active_action_set_layers(
+        context,
+        input_handle: MistInputHandle,
+        handles_out: *mut MistInputActionSetHandle,
+        handles_count: *mut usize,
+    ) -> MistResult {
+        mist_export!([context, handles_out, handles_count], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            let handles = unwrap_client_result!(subprocess
+                .client()
+                .steam_input()
+                .get_active_action_set_layers(input_handle));
+            let handles_len = handles.len().min(MIST_STEAM_INPUT_MAX_COUNT);
+
+            for (i, handle) in handles.iter().take(handles_len).enumerate() {
+                let handle_out = unsafe { handles_out.add(i) };
+
+                unsafe {
+                    *handle_out = *handle;
+                }
+            }
 
-    let action_set_name = unsafe { CStr::from_ptr(action_set_name) }
-        .to_string_lossy()
-        .to_string();
+            unsafe { *handles_count = handles_len };
 
-    let handle = unwrap_client_result!(subprocess
-        .client()
-        .steam_input()
-        .get_action_set_handle(action_set_name));
+            Success
+        })
+    }
+}
 
-    unsafe {
-        *action_set_handle = handle;
+mist_context_export! {
+    /// Get the origin of the controller matching the Xbox origin
+    /// Puts the origin into origin
+    /// # Safety
+    /// origin must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_input_get_action_origin_from_xbox_origin(
+        context,
+        input_handle: MistInputHandle,
+        xbox_origin: MistXboxOrigin,
+        origin: *mut MistInputActionOrigin,
+    ) -> MistResult {
+        mist_export!([context, origin], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            let action_origin = unwrap_client_result!(subprocess
+                .client()
+                .steam_input()
+                .get_action_origin_from_xbox_origin(input_handle, xbox_origin));
+
+            unsafe { *origin = action_origin };
+
+            Success
+        })
     }
+}
 
-    Success
+mist_context_export! {
+    /// Get the primary origin of the analog or digital action with its localized string and png glyph path
+    /// The prompt is cached until Steam Input loads a controller configuration or an action set or layer is changed through mist
+    /// Puts the prompt into prompt
+    /// # Safety
+    /// prompt must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_input_get_action_prompt(
+        context,
+        input_handle: MistInputHandle,
+        action_set_handle: MistInputActionSetHandle,
+        action_type: MistInputActionType,
+        action_handle: u64,
+        size: MistSteamInputGlyphSize,
+        flags: MistSteamInputGlyphStyle,
+        prompt: *mut MistInputActionPrompt,
+    ) -> MistResult {
+        mist_export!([context, prompt], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            let key = (
+                input_handle,
+                action_set_handle,
+                action_type,
+                action_handle,
+                size,
+                flags,
+            );
+
+            let cached = match &mut subprocess.state_mut().input_client {
+                Some(input_client) => input_client.action_prompts().get(&key).copied(),
+                None => return Error::SteamInput(SteamInputError::NotInitialized).into(),
+            };
+
+            let origin = match cached {
+                Some(origin) => origin,
+                None => {
+                    let (origin, origin_string, glyph_path) =
+                        unwrap_client_result!(subprocess.client().steam_input().get_action_prompt(
+                            input_handle,
+                            action_set_handle,
+                            action_type,
+                            action_handle,
+                            size,
+                            flags
+                        ));
+
+                    let state = subprocess.state_mut();
+                    if let Some(origin_string) = origin_string {
+                        state.origin_strings.entry(origin).or_insert(origin_string);
+                    }
+                    if let Some(glyph_path) = glyph_path {
+                        state.glyphs.insert_png((origin, size, flags), glyph_path);
+                    }
+                    if let Some(input_client) = &mut state.input_client {
+                        input_client.action_prompts().insert(key, origin);
+                    }
+
+                    origin
+                }
+            };
+
+            let state = subprocess.state();
+            let bound = origin != MistInputActionOrigin::None;
+            let origin_string = state
+                .origin_strings
+                .get(&origin)
+                .filter(|_| bound)
+                .map_or(std::ptr::null(), |origin_string| origin_string.as_ptr());
+            let glyph_path = state
+                .glyphs
+                .png(&(origin, size, flags))
+                .filter(|_| bound)
+                .map_or(std::ptr::null(), |glyph_path| glyph_path.as_ptr());
+
+            unsafe {
+                *prompt = MistInputActionPrompt {
+                    origin,
+                    origin_string,
+                    glyph_path,
+                }
+            };
+
+            Success
+        })
+    }
 }
 
-/// Get the analog action data for a analog action
-/// NOTE: This method is NOT thread safe, only call it from the thread used for input init!
-/// Returns MistInputAnalogActionData
-#[no_mangle]
-pub extern "C" fn mist_steam_input_get_analog_action_data(
-    input_handle: MistInputHandle,
-    analog_action_handle: MistInputAnalogActionHandle,
-) -> MistInputAnalogActionData {
-    let input_state = get_input_state!();
-
-    for i in 0..MIST_MAX_GAMEPADS {
-        let pad = &input_state.gamepads[i];
-        if pad.input_handle == input_handle {
-            return pad.analog_action_data[analog_action_handle as usize];
-        }
+mist_context_export! {
+    /// Get the action set from name
+    /// The action set is put in action_set_handle
+    /// # Safety
+    /// action_set_handle must be valid for writes
+    /// action_set_name must be a NUL-terminated string
+    /// Returns MistResult
+    unsafe fn steam_input_get_action_set_handle(
+        context,
+        action_set_name: *const c_char,
+        action_set_handle: *mut MistInputActionSetHandle,
+    ) -> MistResult {
+        mist_export!([context, action_set_name, action_set_handle], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            let action_set_name = unsafe { CStr::from_ptr(action_set_name) }
+                .to_string_lossy()
+                .to_string();
+
+            let handle = unwrap_client_result!(subprocess
+                .client()
+                .steam_input()
+                .get_action_set_handle(action_set_name));
+
+            unsafe {
+                *action_set_handle = handle;
+            }
+
+            Success
+        })
     }
+}
 
-    MistInputAnalogActionData::default()
+mist_context_export! {
+    /// Get the analog action data for a analog action
+    /// Returns MistInputAnalogActionData
+    fn steam_input_get_analog_action_data(
+        context,
+        input_handle: MistInputHandle,
+        analog_action_handle: MistInputAnalogActionHandle,
+    ) -> MistInputAnalogActionData {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
+
+            let memory = get_input_state_memory!(context);
+
+            let slot = match memory.analog_action_slot(analog_action_handle) {
+                Some(slot) => slot,
+                None => {
+                    crate::mist_log_error("Unknown analog action handle, request it with mist_steam_input_get_analog_action_handle first");
+                    return crate::MistExportResult::from_error(Error::Mist(
+                        MistError::InvalidIndex,
+                    ));
+                }
+            };
+
+            unwrap_snapshot!(read_snapshot(context, *memory, |input_state, buffer| {
+                match input_state
+                    .gamepads
+                    .iter()
+                    .position(|pad| pad.input_handle == input_handle)
+                {
+                    Some(i) => unsafe { (*memory.analog_action_data(buffer, i))[slot] },
+                    None => MistInputAnalogActionData::default(),
+                }
+            }))
+        })
+    }
 }
 
-/// Get the analog action handle from name
-/// The action handle is put in analog_action_handle
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_input_get_analog_action_handle(
-    action_name: *const c_char,
-    analog_action_handle: *mut MistInputAnalogActionHandle,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
+mist_context_export! {
+    /// Get the analog action handle from name
+    /// The action handle is put in analog_action_handle
+    /// # Safety
+    /// analog_action_handle must be valid for writes
+    /// action_name must be a NUL-terminated string
+    /// Returns MistResult
+    unsafe fn steam_input_get_analog_action_handle(
+        context,
+        action_name: *const c_char,
+        analog_action_handle: *mut MistInputAnalogActionHandle,
+    ) -> MistResult {
+        mist_export!([context, action_name, analog_action_handle], {
+            let context = MistContext::from_ptr(context);
+
+            let action_name = unsafe { CStr::from_ptr(action_name) }
+                .to_string_lossy()
+                .to_string();
+
+            if let Some(replay) = context.input_replay.lock().as_ref() {
+                unsafe { *analog_action_handle = replay.analog_action_handle(&action_name) };
+                return Success;
+            }
 
-    let action_name = unsafe { CStr::from_ptr(action_name) }
-        .to_string_lossy()
-        .to_string();
+            let mut subprocess = get_subprocess!(context);
 
-    let handle = unwrap_client_result!(subprocess
-        .client()
-        .steam_input()
-        .get_analog_action_handle(action_name));
+            let handle = unwrap_client_result!(subprocess
+                .client()
+                .steam_input()
+                .get_analog_action_handle(action_name.clone()));
 
-    unsafe {
-        *analog_action_handle = handle;
+            if let Some(input_client) = &mut subprocess.state_mut().input_client {
+                input_client.analog_action_names.insert(handle, action_name);
+            }
+
+            unsafe {
+                *analog_action_handle = handle;
+            }
+
+            Success
+        })
     }
+}
 
-    Success
+mist_context_export! {
+    /// Get all the origins for a digital action
+    /// Puts the origins in the origins_out parameter which needs to be a array of length MIST_STEAM_INPUT_MAX_ORIGINS
+    /// The count will be put in origins_count
+    /// # Safety
+    /// origins_out must be valid for writes of MIST_STEAM_INPUT_MAX_ORIGINS origins, origins_count must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_input_get_analog_action_origins(
+        context,
+        input_handle: MistInputHandle,
+        action_set_handle: MistInputActionSetHandle,
+        analog_action_handle: MistInputAnalogActionHandle,
+        origins_out: *mut MistInputHandle,
+        origins_count: *mut usize,
+    ) -> MistResult {
+        mist_export!([context, origins_out, origins_count], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            let origins = unwrap_client_result!(subprocess
+                .client()
+                .steam_input()
+                .get_analog_action_origins(input_handle, action_set_handle, analog_action_handle));
+            let origins_len = origins.len().min(MIST_STEAM_INPUT_MAX_ORIGINS);
+
+            for (i, origin) in origins.iter().take(origins_len).enumerate() {
+                let origin_out = unsafe { origins_out.add(i) };
+
+                unsafe {
+                    *origin_out = *origin as _;
+                }
+            }
+
+            unsafe { *origins_count = origins_len };
+
+            Success
+        })
+    }
 }
 
-/// Get all the origins for a digital action
-/// Puts the origins in the origins_out parameter which needs to be a array of length MIST_STEAM_INPUT_MAX_ORIGINS
-/// The count will be put in origins_count
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_input_get_analog_action_origins(
-    input_handle: MistInputHandle,
-    action_set_handle: MistInputActionSetHandle,
-    analog_action_handle: MistInputAnalogActionHandle,
-    origins_out: *mut MistInputHandle,
-    origins_count: *mut usize,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
+mist_context_export! {
+    /// Get the input handles for all controllers
+    /// Puts the handles in the handles_out parameter which needs to be a array of length MIST_STEAM_INPUT_MAX_COUNT
+    /// The count will be put in handles_count
+    /// # Safety
+    /// handles_out must be valid for writes of MIST_STEAM_INPUT_MAX_COUNT handles, handles_count must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_input_get_connected_controllers(
+        context,
+        handles_out: *mut MistInputHandle,
+        handles_count: *mut usize,
+    ) -> MistResult {
+        mist_export!([context, handles_out, handles_count], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            let handles = unwrap_client_result!(subprocess
+                .client()
+                .steam_input()
+                .get_connected_controllers());
+            let handles_len = handles.len().min(MIST_STEAM_INPUT_MAX_COUNT);
+
+            for (i, handle) in handles.iter().take(handles_len).enumerate() {
+                let handle_out = unsafe { handles_out.add(i) };
+
+                unsafe {
+                    *handle_out = *handle;
+                }
+            }
 
-    let origins = unwrap_client_result!(subprocess
-        .client()
-        .steam_input()
-        .get_analog_action_origins(input_handle, action_set_handle, analog_action_handle));
-    let origins_len = origins.len().min(MIST_STEAM_INPUT_MAX_ORIGINS);
+            unsafe { *handles_count = handles_len };
 
-    for i in 0..origins_len {
-        let origin_out = unsafe { origins_out.add(i) };
+            Success
+        })
+    }
+}
 
-        unsafe {
-            *origin_out = origins[i] as _;
-        }
+mist_context_export! {
+    /// Get the input handle for a gamepad at index
+    /// Puts the input handle into input_handle
+    /// # Safety
+    /// input_handle must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_input_get_controller_for_gamepad_index(
+        context,
+        index: c_int,
+        input_handle: *mut MistInputHandle,
+    ) -> MistResult {
+        mist_export!([context, input_handle], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            let handle = unwrap_client_result!(subprocess
+                .client()
+                .steam_input()
+                .get_controller_for_gamepad_index(index));
+
+            unsafe { *input_handle = handle };
+
+            Success
+        })
     }
+}
+
+mist_context_export! {
+    /// Get the input handle for a gamepad at index
+    /// Puts the input handle into input_handle
+    /// # Safety
+    /// input_action_set_handle must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_input_get_current_action_set(
+        context,
+        input_handle: MistInputHandle,
+        input_action_set_handle: *mut MistInputActionSetHandle,
+    ) -> MistResult {
+        mist_export!([context, input_action_set_handle], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            let handle = unwrap_client_result!(subprocess
+                .client()
+                .steam_input()
+                .get_current_action_set(input_handle));
+
+            unsafe { *input_action_set_handle = handle };
+
+            Success
+        })
+    }
+}
 
-    unsafe { *origins_count = origins_len };
+mist_context_export! {
+    /// Get the revision of the binding of the controller
+    /// Puts the revision into major and minor, has_binding is false if the controller has no binding
+    /// # Safety
+    /// major, minor and has_binding must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_input_get_device_binding_revision(
+        context,
+        input_handle: MistInputHandle,
+        major: *mut c_int,
+        minor: *mut c_int,
+        has_binding: *mut bool,
+    ) -> MistResult {
+        mist_export!([context, major, minor, has_binding], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            let revision = unwrap_client_result!(subprocess
+                .client()
+                .steam_input()
+                .get_device_binding_revision(input_handle));
+
+            let (major_revision, minor_revision) = revision.unwrap_or_default();
+            unsafe {
+                *major = major_revision;
+                *minor = minor_revision;
+                *has_binding = revision.is_some();
+            }
 
-    Success
+            Success
+        })
+    }
 }
 
-/// Get the input handles for all controllers
-/// Puts the handles in the handles_out parameter which needs to be a array of length MIST_STEAM_INPUT_MAX_COUNT
-/// The count will be put in handles_count
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_input_get_connected_controllers(
-    handles_out: *mut MistInputHandle,
-    handles_count: *mut usize,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
+mist_context_export! {
+    /// Get the digital action data for a digital action
+    /// Returns MistInputDigitalActionData
+    fn steam_input_get_digital_action_data(
+        context,
+        input_handle: MistInputHandle,
+        digital_action_handle: MistInputDigitalActionHandle,
+    ) -> MistInputDigitalActionData {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
+
+            let memory = get_input_state_memory!(context);
+
+            let slot = match memory.digital_action_slot(digital_action_handle) {
+                Some(slot) => slot,
+                None => {
+                    crate::mist_log_error("Unknown digital action handle, request it with mist_steam_input_get_digital_action_handle first");
+                    return crate::MistExportResult::from_error(Error::Mist(
+                        MistError::InvalidIndex,
+                    ));
+                }
+            };
+
+            unwrap_snapshot!(read_snapshot(context, *memory, |input_state, buffer| {
+                match input_state
+                    .gamepads
+                    .iter()
+                    .position(|pad| pad.input_handle == input_handle)
+                {
+                    Some(i) => unsafe { (*memory.digital_action_data(buffer, i))[slot] },
+                    None => MistInputDigitalActionData::default(),
+                }
+            }))
+        })
+    }
+}
 
-    let handles = unwrap_client_result!(subprocess
-        .client()
-        .steam_input()
-        .get_connected_controllers());
-    let handles_len = handles.len().min(MIST_STEAM_INPUT_MAX_COUNT);
+mist_context_export! {
+    /// Get digital action handle from name
+    /// The action handke is put in input_digital_action_handle
+    /// # Safety
+    /// input_digital_action_handle must be valid for writes
+    /// action_name must be a NUL-terminated string
+    /// Returns MistResult
+    unsafe fn steam_input_get_digital_action_handle(
+        context,
+        action_name: *const c_char,
+        input_digital_action_handle: *mut MistInputDigitalActionHandle,
+    ) -> MistResult {
+        mist_export!([context, action_name, input_digital_action_handle], {
+            let context = MistContext::from_ptr(context);
+
+            let action_name = unsafe { CStr::from_ptr(action_name) }
+                .to_string_lossy()
+                .to_string();
+
+            if let Some(replay) = context.input_replay.lock().as_ref() {
+                unsafe {
+                    *input_digital_action_handle = replay.digital_action_handle(&action_name)
+                };
+                return Success;
+            }
 
-    for i in 0..handles_len {
-        let handle_out = unsafe { handles_out.add(i) };
+            let mut subprocess = get_subprocess!(context);
 
-        unsafe {
-            *handle_out = handles[i];
-        }
-    }
+            let handle = unwrap_client_result!(subprocess
+                .client()
+                .steam_input()
+                .get_digital_action_handle(action_name.clone()));
 
-    unsafe { *handles_count = handles_len };
+            if let Some(input_client) = &mut subprocess.state_mut().input_client {
+                input_client
+                    .digital_action_names
+                    .insert(handle, action_name);
+            }
 
-    Success
+            unsafe {
+                *input_digital_action_handle = handle;
+            }
+
+            Success
+        })
+    }
 }
 
-/// Get the input handle for a gamepad at index
-/// Puts the input handle into input_handle
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_input_get_controller_for_gamepad_index(
-    index: c_int,
-    input_handle: *mut MistInputHandle,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
+mist_context_export! {
+    /// Get all the origins for a digital action
+    /// Puts the origins in the origins_out parameter which needs to be a array of length MIST_STEAM_INPUT_MAX_ORIGINS
+    /// The count will be put in origins_count
+    /// # Safety
+    /// origins_out must be valid for writes of MIST_STEAM_INPUT_MAX_ORIGINS origins, origins_count must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_input_get_digital_action_origins(
+        context,
+        input_handle: MistInputHandle,
+        action_set_handle: MistInputActionSetHandle,
+        digital_action_handle: MistInputDigitalActionHandle,
+        origins_out: *mut MistInputHandle,
+        origins_count: *mut usize,
+    ) -> MistResult {
+        mist_export!([context, origins_out, origins_count], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            let origins = unwrap_client_result!(subprocess
+                .client()
+                .steam_input()
+                .get_digital_action_origins(
+                    input_handle,
+                    action_set_handle,
+                    digital_action_handle
+                ));
+            let origins_len = origins.len().min(MIST_STEAM_INPUT_MAX_ORIGINS);
+
+            for (i, origin) in origins.iter().take(origins_len).enumerate() {
+                let origin_out = unsafe { origins_out.add(i) };
+
+                unsafe {
+                    *origin_out = *origin as _;
+                }
+            }
 
-    let handle = unwrap_client_result!(subprocess
-        .client()
-        .steam_input()
-        .get_controller_for_gamepad_index(index));
+            unsafe { *origins_count = origins_len };
 
-    unsafe { *input_handle = handle };
+            Success
+        })
+    }
+}
 
-    Success
+mist_context_export! {
+    /// Get the gamepad index from an input handle.
+    /// Puts the gamepad index into index
+    /// # Safety
+    /// index must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_input_get_gamepad_index_for_controller(
+        context,
+        input_handle: MistInputHandle,
+        index: *mut c_int,
+    ) -> MistResult {
+        mist_export!([context, index], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            let handle = unwrap_client_result!(subprocess
+                .client()
+                .steam_input()
+                .get_gamepad_index_for_controller(input_handle));
+
+            unsafe { *index = handle };
+
+            Success
+        })
+    }
 }
 
-/// Get the input handle for a gamepad at index
-/// Puts the input handle into input_handle
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_input_get_current_action_set(
-    input_handle: MistInputHandle,
-    input_action_set_handle: *mut MistInputActionSetHandle,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
+mist_context_export! {
+    /// Get the gamepad index from an input handle.
+    /// Puts the gamepad index into index
+    /// # Safety
+    /// path must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_input_get_glyph_png_for_action_origin(
+        context,
+        origin: MistInputActionOrigin,
+        size: MistSteamInputGlyphSize,
+        flags: MistSteamInputGlyphStyle,
+        path: *mut *const c_char,
+    ) -> MistResult {
+        mist_export!([context, path], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            if let Some(path_cstr) = subprocess.state().glyphs.png(&(origin, size, flags)) {
+                unsafe { *path = path_cstr.as_ptr() };
+                return Success;
+            }
 
-    let handle = unwrap_client_result!(subprocess
-        .client()
-        .steam_input()
-        .get_current_action_set(input_handle));
+            let path_cstr = unwrap_client_result!(subprocess
+                .client()
+                .steam_input()
+                .get_glyph_png_for_action_origin(origin, size, flags));
 
-    unsafe { *input_action_set_handle = handle };
+            let glyphs = &mut subprocess.state_mut().glyphs;
+            glyphs.insert_png((origin, size, flags), path_cstr);
 
-    Success
-}
+            unsafe { *path = glyphs.png(&(origin, size, flags)).unwrap().as_ptr() };
 
-/// Get the digital action data for a digital action
-/// NOTE: This method is NOT thread safe, only call it from the thread used for input init!
-/// Returns MistInputDigitalActionData
-#[no_mangle]
-pub extern "C" fn mist_steam_input_get_digital_action_data(
-    input_handle: MistInputHandle,
-    digital_action_handle: MistInputDigitalActionHandle,
-) -> MistInputDigitalActionData {
-    let input_state = get_input_state!();
-
-    for i in 0..MIST_MAX_GAMEPADS {
-        let pad = &input_state.gamepads[i];
-        if pad.input_handle == input_handle {
-            return pad.digital_action_data[digital_action_handle as usize];
-        }
+            Success
+        })
     }
-
-    MistInputDigitalActionData::default()
 }
 
-/// Get digital action handle from name
-/// The action handke is put in input_digital_action_handle
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_input_get_digital_action_handle(
-    action_name: *const c_char,
-    input_digital_action_handle: *mut MistInputDigitalActionHandle,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
+mist_context_export! {
+    /// Get the gamepad index from an input handle.
+    /// Puts the gamepad index into index
+    /// # Safety
+    /// path must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_input_get_glyph_svg_for_action_origin(
+        context,
+        origin: MistInputActionOrigin,
+        flags: MistSteamInputGlyphStyle,
+        path: *mut *const c_char,
+    ) -> MistResult {
+        mist_export!([context, path], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            if let Some(path_cstr) = subprocess.state().glyphs.svg(&(origin, flags)) {
+                unsafe { *path = path_cstr.as_ptr() };
+                return Success;
+            }
 
-    let action_name = unsafe { CStr::from_ptr(action_name) }
-        .to_string_lossy()
-        .to_string();
+            let path_cstr = unwrap_client_result!(subprocess
+                .client()
+                .steam_input()
+                .get_glyph_svg_for_action_origin(origin, flags));
 
-    let handle = unwrap_client_result!(subprocess
-        .client()
-        .steam_input()
-        .get_digital_action_handle(action_name));
+            let glyphs = &mut subprocess.state_mut().glyphs;
+            glyphs.insert_svg((origin, flags), path_cstr);
 
-    unsafe {
-        *input_digital_action_handle = handle;
-    }
+            unsafe { *path = glyphs.svg(&(origin, flags)).unwrap().as_ptr() };
 
-    Success
+            Success
+        })
+    }
 }
 
-/// Get all the origins for a digital action
-/// Puts the origins in the origins_out parameter which needs to be a array of length MIST_STEAM_INPUT_MAX_ORIGINS
-/// The count will be put in origins_count
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_input_get_digital_action_origins(
-    input_handle: MistInputHandle,
-    action_set_handle: MistInputActionSetHandle,
-    digital_action_handle: MistInputDigitalActionHandle,
-    origins_out: *mut MistInputHandle,
-    origins_count: *mut usize,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
-
-    let origins = unwrap_client_result!(subprocess
-        .client()
-        .steam_input()
-        .get_digital_action_origins(input_handle, action_set_handle, digital_action_handle));
-    let origins_len = origins.len().min(MIST_STEAM_INPUT_MAX_ORIGINS);
+mist_context_export! {
+    /// Get input type for controller
+    /// # Safety
+    /// input_type must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_input_get_input_type_for_handle(
+        context,
+        input_handle: MistInputHandle,
+        input_type: *mut MistSteamInputType,
+    ) -> MistResult {
+        mist_export!([context, input_type], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            let input_ty = unwrap_client_result!(subprocess
+                .client()
+                .steam_input()
+                .get_input_type_for_handle(input_handle));
+
+            unsafe { *input_type = input_ty };
+
+            Success
+        })
+    }
+}
 
-    for i in 0..origins_len {
-        let origin_out = unsafe { origins_out.add(i) };
+mist_context_export! {
+    /// Get the motion data for a gamepad
+    /// Returns MistInputMotionData
+    fn steam_input_get_motion_data(context, input_handle: MistInputHandle) -> MistInputMotionData {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
+
+            let memory = get_input_state_memory!(context);
+
+            unwrap_snapshot!(read_snapshot(context, *memory, |input_state, _| {
+                input_state
+                    .gamepads
+                    .iter()
+                    .find(|pad| pad.input_handle == input_handle)
+                    .map(|pad| pad.motion_data)
+                    .unwrap_or_default()
+            }))
+        })
+    }
+}
 
-        unsafe {
-            *origin_out = origins[i] as _;
-        }
+mist_context_export! {
+    /// Get the Remote Play session of the controller, 0 if it is not used through Remote Play
+    /// Puts the session id into session_id
+    /// # Safety
+    /// session_id must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_input_get_remote_play_session_id(
+        context,
+        input_handle: MistInputHandle,
+        session_id: *mut u32,
+    ) -> MistResult {
+        mist_export!([context, session_id], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            let id = unwrap_client_result!(subprocess
+                .client()
+                .steam_input()
+                .get_remote_play_session_id(input_handle));
+
+            unsafe { *session_id = id };
+
+            Success
+        })
     }
+}
 
-    unsafe { *origins_count = origins_len };
+mist_context_export! {
+    /// Get the controller types the user enabled Steam Input configurations for, for the session of a Remote Play Together host
+    /// Puts the MistSteamInputConfigurationEnableType flags into settings
+    /// # Safety
+    /// settings must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_input_get_session_input_configuration_settings(
+        context,
+        settings: *mut u16,
+    ) -> MistResult {
+        mist_export!([context, settings], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            let enabled_types = unwrap_client_result!(subprocess
+                .client()
+                .steam_input()
+                .get_session_input_configuration_settings());
+
+            unsafe { *settings = enabled_types };
+
+            Success
+        })
+    }
+}
 
-    Success
+mist_context_export! {
+    /// Get the string from origin
+    /// # Safety
+    /// string must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_input_get_string_for_action_origin(
+        context,
+        origin: MistInputActionOrigin,
+        string: *mut *const c_char,
+    ) -> MistResult {
+        mist_export!([context, string], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            let origin_string_cstr = unwrap_client_result!(subprocess
+                .client()
+                .steam_input()
+                .get_string_for_action_origin(origin));
+
+            let origin_string_cstr_stored = subprocess
+                .state_mut()
+                .origin_strings
+                .entry(origin)
+                .or_insert_with(|| origin_string_cstr);
+
+            unsafe { *string = origin_string_cstr_stored.as_ptr() };
+
+            Success
+        })
+    }
 }
 
-/// Get the gamepad index from an input handle.
-/// Puts the gamepad index into index
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_input_get_gamepad_index_for_controller(
-    input_handle: MistInputHandle,
-    index: *mut c_int,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
+mist_context_export! {
+    /// Get the string from the Xbox origin
+    /// # Safety
+    /// string must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_input_get_string_for_xbox_origin(
+        context,
+        origin: MistXboxOrigin,
+        string: *mut *const c_char,
+    ) -> MistResult {
+        mist_export!([context, string], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            if let Some(origin_string) = subprocess.state().xbox_origin_strings.get(&origin) {
+                unsafe { *string = origin_string.as_ptr() };
+                return Success;
+            }
 
-    let handle = unwrap_client_result!(subprocess
-        .client()
-        .steam_input()
-        .get_gamepad_index_for_controller(input_handle));
+            let origin_string = unwrap_client_result!(subprocess
+                .client()
+                .steam_input()
+                .get_string_for_xbox_origin(origin));
 
-    unsafe { *index = handle };
+            let origin_string_stored = subprocess
+                .state_mut()
+                .xbox_origin_strings
+                .entry(origin)
+                .or_insert(origin_string);
 
-    Success
+            unsafe { *string = origin_string_stored.as_ptr() };
+
+            Success
+        })
+    }
 }
 
-/// Get the gamepad index from an input handle.
-/// Puts the gamepad index into index
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_input_get_glyph_png_for_action_origin(
-    origin: MistInputActionOrigin,
-    size: MistSteamInputGlyphSize,
-    flags: MistSteamInputGlyphStyle,
-    path: *mut *const c_char,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
+mist_context_export! {
+    /// Inits steam input
+    /// # Safety
+    /// initialized must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_input_init(context, initialized: *mut bool) -> MistResult {
+        mist_export!([context, initialized], {
+            let context = MistContext::from_ptr(context);
 
-    let path_cstr = unwrap_client_result!(subprocess
-        .client()
-        .steam_input()
-        .get_glyph_png_for_action_origin(origin, size, flags));
+            let mut subprocess = get_subprocess!(context);
 
-    let path_cstr_stored = subprocess
-        .state_mut()
-        .glpyh_png
-        .entry((origin, size, flags))
-        .or_insert_with(|| path_cstr);
+            let (os_id, inited) = unwrap_client_result!(subprocess.client().steam_input().init());
 
-    unsafe { *path = path_cstr_stored.as_ptr() };
+            let res = MistSteamInputClient::setup(context, &mut subprocess, os_id);
 
-    Success
+            unsafe { *initialized = inited };
+
+            res
+        })
+    }
 }
 
-/// Get the gamepad index from an input handle.
-/// Puts the gamepad index into index
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_input_get_glyph_svg_for_action_origin(
-    origin: MistInputActionOrigin,
-    flags: MistSteamInputGlyphStyle,
-    path: *mut *const c_char,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
+mist_context_export! {
+    /// Takes the oldest digital action press or release event, has_event is set to false once all are drained
+    /// Events are recorded by the subprocess as they happen, so presses shorter than a frame are not lost
+    /// The queue holds 256 events, newer events are dropped while it is full
+    /// NOTE: This method is NOT thread safe, only call it from the thread used for input init!
+    /// # Safety
+    /// has_event and event must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_input_next_action_event(
+        context,
+        has_event: *mut bool,
+        event: *mut MistInputActionEvent,
+    ) -> MistResult {
+        mist_export!([context, has_event, event], {
+            let context = MistContext::from_ptr(context);
+
+            let memory = get_input_state_memory!(context);
+            let state = unsafe { &*memory.header() };
+
+            // The backlog belongs to the shared memory, a replay has its own events
+            let backlog = match context.input_replay.lock().is_none() {
+                true => context.input_event_backlog.lock().action_events.pop_front(),
+                false => None,
+            };
+
+            match backlog.or_else(|| state.action_events.pop()) {
+                Some(next) => unsafe {
+                    *event = next;
+                    *has_event = true;
+                },
+                None => unsafe { *has_event = false },
+            }
 
-    let path_cstr = unwrap_client_result!(subprocess
-        .client()
-        .steam_input()
-        .get_glyph_svg_for_action_origin(origin, flags));
+            Success
+        })
+    }
+}
+
+mist_context_export! {
+    /// Resolves the png glyphs of every combination of origin, size and style and the svg glyphs of every origin and style in one call
+    /// The paths are cached, so later calls to mist_steam_input_get_glyph_png_for_action_origin and mist_steam_input_get_glyph_svg_for_action_origin do not block
+    /// # Safety
+    /// origins must be valid for reads of origin_count elements
+    /// sizes must be valid for reads of size_count elements
+    /// styles must be valid for reads of style_count elements
+    /// Returns MistResult
+    unsafe fn steam_input_prefetch_glyphs(
+        context,
+        origins: *const MistInputActionOrigin,
+        origin_count: usize,
+        sizes: *const MistSteamInputGlyphSize,
+        size_count: usize,
+        styles: *const MistSteamInputGlyphStyle,
+        style_count: usize,
+    ) -> MistResult {
+        mist_export!([context, origins, sizes, styles], {
+            let context = MistContext::from_ptr(context);
+
+            let origins = unsafe { std::slice::from_raw_parts(origins, origin_count) };
+            let sizes = unsafe { std::slice::from_raw_parts(sizes, size_count) };
+            let styles = unsafe { std::slice::from_raw_parts(styles, style_count) };
+
+            let mut png = Vec::with_capacity(origins.len() * sizes.len() * styles.len());
+            let mut svg = Vec::with_capacity(origins.len() * styles.len());
+            for &origin in origins {
+                for &style in styles {
+                    for &size in sizes {
+                        png.push((origin, size, style));
+                    }
+                    svg.push((origin, style));
+                }
+            }
 
-    let path_cstr_stored = subprocess
-        .state_mut()
-        .glpyh_svg
-        .entry((origin, flags))
-        .or_insert_with(|| path_cstr);
+            let mut subprocess = get_subprocess!(context);
 
-    unsafe { *path = path_cstr_stored.as_ptr() };
+            let res = resolve_glyphs(&mut subprocess, png, svg);
+            // Single lookups only mark the cache dirty, a prefetch is the point to persist it
+            flush_glyph_cache(&mut subprocess.state_mut().glyphs);
 
-    Success
+            res
+        })
+    }
 }
 
-/// Get input type for controller
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_input_get_input_type_for_handle(
-    input_handle: MistInputHandle,
-    input_type: *mut MistSteamInputType,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
+mist_context_export! {
+    /// Runs the frame
+    /// NOTE: This method is NOT thread safe, only call it from the thread used for input init!
+    /// Returns MistResult
+    fn steam_input_run_frame(context) -> MistResult {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
 
-    let input_ty = unwrap_client_result!(subprocess
-        .client()
-        .steam_input()
-        .get_input_type_for_handle(input_handle));
+            // Memory replaced by a resize, restart or replay is freed once it is no longer read
+            context.free_retired_input_memory();
 
-    unsafe { *input_type = input_ty };
+            if let Some(replay) = context.input_replay.lock().as_mut() {
+                replay.run_frame(context);
+                return Success;
+            }
 
-    Success
+            let mut subprocess = get_subprocess!(context);
+
+            if let Some(input_client) = &mut subprocess.state_mut().input_client {
+                input_client.run_frame(context)
+            } else {
+                Error::SteamInput(SteamInputError::NotInitialized).into()
+            }
+        })
+    }
 }
 
-/// Get the motion data for a gamepad
-/// NOTE: This method is NOT thread safe, only call it from the thread used for input init!
-/// Returns MistInputMotionData
-#[no_mangle]
-pub extern "C" fn mist_steam_input_get_motion_data(
-    input_handle: MistInputHandle,
-) -> MistInputMotionData {
-    let input_state = get_input_state!();
-
-    for i in 0..MIST_MAX_GAMEPADS {
-        let pad = &input_state.gamepads[i];
-        if pad.input_handle == input_handle {
-            return pad.motion_data;
-        }
+mist_context_export! {
+    /// Manually sets the input action manifest
+    /// Slots for all of its actions are allocated, otherwise only 16 analog and 128 digital actions can be requested
+    /// # Safety
+    /// set must be valid for writes
+    /// path must be a NUL-terminated string
+    /// Returns MistResult
+    unsafe fn steam_input_set_input_action_manifest_file_path(
+        context,
+        path: *const c_char,
+        set: *mut bool,
+    ) -> MistResult {
+        mist_export!([context, path, set], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            let path = unsafe { CStr::from_ptr(path) }.to_owned();
+
+            let (has_set, os_id) = unwrap_client_result!(subprocess
+                .client()
+                .steam_input()
+                .set_input_action_manifest_file_path(path));
+
+            unsafe {
+                *set = has_set;
+            }
+
+            // The shared memory was resized to fit the actions of the manifest
+            match os_id {
+                Some(os_id) => MistSteamInputClient::setup(context, &mut subprocess, os_id),
+                None => Success,
+            }
+        })
     }
+}
 
-    MistInputMotionData::default()
+mist_context_export! {
+    /// Sets the led color of a controller
+    /// Returns MistResult
+    fn steam_input_set_led_color(
+        context,
+        input_handle: MistInputHandle,
+        color_r: u8,
+        color_g: u8,
+        color_b: u8,
+        flags: MistSteamControllerLEDFlag,
+    ) -> MistResult {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            unwrap_client_result!(subprocess.client().steam_input().set_led_color(
+                input_handle,
+                color_r,
+                color_g,
+                color_b,
+                flags
+            ));
+
+            Success
+        })
+    }
 }
 
-/// Get the string from origin
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_input_get_string_for_action_origin(
-    origin: MistInputActionOrigin,
-    string: *mut *const c_char,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
+mist_context_export! {
+    /// Shows the steam input binding menu for a controller
+    /// # Safety
+    /// overlay_shown must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_input_show_binding_panel(
+        context,
+        input_handle: MistInputHandle,
+        overlay_shown: *mut bool,
+    ) -> MistResult {
+        mist_export!([context, overlay_shown], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            let shown = unwrap_client_result!(subprocess
+                .client()
+                .steam_input()
+                .show_binding_panel(input_handle));
+
+            unsafe { *overlay_shown = shown };
+
+            Success
+        })
+    }
+}
 
-    let origin_string_cstr = unwrap_client_result!(subprocess
-        .client()
-        .steam_input()
-        .get_string_for_action_origin(origin));
+mist_context_export! {
+    /// Shuts down steam input
+    /// # Safety
+    /// shutdown must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_input_shutdown(context, shutdown: *mut bool) -> MistResult {
+        mist_export!([context, shutdown], {
+            let context = MistContext::from_ptr(context);
 
-    let origin_string_cstr_stored = subprocess
-        .state_mut()
-        .origin_strings
-        .entry(origin)
-        .or_insert_with(|| origin_string_cstr);
+            let mut subprocess = get_subprocess!(context);
 
-    unsafe { *string = origin_string_cstr_stored.as_ptr() };
+            let res = unwrap_client_result!(subprocess.client().steam_input().shutdown());
 
-    Success
+            unsafe { *shutdown = res };
+
+            Success
+        })
+    }
 }
 
-/// Inits steam input
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_input_init(initialized: *mut bool) -> MistResult {
-    let mut subprocess = get_subprocess!();
+mist_context_export! {
+    /// Stops the virtual analog momentum
+    /// Returns MistResult
+    fn steam_input_stop_analog_action_momentum(
+        context,
+        input_handle: MistInputHandle,
+        action: MistInputAnalogActionHandle,
+    ) -> MistResult {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            unwrap_client_result!(subprocess
+                .client()
+                .steam_input()
+                .stop_analog_action_momentum(input_handle, action));
+
+            Success
+        })
+    }
+}
 
-    let (os_id, inited) = unwrap_client_result!(subprocess.client().steam_input().init());
+mist_context_export! {
+    /// Trigger vibration
+    /// Returns MistResult
+    fn steam_input_trigger_vibration(
+        context,
+        input_handle: MistInputHandle,
+        left_speed: c_ushort,
+        right_speed: c_ushort,
+    ) -> MistResult {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            unwrap_client_result!(subprocess.client().steam_input().trigger_vibration(
+                input_handle,
+                left_speed,
+                right_speed
+            ));
+
+            Success
+        })
+    }
+}
 
-    let res = MistSteamInputClient::setup(&mut *subprocess, os_id);
+mist_context_export! {
+    /// Trigger vibration extended
+    /// Returns MistResult
+    fn steam_input_trigger_vibration_extended(
+        context,
+        input_handle: MistInputHandle,
+        left_speed: c_ushort,
+        right_speed: c_ushort,
+        left_trigger_speed: c_ushort,
+        right_trigger_speed: c_ushort,
+    ) -> MistResult {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            unwrap_client_result!(subprocess
+                .client()
+                .steam_input()
+                .trigger_vibration_extended(
+                    input_handle,
+                    left_speed,
+                    right_speed,
+                    left_trigger_speed,
+                    right_trigger_speed
+                ));
+
+            Success
+        })
+    }
+}
 
-    unsafe { *initialized = inited };
+mist_context_export! {
+    /// Trigger haptic event
+    /// Returns MistResult
+    fn steam_input_trigger_simple_haptic_event(
+        context,
+        input_handle: MistInputHandle,
+        haptic_location: MistControllerHapticLocation,
+        intensity: u8,
+        gain_db: c_char,
+        other_intensity: u8,
+        other_gain_db: c_char,
+    ) -> MistResult {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            unwrap_client_result!(subprocess
+                .client()
+                .steam_input()
+                .trigger_simple_haptic_event(
+                    input_handle,
+                    haptic_location,
+                    intensity,
+                    gain_db,
+                    other_intensity,
+                    other_gain_db
+                ));
+
+            Success
+        })
+    }
+}
 
-    res
+mist_context_export! {
+    /// Translate origin to other input type origin
+    /// # Safety
+    /// translated_origin must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_input_translate_action_origin(
+        context,
+        destination_input_type: MistSteamInputType,
+        source_origin: MistInputActionOrigin,
+        translated_origin: *mut MistInputActionOrigin,
+    ) -> MistResult {
+        mist_export!([context, translated_origin], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            let translated = unwrap_client_result!(subprocess
+                .client()
+                .steam_input()
+                .translate_action_origin(destination_input_type, source_origin));
+
+            unsafe { *translated_origin = translated };
+
+            Success
+        })
+    }
 }
 
-/// Runs the frame
-/// NOTE: This method is NOT thread safe, only call it from the thread used for input init!
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_input_run_frame() -> MistResult {
-    let mut subprocess = get_subprocess!();
+// Extra methods
 
-    if let Some(input_client) = &mut subprocess.state_mut().input_client {
-        input_client.run_frame()
-    } else {
-        Error::SteamInput(SteamInputError::NotInitialized).into()
+mist_context_export! {
+    /// Checks if gamepad at index is not unknown
+    /// Returns bool
+    fn steam_input_ex_query_gamepad(context, index: c_int) -> bool {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
+
+            let memory = get_input_state_memory!(context);
+
+            match gamepad_index(index) {
+                Some(index) => {
+                    unwrap_snapshot!(read_snapshot(context, *memory, |input_state, _| {
+                        input_state.gamepads[index].input_type != MistSteamInputType::Unknown
+                    }))
+                }
+                None => false,
+            }
+        })
     }
 }
 
-/// Manually sets the input action manifest
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_input_set_input_action_manifest_file_path(
-    path: *const c_char,
-    set: *mut bool,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
+mist_context_export! {
+    /// Checks if gamepad at index is not unknown
+    /// # Safety
+    /// gamepad_mapping must be valid for writes
+    /// Returns bool
+    unsafe fn steam_input_ex_get_gamepad_mapping(
+        context,
+        gamepad_mapping: *mut [MistInputHandle; MIST_STEAM_INPUT_MAX_COUNT],
+    ) {
+        mist_export!([context, gamepad_mapping], {
+            let context = MistContext::from_ptr(context);
+
+            let memory = get_input_state_memory!(context);
+
+            let mapping = unwrap_snapshot!(read_snapshot(context, *memory, |input_state, _| {
+                input_state.gamepad_mapping
+            }));
+
+            unsafe { *gamepad_mapping = mapping };
+        })
+    }
+}
 
-    let path = unsafe { CStr::from_ptr(path) }.to_owned();
+mist_context_export! {
+    /// Takes the oldest change of the gamepad mapping, has_event is set to false once all are drained
+    /// Every controller assigned to or removed from a slot of mist_steam_input_ex_get_gamepad_mapping is reported
+    /// NOTE: This method is NOT thread safe, only call it from the thread used for input init!
+    /// # Safety
+    /// has_event and event must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_input_ex_next_slot_event(
+        context,
+        has_event: *mut bool,
+        event: *mut MistInputSlotEvent,
+    ) -> MistResult {
+        mist_export!([context, has_event, event], {
+            let context = MistContext::from_ptr(context);
+
+            let memory = get_input_state_memory!(context);
+            let state = unsafe { &*memory.header() };
+
+            // The backlog belongs to the shared memory, a replay has its own events
+            let backlog = match context.input_replay.lock().is_none() {
+                true => context.input_event_backlog.lock().slot_events.pop_front(),
+                false => None,
+            };
+
+            match backlog.or_else(|| state.slot_events.pop()) {
+                Some(next) => unsafe {
+                    *event = next;
+                    *has_event = true;
+                },
+                None => unsafe { *has_event = false },
+            }
 
-    let has_set = unwrap_client_result!(subprocess
-        .client()
-        .steam_input()
-        .set_input_action_manifest_file_path(path));
+            Success
+        })
+    }
+}
+
+mist_context_export! {
+    /// Gets the frame and time of the snapshot selected by mist_steam_input_run_frame
+    /// The frame counts up with every snapshot taken by the subprocess, so gaps are snapshots the library skipped
+    /// Both are 0 before the first snapshot, timestamp_us is in microseconds of a monotonic clock that starts with the subprocess
+    /// # Safety
+    /// frame and timestamp_us must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_input_ex_get_snapshot_info(
+        context,
+        frame: *mut u64,
+        timestamp_us: *mut u64,
+    ) -> MistResult {
+        mist_export!([context, frame, timestamp_us], {
+            let context = MistContext::from_ptr(context);
+
+            let memory = get_input_state_memory!(context);
+
+            let (snapshot_frame, snapshot_timestamp_us) =
+                unwrap_snapshot!(read_snapshot(context, *memory, |input_state, _| {
+                    (input_state.frame, input_state.timestamp_us)
+                }));
+
+            unsafe {
+                *frame = snapshot_frame;
+                *timestamp_us = snapshot_timestamp_us;
+            }
 
-    unsafe {
-        *set = has_set;
+            Success
+        })
     }
+}
 
-    Success
+mist_context_export! {
+    /// Records the snapshot selected by every mist_steam_input_run_frame to the file at path, with the names of the actions
+    /// A recording already running is stopped
+    /// # Safety
+    /// path must be a NUL-terminated string
+    /// Returns MistResult
+    unsafe fn steam_input_ex_start_recording(context, path: *const c_char) -> MistResult {
+        mist_export!([context, path], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            let path = unsafe { CStr::from_ptr(path) }
+                .to_string_lossy()
+                .to_string();
+
+            let input_client = match &mut subprocess.state_mut().input_client {
+                Some(input_client) => input_client,
+                None => return Error::SteamInput(SteamInputError::NotInitialized).into(),
+            };
+
+            match MistInputRecorder::create(&path) {
+                Ok(recorder) => {
+                    if let Some(Err(err)) = input_client
+                        .recorder
+                        .replace(recorder)
+                        .map(|previous| previous.finish())
+                    {
+                        crate::mist_log_error(&format!(
+                            "Error writing the input recording: {}",
+                            err
+                        ));
+                    }
+
+                    Success
+                }
+                Err(err) => {
+                    crate::mist_log_error(&format!(
+                        "Error creating the input recording {}: {}",
+                        path, err
+                    ));
+                    Error::SteamInput(SteamInputError::RecordingError).into()
+                }
+            }
+        })
+    }
 }
 
-/// Sets the led color of a controller
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_input_set_led_color(
-    input_handle: MistInputHandle,
-    color_r: u8,
-    color_g: u8,
-    color_b: u8,
-    flags: MistSteamControllerLEDFlag,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
+mist_context_export! {
+    /// Stops the recording started by mist_steam_input_ex_start_recording
+    /// Returns MistResult
+    fn steam_input_ex_stop_recording(context) -> MistResult {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            let recorder = subprocess
+                .state_mut()
+                .input_client
+                .as_mut()
+                .and_then(|input_client| input_client.recorder.take());
+
+            match recorder.map(|recorder| recorder.finish()) {
+                Some(Err(err)) => {
+                    crate::mist_log_error(&format!("Error writing the input recording: {}", err));
+                    Error::SteamInput(SteamInputError::RecordingError).into()
+                }
+                _ => Success,
+            }
+        })
+    }
+}
 
-    unwrap_client_result!(subprocess.client().steam_input().set_led_color(
-        input_handle,
-        color_r,
-        color_g,
-        color_b,
-        flags
-    ));
+mist_context_export! {
+    /// Plays back a recording made with mist_steam_input_ex_start_recording, Steam Input and the subprocess are not needed
+    /// Every mist_steam_input_run_frame selects the next recorded snapshot, the last one stays selected once the recording ended
+    /// The action handle functions return the recorded handles, 0 for actions not in the recording
+    /// # Safety
+    /// path must be a NUL-terminated string
+    /// Returns MistResult
+    unsafe fn steam_input_ex_start_replay(context, path: *const c_char) -> MistResult {
+        mist_export!([context, path], {
+            let context = MistContext::from_ptr(context);
+
+            let path = unsafe { CStr::from_ptr(path) }
+                .to_string_lossy()
+                .to_string();
+
+            let mut replay = match MistInputReplay::open(&path) {
+                Ok(replay) => replay,
+                Err(err) => {
+                    crate::mist_log_error(&format!(
+                        "Error reading the input recording {}: {}",
+                        path, err
+                    ));
+                    return Error::SteamInput(SteamInputError::RecordingError).into();
+                }
+            };
+
+            let mut lock = context.input_replay.lock();
+
+            context.input_buffer.store(0, Ordering::Release);
+            context
+                .input_state
+                .store(replay.state_ptr(), Ordering::SeqCst);
+
+            // A previous replay may still be read
+            if let Some(previous) = lock.replace(replay) {
+                context.retire_input_memory(previous);
+            }
 
-    Success
+            Success
+        })
+    }
 }
 
-/// Shows the steam input binding menu for a controller
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_input_show_binding_panel(
-    input_handle: MistInputHandle,
-    overlay_shown: *mut bool,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
+mist_context_export! {
+    /// Stops the replay started by mist_steam_input_ex_start_replay, Steam Input is used again if it was initialized
+    /// Returns MistResult
+    fn steam_input_ex_stop_replay(context) -> MistResult {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
+
+            // Same lock order as the subprocess functions
+            let subprocess = context.subprocess.lock();
+            let mut lock = context.input_replay.lock();
+
+            // Switch back to the shared memory before the replay memory is retired
+            let input_client = subprocess
+                .as_ref()
+                .and_then(|subprocess| subprocess.state().input_client.as_ref());
+            match input_client {
+                Some(input_client) => {
+                    context
+                        .input_buffer
+                        .store(input_client.front_buffer, Ordering::Release);
+                    context
+                        .input_state
+                        .store(input_client.shmem.as_ptr() as *mut _, Ordering::SeqCst);
+                }
+                None => context
+                    .input_state
+                    .store(std::ptr::null_mut(), Ordering::SeqCst),
+            }
 
-    let shown = unwrap_client_result!(subprocess
-        .client()
-        .steam_input()
-        .show_binding_panel(input_handle));
+            if let Some(replay) = lock.take() {
+                context.retire_input_memory(replay);
+            }
 
-    unsafe { *overlay_shown = shown };
+            Success
+        })
+    }
+}
 
-    Success
+mist_context_export! {
+    /// Checks if a replay is running and has recorded frames left
+    /// # Safety
+    /// replaying must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_input_ex_is_replaying(context, replaying: *mut bool) -> MistResult {
+        mist_export!([context, replaying], {
+            let context = MistContext::from_ptr(context);
+
+            let is_playing = context
+                .input_replay
+                .lock()
+                .as_ref()
+                .map(|replay| replay.is_playing())
+                .unwrap_or(false);
+
+            unsafe { *replaying = is_playing };
+
+            Success
+        })
+    }
 }
 
-/// Shuts down steam input
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_input_shutdown(shutdown: *mut bool) -> MistResult {
-    let mut subprocess = get_subprocess!();
+mist_context_export! {
+    /// Persists the glyph paths to the file at path, the paths in it are used if it was written by the same Steam client version
+    /// The file is written after prefetching glyphs, by mist_steam_input_ex_flush_glyph_cache and by mist_subprocess_deinit
+    /// # Safety
+    /// path must be a NUL-terminated string
+    /// Returns MistResult
+    unsafe fn steam_input_ex_set_glyph_cache_path(context, path: *const c_char) -> MistResult {
+        mist_export!([context, path], {
+            let context = MistContext::from_ptr(context);
+
+            let path = unsafe { CStr::from_ptr(path) }
+                .to_string_lossy()
+                .to_string();
+
+            let mut subprocess = get_subprocess!(context);
+
+            let steam_client_version = match unwrap_client_result!(subprocess
+                .client()
+                .internal()
+                .get_steam_client_version())
+            {
+                Some(version) => version,
+                None => {
+                    crate::mist_log_error("Unable to read the Steam client version");
+                    return Error::SteamInput(SteamInputError::GlyphError).into();
+                }
+            };
+
+            match subprocess
+                .state_mut()
+                .glyphs
+                .set_file(path.clone().into(), steam_client_version)
+            {
+                Ok(()) => Success,
+                Err(err) => {
+                    crate::mist_log_error(&format!(
+                        "Error loading the glyph cache {}: {}",
+                        path, err
+                    ));
+                    Error::SteamInput(SteamInputError::GlyphError).into()
+                }
+            }
+        })
+    }
+}
 
-    let res = unwrap_client_result!(subprocess.client().steam_input().shutdown());
+mist_context_export! {
+    /// Writes the glyph paths resolved since the last write to the file set with mist_steam_input_ex_set_glyph_cache_path
+    /// Returns MistResult
+    fn steam_input_ex_flush_glyph_cache(context) -> MistResult {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            match subprocess.state_mut().glyphs.flush() {
+                Ok(()) => Success,
+                Err(err) => {
+                    crate::mist_log_error(&format!("Error writing the glyph cache: {}", err));
+                    Error::SteamInput(SteamInputError::GlyphError).into()
+                }
+            }
+        })
+    }
+}
 
-    unsafe { *shutdown = res };
+mist_context_export! {
+    /// Decodes the png glyph of the origin to 8 bit RGBA, rows top to bottom without padding
+    /// Puts a pointer to width * height * 4 bytes into pixels, which stays valid until the subprocess is deinitialized
+    /// # Safety
+    /// width, height and pixels must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_input_ex_get_glyph_rgba_for_action_origin(
+        context,
+        origin: MistInputActionOrigin,
+        size: MistSteamInputGlyphSize,
+        flags: MistSteamInputGlyphStyle,
+        width: *mut u32,
+        height: *mut u32,
+        pixels: *mut *const u8,
+    ) -> MistResult {
+        mist_export!([context, width, height, pixels], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            let res = resolve_glyphs(&mut subprocess, vec![(origin, size, flags)], Vec::new());
+            if res != Success {
+                return res;
+            }
 
-    Success
+            match subprocess.state_mut().glyphs.rgba((origin, size, flags)) {
+                Ok(image) => {
+                    unsafe {
+                        *width = image.width;
+                        *height = image.height;
+                        *pixels = image.pixels.as_ptr();
+                    }
+
+                    Success
+                }
+                Err(err) => {
+                    crate::mist_log_error(&format!("Error decoding the glyph: {}", err));
+                    Error::SteamInput(SteamInputError::GlyphError).into()
+                }
+            }
+        })
+    }
 }
 
-/// Stops the virtual analog momentum
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_input_stop_analog_action_momentum(
-    input_handle: MistInputHandle,
-    action: MistInputAnalogActionHandle,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
-
-    unwrap_client_result!(subprocess
-        .client()
-        .steam_input()
-        .stop_analog_action_momentum(input_handle, action));
+mist_context_export! {
+    /// Get the gyro pointer settings of the gamepad at index
+    /// Puts the settings into config
+    /// # Safety
+    /// config must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_input_ex_get_gyro_pointer_config(
+        context,
+        index: c_int,
+        config: *mut MistGyroPointerConfig,
+    ) -> MistResult {
+        mist_export!([context, config], {
+            let context = MistContext::from_ptr(context);
+
+            let index = match gamepad_index(index) {
+                Some(index) => index,
+                None => return Error::Mist(MistError::InvalidIndex).into(),
+            };
+
+            unsafe { *config = context.gyro_pointers.lock()[index].config };
+
+            Success
+        })
+    }
+}
 
-    Success
+mist_context_export! {
+    /// Set the gyro pointer settings of the gamepad at index
+    /// # Safety
+    /// config must be valid for reads
+    /// Returns MistResult
+    unsafe fn steam_input_ex_set_gyro_pointer_config(
+        context,
+        index: c_int,
+        config: *const MistGyroPointerConfig,
+    ) -> MistResult {
+        mist_export!([context, config], {
+            let context = MistContext::from_ptr(context);
+
+            let index = match gamepad_index(index) {
+                Some(index) => index,
+                None => return Error::Mist(MistError::InvalidIndex).into(),
+            };
+
+            context.gyro_pointers.lock()[index].config = unsafe { *config };
+
+            Success
+        })
+    }
 }
 
-/// Trigger vibration
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_input_trigger_vibration(
-    input_handle: MistInputHandle,
-    left_speed: c_ushort,
-    right_speed: c_ushort,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
+mist_context_export! {
+    /// Moves the gyro pointer of the gamepad at index by the rotation since the previous snapshot
+    /// Call once after every mist_steam_input_run_frame, calls for the same snapshot do not move the pointer
+    /// Puts the pointer into pointer
+    /// # Safety
+    /// pointer must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_input_ex_update_gyro_pointer(
+        context,
+        index: c_int,
+        pointer: *mut MistGyroPointerState,
+    ) -> MistResult {
+        mist_export!([context, pointer], {
+            let context = MistContext::from_ptr(context);
+
+            let memory = get_input_state_memory!(context);
+
+            let index = match gamepad_index(index) {
+                Some(index) => index,
+                None => return Error::Mist(MistError::InvalidIndex).into(),
+            };
+
+            let (input_handle, motion_data, timestamp_us) =
+                unwrap_snapshot!(read_snapshot(context, *memory, |input_state, _| {
+                    let gamepad = &input_state.gamepads[index];
+                    (
+                        gamepad.input_handle,
+                        gamepad.motion_data,
+                        input_state.timestamp_us,
+                    )
+                }));
+
+            let state = context.gyro_pointers.lock()[index].update(
+                input_handle,
+                &motion_data,
+                timestamp_us,
+            );
+
+            unsafe { *pointer = state };
+
+            Success
+        })
+    }
+}
 
-    unwrap_client_result!(subprocess.client().steam_input().trigger_vibration(
-        input_handle,
-        left_speed,
-        right_speed
-    ));
+mist_context_export! {
+    /// Moves the gyro pointer of the gamepad at index back to the center of the screen
+    /// Returns MistResult
+    fn steam_input_ex_recenter_gyro_pointer(context, index: c_int) -> MistResult {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
 
-    Success
-}
+            let index = match gamepad_index(index) {
+                Some(index) => index,
+                None => return Error::Mist(MistError::InvalidIndex).into(),
+            };
 
-/// Trigger vibration extended
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_input_trigger_vibration_extended(
-    input_handle: MistInputHandle,
-    left_speed: c_ushort,
-    right_speed: c_ushort,
-    left_trigger_speed: c_ushort,
-    right_trigger_speed: c_ushort,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
+            context.gyro_pointers.lock()[index].recenter();
 
-    unwrap_client_result!(subprocess
-        .client()
-        .steam_input()
-        .trigger_vibration_extended(
-            input_handle,
-            left_speed,
-            right_speed,
-            left_trigger_speed,
-            right_trigger_speed
-        ));
+            Success
+        })
+    }
+}
 
-    Success
+mist_context_export! {
+    /// Get the direction of gravity relative to the gamepad at index as a unit vector, from its orientation quaternion
+    /// Puts the vector into gravity
+    /// # Safety
+    /// gravity must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_input_ex_get_gravity(
+        context,
+        index: c_int,
+        gravity: *mut MistVector3,
+    ) -> MistResult {
+        mist_export!([context, gravity], {
+            let context = MistContext::from_ptr(context);
+
+            let memory = get_input_state_memory!(context);
+
+            let index = match gamepad_index(index) {
+                Some(index) => index,
+                None => return Error::Mist(MistError::InvalidIndex).into(),
+            };
+
+            let motion_data =
+                unwrap_snapshot!(read_snapshot(context, *memory, |input_state, _| {
+                    input_state.gamepads[index].motion_data
+                }));
+
+            unsafe { *gravity = motion_data.gravity() };
+
+            Success
+        })
+    }
 }
 
-/// Trigger haptic event
+/// Converts a gyro pointer position to libretro pointer coordinates, positions past the edges are clamped
 /// Returns MistResult
 #[no_mangle]
-pub extern "C" fn mist_steam_input_trigger_simple_haptic_event(
-    input_handle: MistInputHandle,
-    haptic_location: MistControllerHapticLocation,
-    intensity: u8,
-    gain_db: c_char,
-    other_intensity: u8,
-    other_gain_db: c_char,
+pub extern "C" fn mist_steam_input_ex_pointer_to_libretro(
+    x: f32,
+    y: f32,
+    pointer_x: *mut i16,
+    pointer_y: *mut i16,
 ) -> MistResult {
-    let mut subprocess = get_subprocess!();
+    mist_export!([pointer_x, pointer_y], {
+        let (libretro_x, libretro_y) = to_libretro_pointer(x, y);
 
-    unwrap_client_result!(subprocess
-        .client()
-        .steam_input()
-        .trigger_simple_haptic_event(
-            input_handle,
-            haptic_location,
-            intensity,
-            gain_db,
-            other_intensity,
-            other_gain_db
-        ));
+        unsafe {
+            *pointer_x = libretro_x;
+            *pointer_y = libretro_y;
+        }
 
-    Success
+        Success
+    })
 }
 
-/// Translate origin to other input type origin
+/// Converts a gyro pointer position to libretro lightgun coordinates, offscreen is true past the edges of the screen
 /// Returns MistResult
 #[no_mangle]
-pub extern "C" fn mist_steam_input_translate_action_origin(
-    destination_input_type: MistSteamInputType,
-    source_origin: MistInputActionOrigin,
-    translated_origin: *mut MistInputActionOrigin,
+pub extern "C" fn mist_steam_input_ex_pointer_to_libretro_lightgun(
+    x: f32,
+    y: f32,
+    screen_x: *mut i16,
+    screen_y: *mut i16,
+    offscreen: *mut bool,
 ) -> MistResult {
-    let mut subprocess = get_subprocess!();
+    mist_export!([screen_x, screen_y, offscreen], {
+        let (libretro_x, libretro_y, is_offscreen) = to_libretro_lightgun(x, y);
 
-    let translated = unwrap_client_result!(subprocess
-        .client()
-        .steam_input()
-        .translate_action_origin(destination_input_type, source_origin));
+        unsafe {
+            *screen_x = libretro_x;
+            *screen_y = libretro_y;
+            *offscreen = is_offscreen;
+        }
 
-    unsafe { *translated_origin = translated };
+        Success
+    })
+}
 
-    Success
+mist_context_export! {
+    /// Plays the haptic pattern on the controller from the subprocess, replacing the pattern playing on it
+    /// The keyframes need increasing times and the last one a time above 0, which is the duration of the pattern
+    /// The pattern is played loops times or until it is stopped if loops is 0
+    /// Calls to mist_steam_input_trigger_vibration and mist_steam_input_trigger_vibration_extended stop the pattern
+    /// # Safety
+    /// keyframes must be valid for reads of keyframe_count elements
+    /// Returns MistResult
+    unsafe fn steam_input_ex_play_haptic_pattern(
+        context,
+        input_handle: MistInputHandle,
+        keyframes: *const MistHapticKeyframe,
+        keyframe_count: usize,
+        loops: u32,
+    ) -> MistResult {
+        mist_export!([context, keyframes], {
+            let context = MistContext::from_ptr(context);
+
+            let keyframes =
+                unsafe { std::slice::from_raw_parts(keyframes, keyframe_count) }.to_vec();
+
+            let mut subprocess = get_subprocess!(context);
+
+            unwrap_client_result!(subprocess.client().steam_input().play_haptic_pattern(
+                input_handle,
+                keyframes,
+                loops
+            ));
+
+            Success
+        })
+    }
 }
 
-// Extra methods
+mist_context_export! {
+    /// Stops the haptic pattern playing on the controller and its motors
+    /// Returns MistResult
+    fn steam_input_ex_stop_haptic_pattern(context, input_handle: MistInputHandle) -> MistResult {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
 
-/// Checks if gamepad at index is not unknown
-/// Returns bool
-#[no_mangle]
-pub extern "C" fn mist_steam_input_ex_query_gamepad(index: c_int) -> bool {
-    let input_state = get_input_state!();
+            let mut subprocess = get_subprocess!(context);
 
-    input_state.gamepads[index as usize].input_type != MistSteamInputType::Unknown
-}
+            unwrap_client_result!(subprocess
+                .client()
+                .steam_input()
+                .stop_haptic_pattern(input_handle));
 
-/// Checks if gamepad at index is not unknown
-/// Returns bool
-#[no_mangle]
-pub extern "C" fn mist_steam_input_ex_get_gamepad_mapping(
-    gamepad_mapping: *mut [MistInputHandle; MIST_STEAM_INPUT_MAX_COUNT],
-) {
-    let input_state = get_input_state!();
+            Success
+        })
+    }
+}
 
-    unsafe { *gamepad_mapping = input_state.gamepad_mapping };
+mist_context_export! {
+    /// Get the Remote Play session of the gamepad at index from the current snapshot, 0 if it is not used through Remote Play
+    /// Puts the session id into session_id
+    /// # Safety
+    /// session_id must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_input_ex_get_gamepad_remote_play_session_id(
+        context,
+        index: c_int,
+        session_id: *mut u32,
+    ) -> MistResult {
+        mist_export!([context, session_id], {
+            let context = MistContext::from_ptr(context);
+
+            let memory = get_input_state_memory!(context);
+
+            let index = match gamepad_index(index) {
+                Some(index) => index,
+                None => return Error::Mist(MistError::InvalidIndex).into(),
+            };
+
+            let id = unwrap_snapshot!(read_snapshot(context, *memory, |input_state, _| {
+                input_state.gamepads[index].remote_play_session_id
+            }));
+
+            unsafe { *session_id = id };
+
+            Success
+        })
+    }
 }
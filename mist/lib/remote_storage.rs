@@ -1,31 +1,44 @@
-use crate::result::{MistResult, Success};
+use crate::{
+    lib_subprocess::MistContext,
+    result::{MistResult, Success},
+};
 
-/// Begins a file write batch, use file write batches when saving files that gets stored in Steam Cloud.
-/// Will error if there is already a file write batch operation in progress.
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_remote_storage_begin_file_write_batch() -> MistResult {
-    let mut subprocess = get_subprocess!();
+mist_context_export! {
+    /// Begins a file write batch, use file write batches when saving files that gets stored in Steam Cloud.
+    /// Will error if there is already a file write batch operation in progress.
+    /// Returns MistResult
+    fn steam_remote_storage_begin_file_write_batch(context) -> MistResult {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
 
-    unwrap_client_result!(subprocess
-        .client()
-        .steam_remote_storage()
-        .begin_file_write_batch());
+            let mut subprocess = get_subprocess!(context);
 
-    Success
+            unwrap_client_result!(subprocess
+                .client()
+                .steam_remote_storage()
+                .begin_file_write_batch());
+
+            Success
+        })
+    }
 }
 
-/// Ends a file write batch
-/// Will error if there is no file write batch operation in progress.
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_remote_storage_end_file_write_batch() -> MistResult {
-    let mut subprocess = get_subprocess!();
+mist_context_export! {
+    /// Ends a file write batch
+    /// Will error if there is no file write batch operation in progress.
+    /// Returns MistResult
+    fn steam_remote_storage_end_file_write_batch(context) -> MistResult {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
 
-    unwrap_client_result!(subprocess
-        .client()
-        .steam_remote_storage()
-        .end_file_write_batch());
+            unwrap_client_result!(subprocess
+                .client()
+                .steam_remote_storage()
+                .end_file_write_batch());
 
-    Success
+            Success
+        })
+    }
 }
@@ -4,6 +4,7 @@ use std::{
 };
 
 use crate::{
+    lib_subprocess::MistContext,
     result::{Error, MistResult, SteamUtilsError, Success},
     types::{
         AppId, MistFloatingGamepadTextInputMode, MistGamepadTextInputLineMode,
@@ -11,290 +12,391 @@ use crate::{
     },
 };
 
-/// Returns the appid of the running application in out ptr
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_utils_get_appid(app_id: *mut AppId) -> MistResult {
-    let mut subprocess = get_subprocess!();
+mist_context_export! {
+    /// Returns the appid of the running application in out ptr
+    /// # Safety
+    /// app_id must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_utils_get_appid(context, app_id: *mut AppId) -> MistResult {
+        mist_export!([context, app_id], {
+            let context = MistContext::from_ptr(context);
 
-    let id = unwrap_client_result!(subprocess.client().steam_utils().get_appid());
+            let mut subprocess = get_subprocess!(context);
 
-    unsafe {
-        *app_id = id;
-    }
+            let id = unwrap_client_result!(subprocess.client().steam_utils().get_appid());
 
-    Success
-}
+            unsafe {
+                *app_id = id;
+            }
 
-/// Returns the battery percentage in out ptr
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_utils_get_current_battery_power(battery_power: *mut u8) -> MistResult {
-    let mut subprocess = get_subprocess!();
+            Success
+        })
+    }
+}
 
-    let power = unwrap_client_result!(subprocess
-        .client()
-        .steam_utils()
-        .get_current_battery_power());
+mist_context_export! {
+    /// Returns the battery percentage in out ptr
+    /// # Safety
+    /// battery_power must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_utils_get_current_battery_power(context, battery_power: *mut u8) -> MistResult {
+        mist_export!([context, battery_power], {
+            let context = MistContext::from_ptr(context);
 
-    unsafe {
-        *battery_power = power;
-    }
+            let mut subprocess = get_subprocess!(context);
 
-    Success
-}
+            let power = unwrap_client_result!(subprocess
+                .client()
+                .steam_utils()
+                .get_current_battery_power());
 
-/// Copies the entered gamepad text to `text` buffer of `text_size`
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_utils_get_entered_gamepad_text_input(
-    text: *mut c_char,
-    text_size: u32,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
-    let text_size = text_size as usize;
-
-    if let Some(entered_text_cstr) = &subprocess.state_mut().entered_gamepad_text {
-        unsafe {
-            crate::copy_string_out(entered_text_cstr, text, text_size as _);
-        }
-    } else {
-        let entered = unwrap_client_result!(subprocess
-            .client()
-            .steam_utils()
-            .get_entered_gamepad_text_input());
-
-        if let Some(entered) = entered {
-            let entered_text_cstr = CString::new(entered).unwrap_or_default();
             unsafe {
-                crate::copy_string_out(&entered_text_cstr, text, text_size as _);
+                *battery_power = power;
             }
-        } else {
-            return Error::SteamUtils(SteamUtilsError::NoGamepadTextEntered).into();
-        }
-    }
 
-    Success
+            Success
+        })
+    }
 }
 
-/// Sets the length out ptr to the length of the entered gamepad text
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_utils_get_entered_gamepad_text_length(length: *mut u32) -> MistResult {
-    let mut subprocess = get_subprocess!();
-
-    let entered = unwrap_client_result!(subprocess
-        .client()
-        .steam_utils()
-        .get_entered_gamepad_text_input());
-
-    if let Some(entered) = entered {
-        let entered_cstr = CString::new(entered).unwrap();
-        unsafe {
-            *length = entered_cstr.as_bytes().len() as u32;
-        }
-        subprocess.state_mut().entered_gamepad_text = Some(entered_cstr);
-    } else {
-        return Error::SteamUtils(SteamUtilsError::NoGamepadTextEntered).into();
+mist_context_export! {
+    /// Copies the entered gamepad text to `text` buffer of `text_size`
+    /// # Safety
+    /// text must be valid for writes of text_size bytes
+    /// Returns MistResult
+    unsafe fn steam_utils_get_entered_gamepad_text_input(
+        context,
+        text: *mut c_char,
+        text_size: u32,
+    ) -> MistResult {
+        mist_export!([context, text], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+            let text_size = text_size as usize;
+
+            if let Some(entered_text_cstr) = &subprocess.state_mut().entered_gamepad_text {
+                unsafe {
+                    crate::copy_string_out(entered_text_cstr, text, text_size as _);
+                }
+            } else {
+                let entered = unwrap_client_result!(subprocess
+                    .client()
+                    .steam_utils()
+                    .get_entered_gamepad_text_input());
+
+                if let Some(entered) = entered {
+                    let entered_text_cstr = CString::new(entered).unwrap_or_default();
+                    unsafe {
+                        crate::copy_string_out(&entered_text_cstr, text, text_size as _);
+                    }
+                } else {
+                    return Error::SteamUtils(SteamUtilsError::NoGamepadTextEntered).into();
+                }
+            }
+
+            Success
+        })
     }
+}
+
+mist_context_export! {
+    /// Sets the length out ptr to the length of the entered gamepad text
+    /// # Safety
+    /// length must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_utils_get_entered_gamepad_text_length(context, length: *mut u32) -> MistResult {
+        mist_export!([context, length], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            let entered = unwrap_client_result!(subprocess
+                .client()
+                .steam_utils()
+                .get_entered_gamepad_text_input());
+
+            if let Some(entered) = entered {
+                let entered_cstr = CString::new(entered).unwrap_or_default();
+                unsafe {
+                    *length = entered_cstr.as_bytes().len() as u32;
+                }
+                subprocess.state_mut().entered_gamepad_text = Some(entered_cstr);
+            } else {
+                return Error::SteamUtils(SteamUtilsError::NoGamepadTextEntered).into();
+            }
 
-    Success
+            Success
+        })
+    }
 }
 
-/// Return if the Steam overlay is enabled in out ptr
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_utils_is_overlay_enabled(overlay_enabled: *mut bool) -> MistResult {
-    let mut subprocess = get_subprocess!();
+mist_context_export! {
+    /// Return if the Steam overlay is enabled in out ptr
+    /// # Safety
+    /// overlay_enabled must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_utils_is_overlay_enabled(context, overlay_enabled: *mut bool) -> MistResult {
+        mist_export!([context, overlay_enabled], {
+            let context = MistContext::from_ptr(context);
 
-    let enabled = unwrap_client_result!(subprocess.client().steam_utils().is_overlay_enabled());
+            let mut subprocess = get_subprocess!(context);
 
-    unsafe {
-        *overlay_enabled = enabled;
-    }
+            let enabled =
+                unwrap_client_result!(subprocess.client().steam_utils().is_overlay_enabled());
 
-    Success
-}
+            unsafe {
+                *overlay_enabled = enabled;
+            }
 
-/// Return if Steam is running in Big Picture mode in out ptr
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_utils_is_steam_in_big_picture_mode(
-    in_big_picture: *mut bool,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
-
-    let big_picture = unwrap_client_result!(subprocess
-        .client()
-        .steam_utils()
-        .is_steam_in_big_picture_mode());
-
-    unsafe {
-        *in_big_picture = big_picture;
+            Success
+        })
     }
-
-    Success
 }
 
-/// Return if Steam is running in VR mode in out ptr
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_utils_is_steam_running_in_vr(running_in_vr: *mut bool) -> MistResult {
-    let mut subprocess = get_subprocess!();
+mist_context_export! {
+    /// Return if Steam is running in Big Picture mode in out ptr
+    /// # Safety
+    /// in_big_picture must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_utils_is_steam_in_big_picture_mode(
+        context,
+        in_big_picture: *mut bool,
+    ) -> MistResult {
+        mist_export!([context, in_big_picture], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            let big_picture = unwrap_client_result!(subprocess
+                .client()
+                .steam_utils()
+                .is_steam_in_big_picture_mode());
 
-    let in_vr = unwrap_client_result!(subprocess.client().steam_utils().is_steam_running_in_vr());
+            unsafe {
+                *in_big_picture = big_picture;
+            }
 
-    unsafe {
-        *running_in_vr = in_vr;
+            Success
+        })
     }
-
-    Success
 }
 
-/// Return if VR view streaming via Steam Remote Play is enabled in the out ptr
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_utils_is_vr_headset_streaming_enabled(
-    vr_streaming_enabled: *mut bool,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
-
-    let enabled = unwrap_client_result!(subprocess
-        .client()
-        .steam_utils()
-        .is_vr_headset_streaming_enabled());
-
-    unsafe {
-        *vr_streaming_enabled = enabled;
-    }
+mist_context_export! {
+    /// Return if Steam is running in VR mode in out ptr
+    /// # Safety
+    /// running_in_vr must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_utils_is_steam_running_in_vr(context, running_in_vr: *mut bool) -> MistResult {
+        mist_export!([context, running_in_vr], {
+            let context = MistContext::from_ptr(context);
 
-    Success
-}
+            let mut subprocess = get_subprocess!(context);
 
-/// Return if steam is running on a steam deck in the out ptr
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_utils_is_steam_running_on_steam_deck(
-    on_deck: *mut bool,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
-
-    let result = unwrap_client_result!(subprocess
-        .client()
-        .steam_utils()
-        .is_steam_running_on_steam_deck());
-
-    unsafe {
-        *on_deck = result;
-    }
+            let in_vr =
+                unwrap_client_result!(subprocess.client().steam_utils().is_steam_running_in_vr());
 
-    Success
+            unsafe {
+                *running_in_vr = in_vr;
+            }
+
+            Success
+        })
+    }
 }
 
-/// Set if Steam Remote Play should be avaliable for HMD content
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_utils_set_vr_headset_streaming_enabled(enabled: bool) -> MistResult {
-    let mut subprocess = get_subprocess!();
+mist_context_export! {
+    /// Return if VR view streaming via Steam Remote Play is enabled in the out ptr
+    /// # Safety
+    /// vr_streaming_enabled must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_utils_is_vr_headset_streaming_enabled(
+        context,
+        vr_streaming_enabled: *mut bool,
+    ) -> MistResult {
+        mist_export!([context, vr_streaming_enabled], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            let enabled = unwrap_client_result!(subprocess
+                .client()
+                .steam_utils()
+                .is_vr_headset_streaming_enabled());
 
-    unwrap_client_result!(subprocess
-        .client()
-        .steam_utils()
-        .set_vr_headset_streaming_enabled(enabled));
+            unsafe {
+                *vr_streaming_enabled = enabled;
+            }
 
-    Success
+            Success
+        })
+    }
 }
 
-/// Showing a floating keyboard over the game and sends input directly to it
-/// Returns if shown in out ptr
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_utils_show_gamepad_text_input(
-    input_mode: MistGamepadTextInputMode,
-    line_input_mode: MistGamepadTextInputLineMode,
-    description: *const c_char,
-    char_max: u32,
-    existing_text: *const c_char,
-    shown: *mut bool,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
-
-    let description = unsafe { CStr::from_ptr(description) }
-        .to_string_lossy()
-        .to_string();
-    let existing_text = unsafe { CStr::from_ptr(existing_text) }
-        .to_string_lossy()
-        .to_string();
-
-    let did_show =
-        unwrap_client_result!(subprocess.client().steam_utils().show_gamepad_text_input(
-            input_mode,
-            line_input_mode,
-            description,
-            char_max,
-            existing_text
-        ));
-
-    unsafe {
-        *shown = did_show;
+mist_context_export! {
+    /// Return if steam is running on a steam deck in the out ptr
+    /// # Safety
+    /// on_deck must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_utils_is_steam_running_on_steam_deck(
+        context,
+        on_deck: *mut bool,
+    ) -> MistResult {
+        mist_export!([context, on_deck], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            let result = unwrap_client_result!(subprocess
+                .client()
+                .steam_utils()
+                .is_steam_running_on_steam_deck());
+
+            unsafe {
+                *on_deck = result;
+            }
+
+            Success
+        })
     }
+}
+
+mist_context_export! {
+    /// Set if Steam Remote Play should be avaliable for HMD content
+    /// Returns MistResult
+    fn steam_utils_set_vr_headset_streaming_enabled(context, enabled: bool) -> MistResult {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
 
-    Success
+            let mut subprocess = get_subprocess!(context);
+
+            unwrap_client_result!(subprocess
+                .client()
+                .steam_utils()
+                .set_vr_headset_streaming_enabled(enabled));
+
+            Success
+        })
+    }
 }
 
-/// Showing a floating keyboard over the game and sends input directly to it
-/// Returns if shown in out ptr
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_utils_show_floating_gamepad_text_input(
-    keyboard_mode: MistFloatingGamepadTextInputMode,
-    text_field_x_position: c_int,
-    text_field_y_position: c_int,
-    text_field_width: c_int,
-    text_field_height: c_int,
-    shown: *mut bool,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
-
-    let did_show = unwrap_client_result!(subprocess
-        .client()
-        .steam_utils()
-        .show_floating_gamepad_text_input(
-            keyboard_mode,
-            text_field_x_position,
-            text_field_y_position,
-            text_field_width,
-            text_field_height
-        ));
-
-    unsafe {
-        *shown = did_show;
+mist_context_export! {
+    /// Showing a floating keyboard over the game and sends input directly to it
+    /// Returns if shown in out ptr
+    /// # Safety
+    /// shown must be valid for writes
+    /// description and existing_text must be NUL-terminated strings
+    /// Returns MistResult
+    unsafe fn steam_utils_show_gamepad_text_input(
+        context,
+        input_mode: MistGamepadTextInputMode,
+        line_input_mode: MistGamepadTextInputLineMode,
+        description: *const c_char,
+        char_max: u32,
+        existing_text: *const c_char,
+        shown: *mut bool,
+    ) -> MistResult {
+        mist_export!([context, description, existing_text, shown], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            let description = unsafe { CStr::from_ptr(description) }
+                .to_string_lossy()
+                .to_string();
+            let existing_text = unsafe { CStr::from_ptr(existing_text) }
+                .to_string_lossy()
+                .to_string();
+
+            let did_show =
+                unwrap_client_result!(subprocess.client().steam_utils().show_gamepad_text_input(
+                    input_mode,
+                    line_input_mode,
+                    description,
+                    char_max,
+                    existing_text
+                ));
+
+            unsafe {
+                *shown = did_show;
+            }
+
+            Success
+        })
     }
+}
+
+mist_context_export! {
+    /// Showing a floating keyboard over the game and sends input directly to it
+    /// Returns if shown in out ptr
+    /// # Safety
+    /// shown must be valid for writes
+    /// Returns MistResult
+    unsafe fn steam_utils_show_floating_gamepad_text_input(
+        context,
+        keyboard_mode: MistFloatingGamepadTextInputMode,
+        text_field_x_position: c_int,
+        text_field_y_position: c_int,
+        text_field_width: c_int,
+        text_field_height: c_int,
+        shown: *mut bool,
+    ) -> MistResult {
+        mist_export!([context, shown], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            let did_show = unwrap_client_result!(subprocess
+                .client()
+                .steam_utils()
+                .show_floating_gamepad_text_input(
+                    keyboard_mode,
+                    text_field_x_position,
+                    text_field_y_position,
+                    text_field_width,
+                    text_field_height
+                ));
+
+            unsafe {
+                *shown = did_show;
+            }
 
-    Success
+            Success
+        })
+    }
 }
 
-/// Make Steam translate controller input into mouse/kb for UI that does not support controllers
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_utils_set_game_launcher_mode(launcher_mode: bool) -> MistResult {
-    let mut subprocess = get_subprocess!();
+mist_context_export! {
+    /// Make Steam translate controller input into mouse/kb for UI that does not support controllers
+    /// Returns MistResult
+    fn steam_utils_set_game_launcher_mode(context, launcher_mode: bool) -> MistResult {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
 
-    unwrap_client_result!(subprocess
-        .client()
-        .steam_utils()
-        .set_game_launcher_mode(launcher_mode));
+            unwrap_client_result!(subprocess
+                .client()
+                .steam_utils()
+                .set_game_launcher_mode(launcher_mode));
 
-    Success
+            Success
+        })
+    }
 }
 
-/// Open the VR dashboard
-/// Returns MistResult
-#[no_mangle]
-pub extern "C" fn mist_steam_utils_start_vr_dashboard() -> MistResult {
-    let mut subprocess = get_subprocess!();
+mist_context_export! {
+    /// Open the VR dashboard
+    /// Returns MistResult
+    fn steam_utils_start_vr_dashboard(context) -> MistResult {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
 
-    unwrap_client_result!(subprocess.client().steam_utils().start_vr_dashboard());
+            unwrap_client_result!(subprocess.client().steam_utils().start_vr_dashboard());
 
-    Success
+            Success
+        })
+    }
 }
@@ -6,6 +6,27 @@ mist_callbacks!(
             m_nAppID => app_id: AppId
         }
     },
+    SteamInput {
+        SteamInputDeviceConnected {
+            m_ulConnectedDeviceHandle => input_handle: MistInputHandle
+        },
+        SteamInputDeviceDisconnected {
+            m_ulDisconnectedDeviceHandle => input_handle: MistInputHandle
+        },
+        SteamInputConfigurationLoaded {
+            m_unAppID => app_id: AppId,
+            m_ulDeviceHandle => input_handle: MistInputHandle,
+            m_unMajorRevision => major_revision: u32,
+            m_unMinorRevision => minor_revision: u32,
+            m_bUsesSteamInputAPI => uses_steam_input_api: bool,
+            m_bUsesGamepadAPI => uses_gamepad_api: bool,
+            [(|server, _event| {
+                if let Some(input_data) = &server.service().steam_input_data {
+                    input_data.configuration_loaded();
+                }
+            })]
+        }
+    },
     SteamRemoteStorage {
         RemoteStorageLocalFileChange {}
     },
@@ -40,3 +61,53 @@ pub struct MistCallbackMsg {
     pub callback: u32,
     pub data: *const std::ffi::c_void,
 }
+
+/// Handler called from mist_poll for registered callbacks
+/// The callback msg and the data it points to are only valid for the duration of the call
+pub type MistCallbackHandler =
+    Option<extern "C" fn(callback: *const MistCallbackMsg, user_data: *mut std::ffi::c_void)>;
+
+impl MistCallback {
+    #[allow(dead_code)]
+    pub fn msg(&self) -> MistCallbackMsg {
+        MistCallbackMsg {
+            user: self.user,
+            callback: self.callback,
+            data: &self.data as *const _ as *const std::ffi::c_void,
+        }
+    }
+}
+
+/// Callbacks are filtered by the subprocess before they are sent to the library, all are subscribed by default
+/// The library keeps a copy so the subscriptions can be restored when the subprocess is restarted
+#[derive(Default)]
+pub struct MistCallbackSubscriptions {
+    pub unsubscribed_by_default: bool,
+    /// Callbacks whose subscription differs from the default
+    pub exceptions: std::collections::HashSet<u32>,
+}
+
+impl MistCallbackSubscriptions {
+    #[allow(dead_code)] // Only used by the binary
+    pub fn is_subscribed(&self, callback_id: u32) -> bool {
+        self.unsubscribed_by_default == self.exceptions.contains(&callback_id)
+    }
+
+    pub fn set_subscribed(&mut self, callback_ids: Option<Vec<u32>>, subscribed: bool) {
+        match callback_ids {
+            Some(callback_ids) => {
+                for callback_id in callback_ids {
+                    if subscribed == self.unsubscribed_by_default {
+                        self.exceptions.insert(callback_id);
+                    } else {
+                        self.exceptions.remove(&callback_id);
+                    }
+                }
+            }
+            None => {
+                self.unsubscribed_by_default = !subscribed;
+                self.exceptions.clear();
+            }
+        }
+    }
+}
@@ -0,0 +1,240 @@
+//! Safe Rust API for mist
+//! The Mist handle owns its own subprocess and is independent from the C API
+
+use crate::{lib_subprocess::MistSubprocess, result::Error};
+#[cfg(feature = "async")]
+use crate::{
+    lib_subprocess::{spawn_process, wait_or_kill_process},
+    result::MistError,
+    service::MistAsyncClient,
+    transport::MistTransportWriter,
+};
+
+pub use crate::callbacks::{callbacks, MistCallback, MistCallbacks};
+pub use crate::service::{
+    set_call_timeout, set_hang_timeout, set_interface_call_timeout, MistClientSteamApps,
+    MistClientSteamFriends, MistClientSteamInput, MistClientSteamRemoteStorage,
+    MistClientSteamUtils,
+};
+pub use crate::types::*;
+
+#[cfg(feature = "async")]
+pub use crate::service::{
+    MistAsyncClientSteamApps, MistAsyncClientSteamFriends, MistAsyncClientSteamInput,
+    MistAsyncClientSteamRemoteStorage, MistAsyncClientSteamUtils,
+};
+
+pub struct Mist {
+    subprocess: MistSubprocess,
+}
+
+impl Mist {
+    /// Spawns the mist subprocess and waits for it to initialize
+    pub fn init() -> Result<Mist, Error> {
+        Ok(Mist {
+            subprocess: MistSubprocess::spawn()?,
+        })
+    }
+
+    /// Returns false if the subprocess has exited
+    pub fn is_alive(&mut self) -> bool {
+        self.subprocess.is_alive()
+    }
+
+    /// Returns true if the subprocess stopped responding, see set_hang_timeout
+    pub fn is_hung(&self) -> bool {
+        self.subprocess.is_hung()
+    }
+
+    /// Spawns a new subprocess and kills the current one
+    /// The current subprocess is kept if the new one fails to start, so the restart can be retried
+    pub fn restart(&mut self) -> Result<(), Error> {
+        let subprocess = MistSubprocess::spawn()?;
+        let mut old = std::mem::replace(&mut self.subprocess, subprocess);
+
+        // The new subprocess is used even if the old one can not be killed
+        if old.is_alive() {
+            old.kill()?;
+        }
+
+        Ok(())
+    }
+
+    pub fn apps(&mut self) -> &mut dyn MistClientSteamApps {
+        self.subprocess.client().steam_apps()
+    }
+
+    pub fn friends(&mut self) -> &mut dyn MistClientSteamFriends {
+        self.subprocess.client().steam_friends()
+    }
+
+    pub fn input(&mut self) -> &mut dyn MistClientSteamInput {
+        self.subprocess.client().steam_input()
+    }
+
+    pub fn remote_storage(&mut self) -> &mut dyn MistClientSteamRemoteStorage {
+        self.subprocess.client().steam_remote_storage()
+    }
+
+    pub fn utils(&mut self) -> &mut dyn MistClientSteamUtils {
+        self.subprocess.client().steam_utils()
+    }
+
+    /// Polls the subprocess and returns an iterator draining all received callbacks
+    pub fn callbacks(&mut self) -> Result<impl Iterator<Item = MistCallback> + '_, Error> {
+        let client = self.subprocess.client();
+        client.poll()?;
+
+        Ok(client.callbacks().drain(..))
+    }
+
+    /// Subscribes to the callback ids, None subscribes to all callbacks which is the default
+    pub fn subscribe_callbacks(&mut self, callback_ids: Option<Vec<u32>>) -> Result<(), Error> {
+        self.subprocess
+            .client()
+            .internal()
+            .subscribe_callbacks(callback_ids)
+    }
+
+    /// Unsubscribes from the callback ids, None unsubscribes from all callbacks
+    /// The subprocess does not send callbacks which are not subscribed to
+    pub fn unsubscribe_callbacks(&mut self, callback_ids: Option<Vec<u32>>) -> Result<(), Error> {
+        self.subprocess
+            .client()
+            .internal()
+            .unsubscribe_callbacks(callback_ids)
+    }
+
+    /// Returns the version of the Steam client install, None if it could not be read
+    pub fn steam_client_version(&mut self) -> Result<Option<String>, Error> {
+        self.subprocess
+            .client()
+            .internal()
+            .get_steam_client_version()
+    }
+
+    /// Blocks until a callback with the callback id is received, returns None on timeout
+    /// Other callbacks received while waiting are returned by callbacks
+    pub fn wait_for_callback(
+        &mut self,
+        callback_id: u32,
+        timeout: std::time::Duration,
+    ) -> Result<Option<MistCallback>, Error> {
+        if !self.subprocess.is_alive() {
+            return Err(Error::Mist(crate::result::MistError::SubprocessLost));
+        }
+
+        self.subprocess
+            .client()
+            .wait_for_callback(callback_id, 0, timeout)
+    }
+}
+
+impl Drop for Mist {
+    fn drop(&mut self) {
+        if self.subprocess.is_alive() {
+            // Errors are already logged by shutdown
+            let _ = self.subprocess.shutdown();
+        }
+    }
+}
+
+/// Async variant of Mist, calls are futures resolved by the subprocess reply
+#[cfg(feature = "async")]
+pub struct MistAsync {
+    client: MistAsyncClient<MistTransportWriter>,
+    proc: std::process::Child,
+}
+
+#[cfg(feature = "async")]
+impl MistAsync {
+    /// Spawns the mist subprocess and waits for it to initialize
+    pub async fn init() -> Result<MistAsync, Error> {
+        let (mut proc, transport) = spawn_process()?;
+        let (read, write) = match transport.split() {
+            Ok(halves) => halves,
+            Err(err) => {
+                crate::mist_log_error(&format!("Error setting up the IPC transport: {}", err));
+                let _ = proc.kill();
+                return Err(Error::Mist(MistError::SubprocessSpawnError));
+            }
+        };
+        let client = MistAsyncClient::create(read, write);
+
+        if let Err(err) = client.initialized().await {
+            let _ = proc.kill();
+            return Err(err);
+        }
+
+        Ok(MistAsync { client, proc })
+    }
+
+    /// Returns false if the subprocess has exited
+    pub fn is_alive(&mut self) -> bool {
+        self.proc
+            .try_wait()
+            .map(|exit| exit.is_none())
+            .unwrap_or(false)
+    }
+
+    pub fn apps(&self) -> MistAsyncClientSteamApps<'_, MistTransportWriter> {
+        self.client.steam_apps()
+    }
+
+    pub fn friends(&self) -> MistAsyncClientSteamFriends<'_, MistTransportWriter> {
+        self.client.steam_friends()
+    }
+
+    pub fn input(&self) -> MistAsyncClientSteamInput<'_, MistTransportWriter> {
+        self.client.steam_input()
+    }
+
+    pub fn remote_storage(&self) -> MistAsyncClientSteamRemoteStorage<'_, MistTransportWriter> {
+        self.client.steam_remote_storage()
+    }
+
+    pub fn utils(&self) -> MistAsyncClientSteamUtils<'_, MistTransportWriter> {
+        self.client.steam_utils()
+    }
+
+    /// Takes the stream of callbacks, returns None if it has already been taken
+    pub fn callbacks(&self) -> Option<impl futures::Stream<Item = MistCallback>> {
+        self.client.callbacks()
+    }
+
+    /// Subscribes to the callback ids, None subscribes to all callbacks which is the default
+    pub async fn subscribe_callbacks(&self, callback_ids: Option<Vec<u32>>) -> Result<(), Error> {
+        self.client
+            .internal()
+            .subscribe_callbacks(callback_ids)
+            .await
+    }
+
+    /// Unsubscribes from the callback ids, None unsubscribes from all callbacks
+    /// The subprocess does not send callbacks which are not subscribed to
+    pub async fn unsubscribe_callbacks(&self, callback_ids: Option<Vec<u32>>) -> Result<(), Error> {
+        self.client
+            .internal()
+            .unsubscribe_callbacks(callback_ids)
+            .await
+    }
+
+    /// Returns the version of the Steam client install, None if it could not be read
+    pub async fn steam_client_version(&self) -> Result<Option<String>, Error> {
+        self.client.internal().get_steam_client_version().await
+    }
+}
+
+#[cfg(feature = "async")]
+impl Drop for MistAsync {
+    fn drop(&mut self) {
+        use futures::FutureExt;
+
+        if self.is_alive() {
+            // The exit call is written on the first poll, the reply is not needed
+            let _ = self.client.internal().exit().now_or_never();
+            // Errors are already logged
+            let _ = wait_or_kill_process(&mut self.proc);
+        }
+    }
+}
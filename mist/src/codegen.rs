@@ -1,10 +1,7 @@
+// Logs to the host logger, in the subprocess the message is forwarded to the library
 macro_rules! mist_log_error {
     ($error:expr) => {
-        #[cfg(not(feature = "steamworks"))]
-        crate::mist_log_error($error);
-        // TODO: Set the error in some way on the subprocess
-        #[cfg(feature = "steamworks")]
-        drop($error);
+        crate::mist_log_error($error)
     };
 }
 
@@ -28,6 +25,13 @@ macro_rules! mist_service {
     (__fallback_ty_ret, $call_name:ident, $res:ident) => {
         MistServiceToLibraryResult::$call_name
     };
+    // The internal calls are not a Steam interface, every other module needs a MistInterface
+    (__interface Internal) => {
+        None
+    };
+    (__interface $module:ident) => {
+        Some(MistInterface::$module)
+    };
     (__timeout $time:expr) => {
         $time
     };
@@ -68,6 +72,10 @@ macro_rules! mist_service {
             #[allow(dead_code)]
             pub struct MistClient<R: Read, W: Write> {
                 callbacks: std::collections::VecDeque<crate::callbacks::MistCallback>,
+                // Calls which timed out and whose reply has not arrived yet
+                abandoned_replies: usize,
+                // Time the last message from the subprocess was read
+                last_message: std::sync::Arc<parking_lot::Mutex<std::time::Instant>>,
                 write: W,
                 pub receiver: crossbeam_channel::Receiver<MistServiceToLibrary>,
                 _read: std::marker::PhantomData<R>,
@@ -75,50 +83,57 @@ macro_rules! mist_service {
 
             #[allow(dead_code)]
             impl<R: Read + Send + 'static, W: Write> MistClient<R, W> {
-                pub fn create(mut read: R, write: W) -> MistClient<R, W> {
+                pub fn create(read: R, write: W) -> MistClient<R, W> {
                     let (sender, receiver) = crossbeam_channel::unbounded::<MistServiceToLibrary>();
-                    // Spawn a stdin listen thread
+                    let last_message = std::sync::Arc::new(parking_lot::Mutex::new(std::time::Instant::now()));
+
+                    let thread_last_message = last_message.clone();
+                    // Spawn a transport listen thread, the stream can not be recovered after a framing error
                     std::thread::spawn(move || {
-                        loop {
-                            let mut len_buf = [0u8; 32 / 8];
-                            match read.read_exact(&mut len_buf) {
-                                Ok(_) => {
-
-                                    let len = u32::from_le_bytes(len_buf) as usize;
-                                    let mut msg_buf = vec![0; len];
-                                    if let Err(err) = read.read_exact(&mut msg_buf) {
-                                        eprintln!("[mist] Error reading data payload from subprocess: {}", err);
-                                    }
+                        let mut reader = crate::framing::MistFrameReader::new(read);
 
-                                    match bincode::deserialize(&msg_buf) {
-                                        Ok(msg) => if sender.send(msg).is_err() {
-                                            break;
-                                        },
-                                        Err(err) => eprintln!("[mist] Error deserializing data from subprocess: {}", err)
-                                    }
-                                },
-                                Err(err) => if err.kind() != std::io::ErrorKind::UnexpectedEof {
-                                    eprintln!("[mist] Error reading stdin from subprocess: {}", err);
+                        loop {
+                            let payload = match reader.read_frame() {
+                                Ok(payload) => payload,
+                                Err(crate::framing::MistFrameError::Closed) => break,
+                                Err(err) => {
+                                    mist_log_error!(&format!("Error reading data from subprocess: {}", err));
+                                    break;
+                                }
+                            };
+                            crate::recorder::record(crate::recorder::MistIpcDirection::ServiceToLibrary, &payload);
+                            *thread_last_message.lock() = std::time::Instant::now();
+
+                            match crate::framing::decode(&payload) {
+                                // Only used for hang detection
+                                Ok(MistServiceToLibrary::Heartbeat) => {},
+                                Ok(msg) => if sender.send(msg).is_err() {
                                     break;
                                 },
+                                Err(err) => mist_log_error!(&format!("Error deserializing data from subprocess: {}", err))
                             }
                         }
-
                     });
 
                     MistClient {
                         callbacks: std::collections::VecDeque::new(),
+                        abandoned_replies: 0,
+                        last_message,
                         write,
                         receiver,
                         _read: std::marker::PhantomData,
                     }
                 }
 
+                /// Returns true if nothing has been received from the subprocess within the hang timeout
+                pub fn is_hung(&self) -> bool {
+                    self.last_message.lock().elapsed() > hang_timeout()
+                }
+
                 pub fn write_data<D: serde::Serialize>(&mut self, data: &D) -> Result<()> {
-                    let mut data = bincode::serialize(data)?;
-                    let mut payload = (data.len() as u32).to_le_bytes().to_vec();
-                    payload.append(&mut data);
-                    self.write.write_all(&payload)?;
+                    let frame = crate::framing::encode_frame(data)?;
+                    crate::recorder::record(crate::recorder::MistIpcDirection::LibraryToService, &frame[4..]);
+                    self.write.write_all(&frame)?;
                     self.write.flush()?;
                     Ok(())
                 }
@@ -131,7 +146,11 @@ macro_rules! mist_service {
                             MistServiceToLibrary::Callback(callback) => {
                                 self.callbacks.push_back(callback);
                             },
-                            MistServiceToLibrary::Result(_) => {}
+                            MistServiceToLibrary::Result(_) => {
+                                self.abandoned_replies = self.abandoned_replies.saturating_sub(1);
+                            },
+                            MistServiceToLibrary::Log(level, msg) => crate::mist_log(level, &msg),
+                            MistServiceToLibrary::Heartbeat => {},
                         }
                     }
 
@@ -142,6 +161,64 @@ macro_rules! mist_service {
                     &mut self.callbacks
                 }
 
+                /// Queues the received messages and takes the first queued callback with the callback id, without blocking
+                /// The first skip queued callbacks are never returned
+                pub fn take_callback(&mut self, callback_id: u32, skip: usize) -> Result<Option<crate::callbacks::MistCallback>, Error> {
+                    let disconnected = loop {
+                        match self.receiver.try_recv() {
+                            Ok(MistServiceToLibrary::Initialized) => unreachable!(),
+                            Ok(MistServiceToLibrary::InitError(_)) => unreachable!(),
+                            Ok(MistServiceToLibrary::Callback(callback)) => self.callbacks.push_back(callback),
+                            Ok(MistServiceToLibrary::Result(_)) => {
+                                self.abandoned_replies = self.abandoned_replies.saturating_sub(1);
+                            },
+                            Ok(MistServiceToLibrary::Log(level, msg)) => crate::mist_log(level, &msg),
+                            Ok(MistServiceToLibrary::Heartbeat) => {},
+                            Err(crossbeam_channel::TryRecvError::Empty) => break false,
+                            Err(crossbeam_channel::TryRecvError::Disconnected) => break true,
+                        }
+                    };
+
+                    if let Some(pos) = self.callbacks.iter().skip(skip).position(|callback| callback.callback == callback_id) {
+                        return Ok(self.callbacks.remove(skip + pos));
+                    }
+
+                    if disconnected {
+                        return Err(Error::Mist(MistError::SubprocessLost));
+                    }
+
+                    Ok(None)
+                }
+
+                /// Blocks until a callback with the callback id is received or the timeout passes
+                /// Callbacks received while waiting are queued, the first skip queued callbacks are never returned
+                pub fn wait_for_callback(&mut self, callback_id: u32, skip: usize, timeout: std::time::Duration) -> Result<Option<crate::callbacks::MistCallback>, Error> {
+                    if let Some(pos) = self.callbacks.iter().skip(skip).position(|callback| callback.callback == callback_id) {
+                        return Ok(self.callbacks.remove(skip + pos));
+                    }
+
+                    let deadline = std::time::Instant::now() + timeout;
+                    loop {
+                        match self.receiver.recv_deadline(deadline) {
+                            Ok(MistServiceToLibrary::Initialized) => unreachable!(),
+                            Ok(MistServiceToLibrary::InitError(_)) => unreachable!(),
+                            Ok(MistServiceToLibrary::Callback(callback)) => {
+                                if callback.callback == callback_id {
+                                    return Ok(Some(callback));
+                                }
+                                self.callbacks.push_back(callback);
+                            },
+                            Ok(MistServiceToLibrary::Result(_)) => {
+                                self.abandoned_replies = self.abandoned_replies.saturating_sub(1);
+                            },
+                            Ok(MistServiceToLibrary::Log(level, msg)) => crate::mist_log(level, &msg),
+                            Ok(MistServiceToLibrary::Heartbeat) => {},
+                            Err(crossbeam_channel::RecvTimeoutError::Timeout) => return Ok(None),
+                            Err(crossbeam_channel::RecvTimeoutError::Disconnected) => return Err(Error::Mist(MistError::SubprocessLost)),
+                        }
+                    }
+                }
+
                 $(
                     pub fn [< $module:snake >](&mut self) -> &mut dyn [<MistClient $module>] {
                         self
@@ -160,13 +237,21 @@ macro_rules! mist_service {
                                     return Err(Error::Mist(MistError::SubprocessLost));
                                 }
 
-                                while let Ok(data) = self.receiver.recv_timeout(std::time::Duration::from_millis(mist_service!(__timeout $($timeout_time)*))) {
+                                // Callbacks and logs received while waiting do not extend the timeout
+                                let deadline = std::time::Instant::now() + call_timeout(mist_service!(__interface $module), mist_service!(__timeout $($timeout_time)*));
+                                while let Ok(data) = self.receiver.recv_deadline(deadline) {
                                     match data {
                                         MistServiceToLibrary::Initialized => unreachable!(),
                                         MistServiceToLibrary::InitError(_) => unreachable!(),
                                         MistServiceToLibrary::Callback(callback) => {
                                             self.callbacks.push_back(callback);
                                         },
+                                        MistServiceToLibrary::Log(level, msg) => crate::mist_log(level, &msg),
+                                        MistServiceToLibrary::Heartbeat => {},
+                                        // Late reply to a call which timed out, replies arrive in call order
+                                        MistServiceToLibrary::Result(_) if self.abandoned_replies > 0 => {
+                                            self.abandoned_replies -= 1;
+                                        },
                                         MistServiceToLibrary::Result(Ok(mist_service!{__fallback_ty_ret, $call_name, res $(,$return_ty)?})) => {
                                             $(
                                                 let res: $return_ty = res;
@@ -186,12 +271,206 @@ macro_rules! mist_service {
                                     stringify!($module),
                                     stringify!($call_name))
                                 );
+                                // The reply is dropped when it arrives
+                                self.abandoned_replies += 1;
                                 return Err(Error::Mist(MistError::Timeout));
                             }
                         )*
                 }
             )+
 
+            #[cfg(feature = "async")]
+            type MistAsyncReply = futures::channel::oneshot::Sender<Result<MistServiceToLibraryResult, Error>>;
+
+            // Async client, the subprocess handles calls in order so replies are matched to pending calls in order
+            #[cfg(feature = "async")]
+            pub struct MistAsyncClient<W: Write> {
+                write: parking_lot::Mutex<W>,
+                // None once the subprocess has been lost
+                pending: std::sync::Arc<parking_lot::Mutex<Option<std::collections::VecDeque<MistAsyncReply>>>>,
+                initialized: parking_lot::Mutex<Option<futures::channel::oneshot::Receiver<Result<(), String>>>>,
+                callbacks: parking_lot::Mutex<Option<futures::channel::mpsc::UnboundedReceiver<crate::callbacks::MistCallback>>>,
+            }
+
+            #[cfg(feature = "async")]
+            impl<W: Write> MistAsyncClient<W> {
+                pub fn create<R: Read + Send + 'static>(read: R, write: W) -> MistAsyncClient<W> {
+                    let pending = std::sync::Arc::new(parking_lot::Mutex::new(Some(std::collections::VecDeque::<MistAsyncReply>::new())));
+                    let (init_sender, init_receiver) = futures::channel::oneshot::channel();
+                    let (callback_sender, callback_receiver) = futures::channel::mpsc::unbounded();
+
+                    let thread_pending = pending.clone();
+                    // Spawn a transport listen thread, pending calls are cancelled when it exits
+                    std::thread::spawn(move || {
+                        let mut init_sender = Some(init_sender);
+                        let mut reader = crate::framing::MistFrameReader::new(read);
+
+                        loop {
+                            let payload = match reader.read_frame() {
+                                Ok(payload) => payload,
+                                Err(crate::framing::MistFrameError::Closed) => break,
+                                Err(err) => {
+                                    mist_log_error!(&format!("Error reading data from subprocess: {}", err));
+                                    break;
+                                }
+                            };
+                            crate::recorder::record(crate::recorder::MistIpcDirection::ServiceToLibrary, &payload);
+
+                            match crate::framing::decode(&payload) {
+                                Ok(MistServiceToLibrary::Initialized) => {
+                                    if let Some(sender) = init_sender.take() {
+                                        let _ = sender.send(Ok(()));
+                                    }
+                                },
+                                Ok(MistServiceToLibrary::InitError(err)) => {
+                                    if let Some(sender) = init_sender.take() {
+                                        let _ = sender.send(Err(err));
+                                    }
+                                },
+                                Ok(MistServiceToLibrary::Callback(callback)) => {
+                                    // The receiver might have been dropped if the callbacks are not used
+                                    let _ = callback_sender.unbounded_send(callback);
+                                },
+                                Ok(MistServiceToLibrary::Log(level, msg)) => crate::mist_log(level, &msg),
+                                Ok(MistServiceToLibrary::Heartbeat) => {},
+                                Ok(MistServiceToLibrary::Result(res)) => {
+                                    // The call might have been dropped before the reply arrived
+                                    if let Some(reply) = thread_pending.lock().as_mut().and_then(|pending| pending.pop_front()) {
+                                        let _ = reply.send(res);
+                                    }
+                                },
+                                Err(err) => mist_log_error!(&format!("Error deserializing data from subprocess: {}", err))
+                            }
+                        }
+
+                        // Cancels all pending calls
+                        *thread_pending.lock() = None;
+                    });
+
+                    MistAsyncClient {
+                        write: parking_lot::Mutex::new(write),
+                        pending,
+                        initialized: parking_lot::Mutex::new(Some(init_receiver)),
+                        callbacks: parking_lot::Mutex::new(Some(callback_receiver)),
+                    }
+                }
+
+                /// Resolves once the subprocess has initialized, can only be awaited once
+                pub async fn initialized(&self) -> Result<(), Error> {
+                    let receiver = self.initialized.lock().take();
+
+                    let receiver = match receiver {
+                        Some(receiver) => receiver,
+                        None => return Err(Error::Mist(MistError::SubprocessAlreadyInitialized)),
+                    };
+
+                    let initialized = async {
+                        match receiver.await {
+                            Ok(Ok(())) => Ok(()),
+                            Ok(Err(err)) => {
+                                mist_log_error!(&format!("Subprocess initialization error: {}", err));
+                                Err(Error::Mist(MistError::SubprocessInitializationError))
+                            }
+                            Err(_) => Err(Error::Mist(MistError::SubprocessLost)),
+                        }
+                    };
+
+                    match with_timeout(initialized, crate::service::INIT_TIMEOUT).await {
+                        Err(Error::Mist(MistError::Timeout)) => {
+                            mist_log_error!("Subprocess initialization error: timed out");
+                            Err(Error::Mist(MistError::SubprocessInitializationError))
+                        }
+                        res => res,
+                    }
+                }
+
+                /// Takes the stream of callbacks, returns None if it has already been taken
+                pub fn callbacks(&self) -> Option<futures::channel::mpsc::UnboundedReceiver<crate::callbacks::MistCallback>> {
+                    self.callbacks.lock().take()
+                }
+
+                fn send(&self, msg: MistLibraryToService) -> Result<futures::channel::oneshot::Receiver<Result<MistServiceToLibraryResult, Error>>, Error> {
+                    let (sender, receiver) = futures::channel::oneshot::channel();
+
+                    let payload = match crate::framing::encode_frame(&msg) {
+                        Ok(payload) => payload,
+                        Err(err) => {
+                            mist_log_error!(&format!("Error serializing data for subprocess: {}", err));
+                            return Err(Error::Mist(MistError::InternalError));
+                        }
+                    };
+
+                    // Keep pending locked while writing so the reply order matches the call order
+                    let mut pending = self.pending.lock();
+                    let pending = match pending.as_mut() {
+                        Some(pending) => pending,
+                        None => return Err(Error::Mist(MistError::SubprocessLost)),
+                    };
+                    // Recorded before writing so the reply is never recorded first
+                    crate::recorder::record(crate::recorder::MistIpcDirection::LibraryToService, &payload[4..]);
+                    let mut write = self.write.lock();
+                    if let Err(err) = write.write_all(&payload).and_then(|_| write.flush()) {
+                        mist_log_error!(&format!("Error writing data to subprocess: {}", err));
+                        return Err(Error::Mist(MistError::SubprocessLost));
+                    }
+                    pending.push_back(sender);
+
+                    Ok(receiver)
+                }
+
+                $(
+                    pub fn [< $module:snake >](&self) -> [<MistAsyncClient $module>]<'_, W> {
+                        [<MistAsyncClient $module>](self)
+                    }
+                )*
+            }
+
+            $(
+                #[cfg(feature = "async")]
+                pub struct [<MistAsyncClient $module>]<'a, W: Write>(&'a MistAsyncClient<W>);
+
+                #[cfg(feature = "async")]
+                impl<'a, W: Write> [<MistAsyncClient $module>]<'a, W> {
+                    $(
+                        pub async fn $call_name(&self, $($arg : $arg_ty),*) -> Result<mist_service!(__fallback_ty$(,$return_ty)?), Error> {
+                            let reply = self.0.send(MistLibraryToService::$call_name($($arg),*))?;
+
+                            // A dropped reply is skipped by the listen thread, keeping the later replies in order
+                            let timeout = call_timeout(mist_service!(__interface $module), mist_service!(__timeout $($timeout_time)*));
+                            let reply = with_timeout(async { reply.await.map_err(|_| Error::Mist(MistError::SubprocessLost)) }, timeout);
+
+                            match reply.await {
+                                Ok(Ok(mist_service!{__fallback_ty_ret, $call_name, res $(,$return_ty)?})) => {
+                                    $(
+                                        let res: $return_ty = res;
+                                        return Ok(res);
+                                    )?
+                                    mist_service!{__fallback_ty_ret$(,$return_ty)?}
+                                },
+                                Ok(Err(err)) => Err(err),
+                                Ok(Ok(_)) => {
+                                    mist_log_error!(
+                                        &format!("Mismatched reply calling function: {}::{}",
+                                        stringify!($module),
+                                        stringify!($call_name))
+                                    );
+                                    Err(Error::Mist(MistError::InternalError))
+                                },
+                                Err(Error::Mist(MistError::Timeout)) => {
+                                    mist_log_error!(
+                                        &format!("Timeout calling function: {}::{}",
+                                        stringify!($module),
+                                        stringify!($call_name))
+                                    );
+                                    Err(Error::Mist(MistError::Timeout))
+                                },
+                                Err(err) => Err(err),
+                            }
+                        }
+                    )*
+                }
+            )+
+
             #[allow(dead_code)]
             #[cfg(feature = "steamworks")]
             pub struct MistServer<S: MistService, R: Read, W: Write>
@@ -199,40 +478,33 @@ macro_rules! mist_service {
                 service: S,
                 write: W,
                 receiver: crossbeam_channel::Receiver<MistLibraryToService>,
+                // Set once the library closed the transport
+                disconnected: bool,
                 _read: std::marker::PhantomData<R>,
             }
 
             #[allow(dead_code)]
             #[cfg(feature = "steamworks")]
             impl<S: MistService, R: Read + Send + 'static, W: Write> MistServer<S, R, W> {
-                pub fn create(service: S, mut read: R, write: W) -> MistServer<S, R, W> {
-                    // stdin reading is blocking, therefore we have a dedicated thread for it. It will always idle while waiting
+                pub fn create(service: S, read: R, write: W) -> MistServer<S, R, W> {
+                    // Transport reading is blocking, therefore we have a dedicated thread for it. It will always idle while waiting
                     let (sender, receiver) = crossbeam_channel::unbounded::<MistLibraryToService>();
                     std::thread::spawn(move || {
-                        loop {
-                            let mut len_buf = [0u8; 32 / 8];
-                            match read.read_exact(&mut len_buf) {
-                                Ok(_) => {
-
-                                    let len = u32::from_le_bytes(len_buf) as usize;
-                                    let mut msg_buf = vec![0; len];
-                                    if let Err(err) = read.read_exact(&mut msg_buf) {
-                                        eprintln!("[mist] Error reading mist error call: {}", err);
-                                        continue;
-                                    }
+                        let mut reader = crate::framing::MistFrameReader::new(read);
 
-                                    match bincode::deserialize(&msg_buf) {
-                                        Ok(msg) => sender.send(msg).expect("Error sending message to main thread"),
-                                        Err(err) => {
-                                            eprintln!("[mist] Error parsing bincode in subprocess: {}", err);
-                                            continue;
-                                        }
-                                    }
+                        loop {
+                            match reader.read_frame() {
+                                Ok(payload) => match crate::framing::decode(&payload) {
+                                    Ok(msg) => sender.send(msg).expect("Error sending message to main thread"),
+                                    // The frame boundaries are still intact so the next frame can be read
+                                    Err(err) => mist_log_error!(&format!("Error parsing bincode in subprocess: {}", err)),
                                 },
-                                // This should never fail as long as we have a working parent process
+                                // The library exited, the main loop stops once the channel is disconnected
+                                Err(crate::framing::MistFrameError::Closed) => break,
+                                // This should never fail as long as we have a working parent process, the stream can not be recovered
                                 Err(err) => {
-                                    eprintln!("[mist] Error reading stdin in subprocess: {}", err);
-                                    std::process::exit(1);
+                                    mist_log_error!(&format!("Error reading the transport in subprocess: {}", err));
+                                    break;
                                 },
                             }
                         }
@@ -242,19 +514,23 @@ macro_rules! mist_service {
                         service,
                         write,
                         receiver,
+                        disconnected: false,
                         _read: std::marker::PhantomData,
                     }
                 }
 
+                /// Returns true once the library closed the transport
+                pub fn is_disconnected(&self) -> bool {
+                    self.disconnected
+                }
+
                 pub fn service(&mut self) -> &mut S {
                     &mut self.service
                 }
 
                 pub fn write_data<D: serde::Serialize>(&mut self, data: &D) -> Result<()> {
-                    let mut data = bincode::serialize(data)?;
-                    let mut payload = (data.len() as u32).to_le_bytes().to_vec();
-                    payload.append(&mut data);
-                    self.write.write(&payload)?;
+                    let frame = crate::framing::encode_frame(data)?;
+                    self.write.write_all(&frame)?;
                     self.write.flush()?;
                     Ok(())
                 }
@@ -278,7 +554,7 @@ macro_rules! mist_service {
                                                     Err(err) => Err(err)
                                                 }); //(ret)));
                                                 if let Err(err) = self.write_data(&msg) {
-                                                    eprintln!("[mist] Error replying to library call in subprocess: {}", err);
+                                                    mist_log_error!(&format!("Error replying to library call in subprocess: {}", err));
                                                 }
                                         }
                                     )*)*
@@ -289,8 +565,8 @@ macro_rules! mist_service {
                             },
                             Err(crossbeam_channel::RecvTimeoutError::Timeout) => break,
                             Err(crossbeam_channel::RecvTimeoutError::Disconnected) => {
-                                eprintln!("[mist] Disconnected from stdin channel in subprocess");
-                                std::process::exit(1);
+                                self.disconnected = true;
+                                break;
                             },
                         }
                     }
@@ -300,27 +576,30 @@ macro_rules! mist_service {
 
             // Enums used to serialize messages with bincode
             #[allow(non_camel_case_types)]
-            #[derive(Serialize, Deserialize, PartialEq)]
-            enum MistLibraryToService {
+            #[derive(Debug, Serialize, Deserialize, PartialEq)]
+            pub enum MistLibraryToService {
                 $($(
                     $call_name($($arg_ty),*)
                 ),*),*
             }
 
             #[allow(non_camel_case_types)]
-            #[derive(Serialize, Deserialize, PartialEq)]
+            #[derive(Debug, Serialize, Deserialize, PartialEq)]
             pub enum MistServiceToLibraryResult {
                 $($(
                     $call_name $( ($return_ty) )?
                 ),*),*
             }
 
-            #[derive(Serialize, Deserialize, PartialEq)]
+            #[derive(Debug, Serialize, Deserialize, PartialEq)]
             pub enum MistServiceToLibrary {
                 Initialized,
                 InitError(String),
                 Callback(crate::callbacks::MistCallback),
-                Result(Result<MistServiceToLibraryResult, Error>)
+                Result(Result<MistServiceToLibraryResult, Error>),
+                Log(crate::types::MistLogLevel, String),
+                // Sent periodically by the subprocess main loop
+                Heartbeat
             }
         }
     }
@@ -333,7 +612,7 @@ macro_rules! mist_errors {
     (__format, $out:expr, $kind:ident, $err:ident) => {
         $out.push_str(&format!("{}Error_{}", stringify!($kind), stringify!($err)));
     };
-    ($($kind:ident: $code:literal { $($err:ident $(= $err_code:literal)*),* }),*) => {
+    ($($kind:ident: $code:literal { $($err:ident $(= $err_code:literal)* => $msg:literal),* }),*) => {
         paste::paste! {
             #[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
             pub enum Error {
@@ -368,6 +647,61 @@ macro_rules! mist_errors {
                 }
             }
 
+            #[allow(dead_code)]
+            impl Error {
+                /// Decodes a MistResult, returns None for success and unknown errors
+                pub fn from_result(result: MistResult) -> Option<Error> {
+                    let code = result & 0xFFFF;
+                    let err = result >> 16;
+
+                    $(
+                        if code == MistResultCode::$kind as MistResult {
+                            $(
+                                if err == [<$kind Error>]::$err as MistResult {
+                                    return Some(Error::$kind([<$kind Error>]::$err));
+                                }
+                            )*
+                        }
+                    )*
+
+                    None
+                }
+
+                /// Human-readable message with a trailing null byte
+                pub fn message_with_nul(&self) -> &'static str {
+                    match self {
+                        $($(
+                            Error::$kind([<$kind Error>]::$err) => concat!($msg, "\0")
+                        ),*),*
+                    }
+                }
+
+                pub fn message(&self) -> &'static str {
+                    let msg = self.message_with_nul();
+                    &msg[..msg.len() - 1]
+                }
+            }
+
+            /// Human-readable message for a MistResult with a trailing null byte
+            #[allow(dead_code)]
+            pub fn result_to_string(result: MistResult) -> &'static str {
+                if result == Success {
+                    "Success\0"
+                } else if let Some(err) = Error::from_result(result) {
+                    err.message_with_nul()
+                } else {
+                    "Unknown error\0"
+                }
+            }
+
+            impl std::fmt::Display for Error {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    f.write_str(self.message())
+                }
+            }
+
+            impl std::error::Error for Error {}
+
             // Needed for subprocess unwrap
             impl From<Error> for std::result::Result<(), Error> {
                 fn from(err: Error) -> Self {
@@ -426,10 +760,10 @@ macro_rules! mist_callbacks {
 
             $(
                 $(
-                    #[derive(Serialize, Deserialize, PartialEq)]
+                    #[derive(Debug, Serialize, Deserialize, PartialEq)]
                     #[repr(C)]
                     pub struct [<MistCallback $callback_ident>] {
-                        $($callback_field_ident: $callback_var_ty),*
+                        $(pub $callback_field_ident: $callback_var_ty),*
                         /*$(,
                             $($callback_fn_field_ident: $callback_fn_var_ty),*
                         )*/
@@ -441,14 +775,14 @@ macro_rules! mist_callbacks {
                 pub use super::{$($([<MistCallback $callback_ident>]),*),*};
             }
 
-            #[derive(Serialize, Deserialize, PartialEq)]
+            #[derive(Debug, Serialize, Deserialize, PartialEq)]
             pub struct MistCallback {
                 pub user: SteamUser,
                 pub callback: u32,
                 pub data: MistCallbacks
             }
 
-            #[derive(Serialize, Deserialize, PartialEq)]
+            #[derive(Debug, Serialize, Deserialize, PartialEq)]
             pub enum MistCallbacks {
                 $($(
                     [<$module $callback_ident>] ([<MistCallback $callback_ident>])
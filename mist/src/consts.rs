@@ -1,11 +1,29 @@
 // Used to throw an error when running the subprocess directly
 pub const PROCESS_INIT_SECRET: &str = "youarenotsupposedtorunmistdirectly";
+// Pid of the library process, watched by the subprocess where the parent pid is not available
+pub const MIST_PARENT_PID_ENV: &str = "MIST_PARENT_PID";
 
 /// cbindgen:ignore
 pub const MIST_INPUT_STATE_BUFFER_SIZE: u8 = 3;
+// Or'd into the latest buffer index while the library has not taken it yet
+/// cbindgen:ignore
+pub const MIST_INPUT_STATE_LATEST_FRESH: u8 = 0x80;
+// Power of two so the ring indices can wrap
+/// cbindgen:ignore
+pub const MIST_INPUT_EVENT_RING_SIZE: usize = 256;
+// Bumped whenever the layout of the Steam Input shared memory changes
+/// cbindgen:ignore
+pub const MIST_INPUT_STATE_VERSION: u32 = 6;
+// Action slots allocated before a manifest is set, or when it has fewer actions
+/// cbindgen:ignore
+pub const MIST_STEAM_INPUT_DEFAULT_ANALOG_ACTIONS: usize = MIST_STEAM_INPUT_MAX_ANALOG_ACTIONS;
+/// cbindgen:ignore
+pub const MIST_STEAM_INPUT_DEFAULT_DIGITAL_ACTIONS: usize = MIST_STEAM_INPUT_MAX_DIGITAL_ACTIONS;
 
 pub const MIST_STEAM_INPUT_MAX_COUNT: usize = 16;
+/// Deprecated, the action capacity follows the action manifest and this is only the capacity without one
 pub const MIST_STEAM_INPUT_MAX_ANALOG_ACTIONS: usize = 16;
+/// Deprecated, the action capacity follows the action manifest and this is only the capacity without one
 pub const MIST_STEAM_INPUT_MAX_DIGITAL_ACTIONS: usize = 128;
 pub const MIST_STEAM_INPUT_MAX_ORIGINS: usize = 8;
 pub const MIST_STEAM_INPUT_MAX_ACTIVE_LAYERS: usize = 16;
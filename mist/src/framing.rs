@@ -0,0 +1,162 @@
+//! Framing of the bincode IPC messages
+//! A frame is the payload length as u32 le followed by the bincode payload
+//! A corrupted stream has no way to find the next frame boundary, so readers fail fast on framing errors
+
+use bincode::Options;
+use serde::{de::DeserializeOwned, Serialize};
+use std::io::Read;
+
+/// Largest payload accepted in a single frame
+/// cbindgen:ignore
+pub const MIST_MAX_FRAME_SIZE: usize = 4 * 1024 * 1024;
+
+#[derive(Debug)]
+pub enum MistFrameError {
+    /// The stream ended before the start of a frame
+    Closed,
+    /// The length prefix exceeds MIST_MAX_FRAME_SIZE
+    TooLarge(usize),
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for MistFrameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MistFrameError::Closed => write!(f, "stream closed"),
+            MistFrameError::TooLarge(len) => write!(
+                f,
+                "frame of {} bytes exceeds the maximum of {} bytes",
+                len, MIST_MAX_FRAME_SIZE
+            ),
+            MistFrameError::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for MistFrameError {}
+
+pub struct MistFrameReader<R: Read> {
+    read: R,
+}
+
+impl<R: Read> MistFrameReader<R> {
+    pub fn new(read: R) -> MistFrameReader<R> {
+        MistFrameReader { read }
+    }
+
+    /// Blocks until a whole frame has been read and returns its payload
+    pub fn read_frame(&mut self) -> Result<Vec<u8>, MistFrameError> {
+        let mut len_buf = [0u8; 32 / 8];
+        let mut read = 0;
+        while read < len_buf.len() {
+            match self.read.read(&mut len_buf[read..]) {
+                // Only a stream ending between frames is closed, ending within the prefix means it was cut off
+                Ok(0) if read == 0 => return Err(MistFrameError::Closed),
+                Ok(0) => {
+                    return Err(MistFrameError::Io(std::io::Error::from(
+                        std::io::ErrorKind::UnexpectedEof,
+                    )))
+                }
+                Ok(n) => read += n,
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => (),
+                Err(err) => return Err(MistFrameError::Io(err)),
+            }
+        }
+
+        let len = u32::from_le_bytes(len_buf) as usize;
+        if len > MIST_MAX_FRAME_SIZE {
+            return Err(MistFrameError::TooLarge(len));
+        }
+
+        let mut payload = vec![0; len];
+        self.read
+            .read_exact(&mut payload)
+            .map_err(MistFrameError::Io)?;
+
+        Ok(payload)
+    }
+}
+
+// Same encoding as bincode::serialize, but bounded by the frame size
+fn bincode_options() -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+        .with_limit(MIST_MAX_FRAME_SIZE as u64)
+}
+
+/// Serializes the message into a frame including the length prefix
+pub fn encode_frame<T: Serialize>(msg: &T) -> bincode::Result<Vec<u8>> {
+    let mut payload = bincode_options().serialize(msg)?;
+    let mut frame = (payload.len() as u32).to_le_bytes().to_vec();
+    frame.append(&mut payload);
+
+    Ok(frame)
+}
+
+/// Deserializes the payload of a frame
+pub fn decode<T: DeserializeOwned>(payload: &[u8]) -> bincode::Result<T> {
+    bincode_options().deserialize(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let msg = (42u32, String::from("mist"), vec![1u64, 2, 3]);
+        let frame = encode_frame(&msg).unwrap();
+
+        let mut reader = MistFrameReader::new(frame.as_slice());
+        let payload = reader.read_frame().unwrap();
+        assert_eq!(payload.len() + 4, frame.len());
+        assert_eq!(decode::<(u32, String, Vec<u64>)>(&payload).unwrap(), msg);
+    }
+
+    #[test]
+    fn too_large_length_prefix() {
+        let len = MIST_MAX_FRAME_SIZE + 1;
+        let frame = (len as u32).to_le_bytes();
+
+        let mut reader = MistFrameReader::new(&frame[..]);
+        assert!(matches!(reader.read_frame(), Err(MistFrameError::TooLarge(l)) if l == len));
+    }
+
+    #[test]
+    fn truncated_payload() {
+        let frame = encode_frame(&String::from("truncated")).unwrap();
+
+        let mut reader = MistFrameReader::new(&frame[..frame.len() - 1]);
+        match reader.read_frame() {
+            Err(MistFrameError::Io(err)) => {
+                assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof)
+            }
+            res => panic!("expected an io error, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn truncated_length_prefix() {
+        let frame = encode_frame(&1u8).unwrap();
+
+        let mut reader = MistFrameReader::new(&frame[..2]);
+        match reader.read_frame() {
+            Err(MistFrameError::Io(err)) => {
+                assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof)
+            }
+            res => panic!("expected an io error, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn eof_at_frame_boundary() {
+        let mut stream = encode_frame(&1u8).unwrap();
+        stream.extend(encode_frame(&2u8).unwrap());
+
+        let mut reader = MistFrameReader::new(stream.as_slice());
+        assert_eq!(decode::<u8>(&reader.read_frame().unwrap()).unwrap(), 1);
+        assert_eq!(decode::<u8>(&reader.read_frame().unwrap()).unwrap(), 2);
+        assert!(matches!(reader.read_frame(), Err(MistFrameError::Closed)));
+    }
+}
@@ -0,0 +1,25 @@
+//! Entry points for the cargo-fuzz targets in mist/fuzz
+
+use crate::{
+    framing::{decode, MistFrameReader},
+    service::{MistLibraryToService, MistServiceToLibrary},
+};
+
+// Decodes a stream of frames the same way the reader threads do
+fn decode_stream<T: serde::de::DeserializeOwned>(data: &[u8]) {
+    let mut reader = MistFrameReader::new(data);
+
+    while let Ok(payload) = reader.read_frame() {
+        let _ = decode::<T>(&payload);
+    }
+}
+
+/// Decodes the data as frames sent from the library to the subprocess
+pub fn decode_library_to_service(data: &[u8]) {
+    decode_stream::<MistLibraryToService>(data);
+}
+
+/// Decodes the data as frames sent from the subprocess to the library
+pub fn decode_service_to_library(data: &[u8]) {
+    decode_stream::<MistServiceToLibrary>(data);
+}
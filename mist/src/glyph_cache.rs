@@ -0,0 +1,216 @@
+//! Cache of the glyph paths returned by Steam Input, optionally persisted to a file
+//! The paths point into the Steam install, so a persisted cache is only used by the Steam client version which wrote it
+//! Glyphs are also decoded to RGBA on request, decoded images are kept in memory only
+//!
+//! Cache file format: the magic bytes followed by a frame (see framing) holding a bincode MistGlyphCacheFile
+
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    ffi::CString,
+    fs::File,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    framing::{decode, encode_frame, MistFrameReader},
+    types::*,
+};
+
+const MIST_GLYPH_CACHE_MAGIC: &[u8; 8] = b"MISTGLY1";
+
+#[derive(Serialize, Deserialize)]
+struct MistGlyphCacheFile {
+    steam_client_version: String,
+    png: Vec<(MistGlyphPngKey, CString)>,
+    svg: Vec<(MistGlyphSvgKey, CString)>,
+}
+
+/// A glyph decoded to 8 bit RGBA, rows are stored top to bottom without padding
+pub struct MistGlyphImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+#[derive(Default)]
+pub struct MistGlyphCache {
+    png: HashMap<MistGlyphPngKey, CString>,
+    svg: HashMap<MistGlyphSvgKey, CString>,
+    rgba: HashMap<MistGlyphPngKey, MistGlyphImage>,
+    // The cache file and the Steam client version the paths are valid for
+    persistent: Option<(PathBuf, String)>,
+    // Paths were inserted since the cache file was last written
+    dirty: bool,
+}
+
+fn invalid_data<E: std::fmt::Display>(err: E) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, err.to_string())
+}
+
+// Glyphs removed by a Steam update are dropped from a loaded cache
+fn path_exists(path: &CString) -> bool {
+    path.to_str()
+        .map(|path| Path::new(path).exists())
+        .unwrap_or(false)
+}
+
+impl MistGlyphCache {
+    pub fn png(&self, key: &MistGlyphPngKey) -> Option<&CString> {
+        self.png.get(key)
+    }
+
+    pub fn svg(&self, key: &MistGlyphSvgKey) -> Option<&CString> {
+        self.svg.get(key)
+    }
+
+    pub fn insert_png(&mut self, key: MistGlyphPngKey, path: CString) -> &CString {
+        let dirty = &mut self.dirty;
+        self.png.entry(key).or_insert_with(|| {
+            *dirty = true;
+            path
+        })
+    }
+
+    pub fn insert_svg(&mut self, key: MistGlyphSvgKey, path: CString) -> &CString {
+        let dirty = &mut self.dirty;
+        self.svg.entry(key).or_insert_with(|| {
+            *dirty = true;
+            path
+        })
+    }
+
+    /// Persists the cache to the file, the paths in it are loaded if it was written by the same Steam client version
+    pub fn set_file(&mut self, file: PathBuf, steam_client_version: String) -> std::io::Result<()> {
+        match Self::read_file(&file) {
+            Ok(cache) if cache.steam_client_version == steam_client_version => {
+                for (key, path) in cache.png.into_iter().filter(|(_, path)| path_exists(path)) {
+                    self.png.entry(key).or_insert(path);
+                }
+                for (key, path) in cache.svg.into_iter().filter(|(_, path)| path_exists(path)) {
+                    self.svg.entry(key).or_insert(path);
+                }
+            }
+            Ok(_) => (),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => (),
+            Err(err) => return Err(err),
+        }
+
+        // Written right away so the file holds the paths cached before it was set
+        self.persistent = Some((file, steam_client_version));
+        self.dirty = true;
+        self.flush()
+    }
+
+    fn read_file(file: &Path) -> std::io::Result<MistGlyphCacheFile> {
+        let mut file = File::open(file)?;
+
+        let mut magic = [0u8; MIST_GLYPH_CACHE_MAGIC.len()];
+        file.read_exact(&mut magic)?;
+        if &magic != MIST_GLYPH_CACHE_MAGIC {
+            return Err(invalid_data("Not a mist glyph cache"));
+        }
+
+        let payload = MistFrameReader::new(file)
+            .read_frame()
+            .map_err(invalid_data)?;
+
+        decode(&payload).map_err(invalid_data)
+    }
+
+    /// Writes the paths to the cache file if there is one and paths were inserted since it was last written
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        let (file, steam_client_version) = match &self.persistent {
+            Some(persistent) if self.dirty => persistent,
+            _ => return Ok(()),
+        };
+
+        let frame = encode_frame(&MistGlyphCacheFile {
+            steam_client_version: steam_client_version.clone(),
+            png: self
+                .png
+                .iter()
+                .map(|(key, path)| (*key, path.clone()))
+                .collect(),
+            svg: self
+                .svg
+                .iter()
+                .map(|(key, path)| (*key, path.clone()))
+                .collect(),
+        })
+        .map_err(invalid_data)?;
+
+        // Written to a temporary file first so a crash never leaves a truncated cache
+        let mut tmp_file = file.clone().into_os_string();
+        tmp_file.push(".tmp");
+        let mut tmp = File::create(&tmp_file)?;
+        tmp.write_all(MIST_GLYPH_CACHE_MAGIC)?;
+        tmp.write_all(&frame)?;
+        drop(tmp);
+
+        std::fs::rename(tmp_file, file)?;
+        self.dirty = false;
+
+        Ok(())
+    }
+
+    /// Decodes the png of the glyph, the png path has to be cached already
+    pub fn rgba(&mut self, key: MistGlyphPngKey) -> Result<&MistGlyphImage, String> {
+        if !self.rgba.contains_key(&key) {
+            let path = self
+                .png
+                .get(&key)
+                .ok_or_else(|| "The glyph path has not been resolved".to_string())?;
+
+            let image = decode_png_rgba(Path::new(path.to_str().map_err(|err| err.to_string())?))?;
+            self.rgba.insert(key, image);
+        }
+
+        Ok(&self.rgba[&key])
+    }
+}
+
+fn decode_png_rgba(path: &Path) -> Result<MistGlyphImage, String> {
+    let file = File::open(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+
+    let mut decoder = png::Decoder::new(file);
+    // Palettes and low bit depths are expanded and 16 bit channels stripped, leaving 8 bit gray, gray alpha, rgb or rgba
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+
+    let mut reader = decoder.read_info().map_err(|err| err.to_string())?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).map_err(|err| err.to_string())?;
+
+    let pixel_count = info.width as usize * info.height as usize;
+    let mut pixels = Vec::with_capacity(pixel_count * 4);
+
+    for row in buf.chunks(info.line_size).take(info.height as usize) {
+        match info.color_type {
+            png::ColorType::Rgba => pixels.extend_from_slice(&row[..info.width as usize * 4]),
+            png::ColorType::Rgb => {
+                for rgb in row.chunks_exact(3).take(info.width as usize) {
+                    pixels.extend_from_slice(&[rgb[0], rgb[1], rgb[2], 0xff]);
+                }
+            }
+            png::ColorType::GrayscaleAlpha => {
+                for gray_alpha in row.chunks_exact(2).take(info.width as usize) {
+                    let gray = gray_alpha[0];
+                    pixels.extend_from_slice(&[gray, gray, gray, gray_alpha[1]]);
+                }
+            }
+            png::ColorType::Grayscale => {
+                for gray in row.iter().take(info.width as usize) {
+                    pixels.extend_from_slice(&[*gray, *gray, *gray, 0xff]);
+                }
+            }
+            png::ColorType::Indexed => return Err("Unexpanded palette".to_string()),
+        }
+    }
+
+    Ok(MistGlyphImage {
+        width: info.width,
+        height: info.height,
+        pixels,
+    })
+}
@@ -0,0 +1,278 @@
+//! Keyframe interpolation and bookkeeping of the haptic patterns played by the subprocess
+//! The motor speeds are interpolated linearly between the keyframes, changes are reported so only they are sent to Steam Input
+
+use std::{collections::HashMap, time::Instant};
+
+use crate::{
+    result::{Error, SteamInputError},
+    types::*,
+};
+
+pub type MotorSpeeds = [u16; 4];
+
+struct HapticPlayback {
+    keyframes: Vec<MistHapticKeyframe>,
+    loops: u32,
+    start: Instant,
+    last_speeds: Option<MotorSpeeds>,
+}
+
+impl HapticPlayback {
+    // None once all loops have been played
+    fn speeds_at(&self, now: Instant) -> Option<MotorSpeeds> {
+        let elapsed_ms = now.duration_since(self.start).as_millis() as u64;
+        // Never 0, play rejects patterns without a duration
+        let duration_ms = self.keyframes.last()?.time_ms as u64;
+
+        if self.loops != 0 && elapsed_ms >= duration_ms * self.loops as u64 {
+            return None;
+        }
+
+        let time_ms = elapsed_ms % duration_ms;
+
+        let next = self
+            .keyframes
+            .iter()
+            .position(|keyframe| keyframe.time_ms as u64 > time_ms);
+
+        let speeds = match next {
+            Some(0) => keyframe_speeds(&self.keyframes[0]),
+            Some(next) => {
+                let from = &self.keyframes[next - 1];
+                let to = &self.keyframes[next];
+                let t = (time_ms - from.time_ms as u64) as f32 / (to.time_ms - from.time_ms) as f32;
+
+                let (from, to) = (keyframe_speeds(from), keyframe_speeds(to));
+                std::array::from_fn(|i| {
+                    (from[i] as f32 + (to[i] as f32 - from[i] as f32) * t).round() as u16
+                })
+            }
+            None => keyframe_speeds(self.keyframes.last()?),
+        };
+
+        Some(speeds)
+    }
+}
+
+fn keyframe_speeds(keyframe: &MistHapticKeyframe) -> MotorSpeeds {
+    [
+        keyframe.left_speed,
+        keyframe.right_speed,
+        keyframe.left_trigger_speed,
+        keyframe.right_trigger_speed,
+    ]
+}
+
+#[derive(Default)]
+pub struct HapticPatterns {
+    playbacks: HashMap<MistInputHandle, HapticPlayback>,
+}
+
+impl HapticPatterns {
+    /// Replaces the pattern playing on the controller, the pattern starts at now
+    pub fn play(
+        &mut self,
+        input_handle: MistInputHandle,
+        keyframes: Vec<MistHapticKeyframe>,
+        loops: u32,
+        now: Instant,
+    ) -> Result<(), Error> {
+        // The pattern lasts until its last keyframe, without a duration it would never play
+        if keyframes
+            .last()
+            .is_none_or(|keyframe| keyframe.time_ms == 0)
+            || keyframes
+                .windows(2)
+                .any(|pair| pair[0].time_ms >= pair[1].time_ms)
+        {
+            return Err(Error::SteamInput(SteamInputError::InvalidHapticPattern));
+        }
+
+        self.playbacks.insert(
+            input_handle,
+            HapticPlayback {
+                keyframes,
+                loops,
+                start: now,
+                last_speeds: None,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Removes the pattern playing on the controller, returns false if none was playing
+    pub fn stop(&mut self, input_handle: MistInputHandle) -> bool {
+        self.playbacks.remove(&input_handle).is_some()
+    }
+
+    /// Removes all patterns, returns the controllers they were playing on
+    pub fn stop_all(&mut self) -> Vec<MistInputHandle> {
+        self.playbacks
+            .drain()
+            .map(|(input_handle, _)| input_handle)
+            .collect()
+    }
+
+    /// Advances the patterns to now, returns the controllers whose speeds changed with the new speeds
+    /// Finished patterns are removed after reporting stopped motors
+    pub fn advance(&mut self, now: Instant) -> Vec<(MistInputHandle, MotorSpeeds)> {
+        let mut changes = Vec::new();
+
+        self.playbacks.retain(|input_handle, playback| {
+            let speeds = playback.speeds_at(now);
+
+            if playback.last_speeds != Some(speeds.unwrap_or_default()) {
+                changes.push((*input_handle, speeds.unwrap_or_default()));
+                playback.last_speeds = speeds;
+            }
+
+            speeds.is_some()
+        });
+
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn keyframe(time_ms: u32, left_speed: u16, right_speed: u16) -> MistHapticKeyframe {
+        MistHapticKeyframe {
+            time_ms,
+            left_speed,
+            right_speed,
+            left_trigger_speed: left_speed / 2,
+            right_trigger_speed: 0,
+        }
+    }
+
+    fn playback(keyframes: Vec<MistHapticKeyframe>, loops: u32) -> HapticPlayback {
+        HapticPlayback {
+            keyframes,
+            loops,
+            start: Instant::now(),
+            last_speeds: None,
+        }
+    }
+
+    fn speeds_at_ms(playback: &HapticPlayback, ms: u64) -> Option<MotorSpeeds> {
+        playback.speeds_at(playback.start + Duration::from_millis(ms))
+    }
+
+    #[test]
+    fn interpolates_between_keyframes() {
+        let playback = playback(
+            vec![
+                keyframe(0, 0, 2000),
+                keyframe(100, 1000, 0),
+                keyframe(300, 1000, 1000),
+            ],
+            1,
+        );
+
+        assert_eq!(speeds_at_ms(&playback, 0), Some([0, 2000, 0, 0]));
+        assert_eq!(speeds_at_ms(&playback, 25), Some([250, 1500, 125, 0]));
+        assert_eq!(speeds_at_ms(&playback, 100), Some([1000, 0, 500, 0]));
+        assert_eq!(speeds_at_ms(&playback, 200), Some([1000, 500, 500, 0]));
+        assert_eq!(speeds_at_ms(&playback, 299), Some([1000, 995, 500, 0]));
+        assert_eq!(speeds_at_ms(&playback, 300), None);
+    }
+
+    #[test]
+    fn holds_the_first_keyframe_until_its_time() {
+        let playback = playback(vec![keyframe(50, 100, 100), keyframe(100, 200, 200)], 1);
+
+        assert_eq!(speeds_at_ms(&playback, 0), Some([100, 100, 50, 0]));
+        assert_eq!(speeds_at_ms(&playback, 49), Some([100, 100, 50, 0]));
+        assert_eq!(speeds_at_ms(&playback, 75), Some([150, 150, 75, 0]));
+    }
+
+    #[test]
+    fn loops_the_pattern() {
+        let keyframes = vec![keyframe(0, 0, 0), keyframe(100, 1000, 1000)];
+
+        let twice = playback(keyframes.clone(), 2);
+        assert_eq!(speeds_at_ms(&twice, 150), speeds_at_ms(&twice, 50));
+        assert_eq!(speeds_at_ms(&twice, 199), Some([990, 990, 495, 0]));
+        assert_eq!(speeds_at_ms(&twice, 200), None);
+
+        let forever = playback(keyframes, 0);
+        assert_eq!(speeds_at_ms(&forever, 10_050), Some([500, 500, 250, 0]));
+    }
+
+    #[test]
+    fn single_keyframe_with_a_duration_plays() {
+        let playback = playback(vec![keyframe(100, 1000, 1000)], 1);
+
+        assert_eq!(speeds_at_ms(&playback, 0), Some([1000, 1000, 500, 0]));
+        assert_eq!(speeds_at_ms(&playback, 100), None);
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        let mut patterns = HapticPatterns::default();
+        let invalid = [
+            vec![],
+            vec![keyframe(0, 1000, 1000)],
+            vec![keyframe(0, 0, 0), keyframe(0, 1000, 1000)],
+            vec![keyframe(100, 0, 0), keyframe(50, 1000, 1000)],
+        ];
+
+        for keyframes in invalid {
+            assert!(matches!(
+                patterns.play(1, keyframes, 1, Instant::now()),
+                Err(Error::SteamInput(SteamInputError::InvalidHapticPattern))
+            ));
+        }
+        assert!(patterns.playbacks.is_empty());
+
+        assert!(patterns
+            .play(1, vec![keyframe(10, 1000, 1000)], 0, Instant::now())
+            .is_ok());
+        assert!(patterns.playbacks.contains_key(&1));
+    }
+
+    #[test]
+    fn reports_only_changed_speeds() {
+        let mut patterns = HapticPatterns::default();
+        let start = Instant::now();
+        let at_ms = |ms| start + Duration::from_millis(ms);
+
+        patterns
+            .play(
+                1,
+                vec![keyframe(50, 1000, 1000), keyframe(100, 0, 0)],
+                1,
+                start,
+            )
+            .unwrap();
+
+        assert_eq!(patterns.advance(at_ms(0)), vec![(1, [1000, 1000, 500, 0])]);
+        assert_eq!(patterns.advance(at_ms(10)), vec![]);
+        assert_eq!(patterns.advance(at_ms(75)), vec![(1, [500, 500, 250, 0])]);
+
+        // A finished pattern stops the motors once and is removed
+        assert_eq!(patterns.advance(at_ms(100)), vec![(1, [0, 0, 0, 0])]);
+        assert!(patterns.playbacks.is_empty());
+        assert_eq!(patterns.advance(at_ms(200)), vec![]);
+    }
+
+    #[test]
+    fn stops_patterns() {
+        let mut patterns = HapticPatterns::default();
+        let keyframes = vec![keyframe(100, 1000, 1000)];
+
+        patterns
+            .play(1, keyframes.clone(), 0, Instant::now())
+            .unwrap();
+        patterns.play(2, keyframes, 0, Instant::now()).unwrap();
+
+        assert!(patterns.stop(1));
+        assert!(!patterns.stop(1));
+        assert_eq!(patterns.stop_all(), vec![2]);
+        assert!(patterns.playbacks.is_empty());
+    }
+}
@@ -0,0 +1,477 @@
+//! Recording and replay of the Steam Input snapshots read by the library
+//! A recording holds the requested action handles with their names and the snapshot selected by every run frame
+//! Replaying it feeds the same snapshots through the action data functions, one per run frame, without Steam or the subprocess
+//!
+//! Recording format: the magic bytes followed by frames (see framing) holding a bincode MistInputRecord each
+
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufWriter, Read, Write},
+    path::Path,
+    sync::atomic::{fence, Ordering},
+};
+
+use crate::{
+    consts::*,
+    framing::{decode, encode_frame, MistFrameError, MistFrameReader},
+    lib_subprocess::MistContext,
+    types::*,
+};
+
+const MIST_INPUT_RECORD_MAGIC: &[u8; 8] = b"MISTINP2";
+
+#[derive(Serialize, Deserialize)]
+enum MistInputRecord {
+    /// Actions requested since the previous record, in slot order
+    Actions {
+        analog: Vec<(MistInputAnalogActionHandle, String)>,
+        digital: Vec<(MistInputDigitalActionHandle, String)>,
+    },
+    /// A run frame selected a new snapshot
+    Snapshot(MistInputRecordSnapshot),
+    /// A run frame kept the previous snapshot
+    Unchanged,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MistInputRecordSnapshot {
+    frame: u64,
+    timestamp_us: u64,
+    input_handles: Vec<MistInputHandle>,
+    gamepad_mapping: [MistInputHandle; MIST_STEAM_INPUT_MAX_COUNT],
+    // Only gamepads which ever had a controller
+    gamepads: Vec<MistInputRecordGamepad>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MistInputRecordGamepad {
+    index: u8,
+    input_type: MistSteamInputType,
+    input_handle: MistInputHandle,
+    motion_data: MistInputMotionData,
+    remote_play_session_id: u32,
+    // Indexed by slot, only the actions requested when the snapshot was recorded
+    analog_action_data: Vec<MistInputAnalogActionData>,
+    digital_action_data: Vec<MistInputDigitalActionData>,
+}
+
+impl MistInputRecordSnapshot {
+    fn capture(
+        memory: MistInputStateMemory,
+        input_state: &MistInputState,
+        buffer: usize,
+        analog_actions: usize,
+        digital_actions: usize,
+    ) -> MistInputRecordSnapshot {
+        let input_handle_count =
+            (input_state.input_handle_count.max(0) as usize).min(MIST_STEAM_INPUT_MAX_COUNT);
+
+        let gamepads = input_state
+            .gamepads
+            .iter()
+            .enumerate()
+            .filter(|(_, pad)| pad.input_handle != 0)
+            .map(|(i, pad)| MistInputRecordGamepad {
+                index: i as u8,
+                input_type: pad.input_type,
+                input_handle: pad.input_handle,
+                motion_data: pad.motion_data,
+                remote_play_session_id: pad.remote_play_session_id,
+                analog_action_data: unsafe { &*memory.analog_action_data(buffer, i) }
+                    [..analog_actions]
+                    .to_vec(),
+                digital_action_data: unsafe { &*memory.digital_action_data(buffer, i) }
+                    [..digital_actions]
+                    .to_vec(),
+            })
+            .collect();
+
+        MistInputRecordSnapshot {
+            frame: input_state.frame,
+            timestamp_us: input_state.timestamp_us,
+            input_handles: input_state.input_handles[..input_handle_count].to_vec(),
+            gamepad_mapping: input_state.gamepad_mapping,
+            gamepads,
+        }
+    }
+
+    // The caller has to make sure nothing reads the buffer while it is written
+    fn write(&self, memory: MistInputStateMemory, buffer: usize) {
+        let state = unsafe { &mut *memory.header() };
+        let input_state = &mut state.buffer[buffer];
+
+        let sequence = input_state.sequence.load(Ordering::Relaxed);
+        input_state
+            .sequence
+            .store(sequence.wrapping_add(1), Ordering::Relaxed);
+        fence(Ordering::Release);
+
+        input_state.frame = self.frame;
+        input_state.timestamp_us = self.timestamp_us;
+
+        let input_handle_count = self.input_handles.len().min(MIST_STEAM_INPUT_MAX_COUNT);
+        input_state.input_handles = [0; MIST_STEAM_INPUT_MAX_COUNT];
+        input_state.input_handles[..input_handle_count]
+            .copy_from_slice(&self.input_handles[..input_handle_count]);
+        input_state.input_handle_count = input_handle_count as i32;
+        input_state.gamepad_mapping = self.gamepad_mapping;
+
+        for i in 0..MIST_MAX_GAMEPADS {
+            input_state.gamepads[i] = MistInputStateGamepad::default();
+            unsafe { &mut *memory.analog_action_data(buffer, i) }
+                .fill(MistInputAnalogActionData::default());
+            unsafe { &mut *memory.digital_action_data(buffer, i) }
+                .fill(MistInputDigitalActionData::default());
+        }
+
+        for pad in &self.gamepads {
+            let i = pad.index as usize;
+            if i >= MIST_MAX_GAMEPADS {
+                continue;
+            }
+
+            input_state.gamepads[i] = MistInputStateGamepad {
+                input_type: pad.input_type,
+                input_handle: pad.input_handle,
+                motion_data: pad.motion_data,
+                remote_play_session_id: pad.remote_play_session_id,
+            };
+
+            // The slots are allocated from the actions in the recording, so they always fit
+            let analog_action_data = unsafe { &mut *memory.analog_action_data(buffer, i) };
+            let analog_actions = pad.analog_action_data.len().min(analog_action_data.len());
+            analog_action_data[..analog_actions]
+                .copy_from_slice(&pad.analog_action_data[..analog_actions]);
+
+            let digital_action_data = unsafe { &mut *memory.digital_action_data(buffer, i) };
+            let digital_actions = pad.digital_action_data.len().min(digital_action_data.len());
+            digital_action_data[..digital_actions]
+                .copy_from_slice(&pad.digital_action_data[..digital_actions]);
+        }
+
+        input_state
+            .sequence
+            .store(sequence.wrapping_add(2), Ordering::Release);
+    }
+}
+
+fn invalid_data<E: std::fmt::Display>(err: E) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, err.to_string())
+}
+
+/// Writes the snapshots selected by the run frames of the library to a recording
+pub struct MistInputRecorder {
+    file: BufWriter<File>,
+    // Number of actions already in the recording
+    analog_actions: usize,
+    digital_actions: usize,
+}
+
+impl MistInputRecorder {
+    pub fn create<P: AsRef<Path>>(path: P) -> std::io::Result<MistInputRecorder> {
+        let mut file = BufWriter::new(File::create(path)?);
+        file.write_all(MIST_INPUT_RECORD_MAGIC)?;
+
+        Ok(MistInputRecorder {
+            file,
+            analog_actions: 0,
+            digital_actions: 0,
+        })
+    }
+
+    fn write_record(&mut self, record: &MistInputRecord) -> std::io::Result<()> {
+        let frame = encode_frame(record).map_err(invalid_data)?;
+        self.file.write_all(&frame)
+    }
+
+    /// Records a run frame, buffer is the snapshot it selected or None if it kept the previous one
+    /// The names are those the action handles were requested with
+    pub fn record(
+        &mut self,
+        context: &MistContext,
+        memory: MistInputStateMemory,
+        buffer: Option<usize>,
+        analog_action_names: &HashMap<MistInputAnalogActionHandle, String>,
+        digital_action_names: &HashMap<MistInputDigitalActionHandle, String>,
+    ) -> std::io::Result<()> {
+        let analog_handles = memory.analog_action_handles();
+        let digital_handles = memory.digital_action_handles();
+
+        if analog_handles.len() > self.analog_actions
+            || digital_handles.len() > self.digital_actions
+        {
+            let name = |names: &HashMap<u64, String>, handle: &u64| {
+                (*handle, names.get(handle).cloned().unwrap_or_default())
+            };
+
+            self.write_record(&MistInputRecord::Actions {
+                analog: analog_handles[self.analog_actions..]
+                    .iter()
+                    .map(|handle| name(analog_action_names, handle))
+                    .collect(),
+                digital: digital_handles[self.digital_actions..]
+                    .iter()
+                    .map(|handle| name(digital_action_names, handle))
+                    .collect(),
+            })?;
+
+            self.analog_actions = analog_handles.len();
+            self.digital_actions = digital_handles.len();
+        }
+
+        // A snapshot which can not be read is recorded as unchanged, so the recording keeps its frames
+        let snapshot = buffer.and_then(|_| {
+            crate::input::read_snapshot(context, memory, |input_state, buffer| {
+                MistInputRecordSnapshot::capture(
+                    memory,
+                    input_state,
+                    buffer,
+                    self.analog_actions,
+                    self.digital_actions,
+                )
+            })
+            .ok()
+        });
+        let record = match snapshot {
+            Some(snapshot) => MistInputRecord::Snapshot(snapshot),
+            None => MistInputRecord::Unchanged,
+        };
+
+        self.write_record(&record)
+    }
+
+    pub fn finish(mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Plays back a recording in memory laid out like the Steam Input shared memory
+pub struct MistInputReplay {
+    // u64 so the memory is aligned like the shared memory
+    memory: Vec<u64>,
+    analog_actions: HashMap<String, MistInputAnalogActionHandle>,
+    digital_actions: HashMap<String, MistInputDigitalActionHandle>,
+    records: std::vec::IntoIter<MistInputRecord>,
+}
+
+impl MistInputReplay {
+    /// Reads the whole recording, a truncated last record is ignored
+    pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<MistInputReplay> {
+        let mut file = File::open(path)?;
+
+        let mut magic = [0u8; MIST_INPUT_RECORD_MAGIC.len()];
+        file.read_exact(&mut magic)?;
+        if &magic != MIST_INPUT_RECORD_MAGIC {
+            return Err(invalid_data("Not a mist input recording"));
+        }
+
+        let mut reader = MistFrameReader::new(file);
+        let mut records = Vec::new();
+        loop {
+            match reader.read_frame() {
+                Ok(payload) => {
+                    records.push(decode::<MistInputRecord>(&payload).map_err(invalid_data)?)
+                }
+                Err(MistFrameError::Closed) => break,
+                Err(MistFrameError::Io(err)) if err.kind() == std::io::ErrorKind::UnexpectedEof => {
+                    break
+                }
+                Err(err) => return Err(invalid_data(err)),
+            }
+        }
+
+        // All actions are registered up front, in the slot order of the recording
+        let mut analog_handles = Vec::new();
+        let mut digital_handles = Vec::new();
+        for record in &records {
+            if let MistInputRecord::Actions { analog, digital } = record {
+                analog_handles.extend(analog.iter().cloned());
+                digital_handles.extend(digital.iter().cloned());
+            }
+        }
+
+        let layout = MistInputStateLayout {
+            analog_action_capacity: analog_handles.len(),
+            digital_action_capacity: digital_handles.len(),
+        };
+
+        let mut replay = MistInputReplay {
+            memory: vec![0; layout.size().div_ceil(8)],
+            analog_actions: HashMap::new(),
+            digital_actions: HashMap::new(),
+            records: records.into_iter(),
+        };
+
+        unsafe {
+            replay
+                .state_ptr()
+                .write(MistInputStateBuffered::new(layout))
+        };
+        let memory = replay.memory();
+
+        for (handle, name) in analog_handles {
+            memory.register_analog_action(handle);
+            replay.analog_actions.insert(name, handle);
+        }
+        for (handle, name) in digital_handles {
+            memory.register_digital_action(handle);
+            replay.digital_actions.insert(name, handle);
+        }
+
+        Ok(replay)
+    }
+
+    pub fn state_ptr(&mut self) -> *mut MistInputStateBuffered {
+        self.memory.as_mut_ptr() as *mut _
+    }
+
+    fn memory(&mut self) -> MistInputStateMemory {
+        unsafe { MistInputStateMemory::from_ptr(self.state_ptr()) }
+    }
+
+    /// Handle the action had when recording, 0 if it was not requested
+    pub fn analog_action_handle(&self, name: &str) -> MistInputAnalogActionHandle {
+        self.analog_actions.get(name).copied().unwrap_or(0)
+    }
+
+    /// Handle the action had when recording, 0 if it was not requested
+    pub fn digital_action_handle(&self, name: &str) -> MistInputDigitalActionHandle {
+        self.digital_actions.get(name).copied().unwrap_or(0)
+    }
+
+    /// Returns true while there are run frames left to play back
+    pub fn is_playing(&self) -> bool {
+        self.records.as_slice().iter().any(|record| {
+            matches!(
+                record,
+                MistInputRecord::Snapshot(_) | MistInputRecord::Unchanged
+            )
+        })
+    }
+
+    /// Plays back the next recorded run frame, the last snapshot stays selected once the recording ended
+    pub fn run_frame(&mut self, context: &MistContext) {
+        let memory = self.memory();
+
+        for record in self.records.by_ref() {
+            match record {
+                MistInputRecord::Actions { .. } => continue,
+                MistInputRecord::Unchanged => return,
+                MistInputRecord::Snapshot(snapshot) => {
+                    // Readers on other threads might still be reading the buffer before the current one
+                    let buffer = (context.input_buffer.load(Ordering::Relaxed) + 1)
+                        % MIST_INPUT_STATE_BUFFER_SIZE as usize;
+
+                    snapshot.write(memory, buffer);
+                    context.input_buffer.store(buffer, Ordering::Release);
+                    return;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_and_replay() {
+        // Memory laid out like the shared memory the subprocess writes
+        let layout = MistInputStateLayout {
+            analog_action_capacity: 2,
+            digital_action_capacity: 2,
+        };
+        let mut source = vec![0u64; layout.size().div_ceil(8)];
+        let source_ptr = source.as_mut_ptr() as *mut MistInputStateBuffered;
+        unsafe { source_ptr.write(MistInputStateBuffered::new(layout)) };
+        let memory = unsafe { MistInputStateMemory::from_ptr(source_ptr) };
+
+        memory.register_analog_action(11);
+        memory.register_digital_action(21);
+
+        let input_state = unsafe { &mut (*memory.header()).buffer[0] };
+        input_state.frame = 7;
+        input_state.timestamp_us = 1234;
+        input_state.input_handles[0] = 5;
+        input_state.input_handle_count = 1;
+        input_state.gamepad_mapping[0] = 5;
+        input_state.gamepads[0].input_type = MistSteamInputType::PS4Controller;
+        input_state.gamepads[0].input_handle = 5;
+        unsafe {
+            (*memory.analog_action_data(0, 0))[0] = MistInputAnalogActionData {
+                mode: MistControllerSourceMode::JoystickMove,
+                x: 0.5,
+                y: -0.25,
+                active: true,
+            };
+            (*memory.digital_action_data(0, 0))[0] = MistInputDigitalActionData {
+                state: true,
+                active: true,
+            };
+        }
+
+        let analog_names = HashMap::from([(11, String::from("move"))]);
+        let digital_names = HashMap::from([(21, String::from("jump"))]);
+
+        let path = std::env::temp_dir().join(format!("mist_input_record_{}", std::process::id()));
+        let context = MistContext::new();
+
+        let mut recorder = MistInputRecorder::create(&path).unwrap();
+        recorder
+            .record(&context, memory, Some(0), &analog_names, &digital_names)
+            .unwrap();
+        recorder
+            .record(&context, memory, None, &analog_names, &digital_names)
+            .unwrap();
+        recorder.finish().unwrap();
+
+        let replay = MistInputReplay::open(&path);
+        std::fs::remove_file(&path).unwrap();
+        let mut replay = replay.unwrap();
+
+        assert_eq!(replay.analog_action_handle("move"), 11);
+        assert_eq!(replay.digital_action_handle("jump"), 21);
+        assert_eq!(replay.analog_action_handle("look"), 0);
+
+        assert!(replay.is_playing());
+        replay.run_frame(&context);
+        let buffer = context.input_buffer.load(Ordering::Relaxed);
+
+        let replayed = replay.memory();
+        assert_eq!(replayed.analog_action_slot(11), Some(0));
+        assert_eq!(replayed.digital_action_slot(21), Some(0));
+
+        let input_state = unsafe { &(*replayed.header()).buffer[buffer] };
+        assert_eq!(input_state.sequence.load(Ordering::Relaxed) % 2, 0);
+        assert_eq!(input_state.frame, 7);
+        assert_eq!(input_state.timestamp_us, 1234);
+        assert_eq!(input_state.input_handle_count, 1);
+        assert_eq!(input_state.input_handles[0], 5);
+        assert_eq!(input_state.gamepad_mapping[0], 5);
+        assert_eq!(
+            input_state.gamepads[0].input_type,
+            MistSteamInputType::PS4Controller
+        );
+        assert_eq!(input_state.gamepads[0].input_handle, 5);
+        assert_eq!(input_state.gamepads[1].input_handle, 0);
+
+        let analog = unsafe { (*replayed.analog_action_data(buffer, 0))[0] };
+        assert_eq!(analog.mode, MistControllerSourceMode::JoystickMove);
+        assert_eq!((analog.x, analog.y, analog.active), (0.5, -0.25, true));
+        assert_eq!(
+            unsafe { (*replayed.digital_action_data(buffer, 0))[0] },
+            MistInputDigitalActionData {
+                state: true,
+                active: true,
+            }
+        );
+
+        // The unchanged frame keeps the snapshot selected
+        replay.run_frame(&context);
+        assert_eq!(context.input_buffer.load(Ordering::Relaxed), buffer);
+        assert!(!replay.is_playing());
+    }
+}
@@ -1,17 +1,43 @@
-use std::{ffi::CString, os::raw::c_char};
+use std::{
+    cell::RefCell,
+    ffi::{c_void, CString},
+    os::raw::{c_char, c_int},
+    time::{Duration, Instant},
+};
 
 #[macro_use]
 mod codegen;
 pub mod callbacks;
+pub mod client;
 mod consts;
+mod framing;
+#[cfg(feature = "fuzzing")]
+pub mod fuzz;
+mod glyph_cache;
+#[allow(dead_code)] // Only used by the subprocess
+mod haptic_pattern;
+mod input_recorder;
+#[allow(dead_code)] // Only used by the subprocess
+mod manifest;
+pub mod motion;
+pub mod recorder;
 pub mod result;
 mod service;
+pub mod transport;
 #[macro_use]
 mod lib_subprocess;
 mod types;
 
-use callbacks::MistCallbackMsg;
-use result::{Error, MistError, MistResult, Success};
+pub use result::Error;
+
+use callbacks::{MistCallbackHandler, MistCallbackMsg};
+use lib_subprocess::{default_context, MistCallbackHandlerRegistration, MistContext};
+use parking_lot::Mutex;
+use result::{MistError, MistResult, Success};
+use types::{
+    MistInputAnalogActionData, MistInputDigitalActionData, MistInputMotionData, MistInterface,
+    MistLogLevel,
+};
 
 macro_rules! unwrap_client_result {
     ($res:expr) => {
@@ -24,8 +50,195 @@ macro_rules! unwrap_client_result {
     };
 }
 
+/// Wraps the body of an exported function so a panic never unwinds into the caller
+/// The listed pointer arguments are checked for NULL before the body runs
+macro_rules! mist_export {
+    ([$($ptr:ident),*], $body:block) => {{
+        crate::mist_clear_last_error();
+
+        $(
+            if $ptr.is_null() {
+                crate::mist_log_error(concat!("NULL passed as ", stringify!($ptr)));
+                return crate::MistExportResult::from_error(crate::result::Error::Mist(
+                    crate::result::MistError::NullPointer,
+                ));
+            }
+        )*
+
+        crate::mist_catch_panic(|| $body)
+    }};
+    ($body:block) => {{
+        crate::mist_clear_last_error();
+
+        crate::mist_catch_panic(|| $body)
+    }};
+}
+
+/// Defines mist_context_<name> operating on a context and mist_<name> operating on the default context
+/// The docs are those of mist_<name>, which is declared unsafe when it takes pointers and lists their requirements under # Safety
+macro_rules! mist_context_export {
+    (
+        $(#[$attr:meta])*
+        fn $name:ident($context:ident $(, $arg:ident: $ty:ty)* $(,)?) $(-> $ret:ty)?
+        $body:block
+    ) => {
+        paste::paste! {
+            #[doc = " Variant of mist_" $name " operating on the context"]
+            #[doc = " # Safety"]
+            #[doc = " context must come from mist_context_create and not be destroyed yet"]
+            #[no_mangle]
+            pub unsafe extern "C" fn [<mist_context_ $name>](
+                $context: *mut crate::lib_subprocess::MistContext,
+                $($arg: $ty),*
+            ) $(-> $ret)? $body
+
+            $(#[$attr])*
+            #[no_mangle]
+            #[allow(clippy::not_unsafe_ptr_arg_deref)] // Only user data is passed, which is never dereferenced
+            pub extern "C" fn [<mist_ $name>]($($arg: $ty),*) $(-> $ret)? {
+                // The default context lives for the whole process
+                unsafe {
+                    [<mist_context_ $name>](crate::lib_subprocess::default_context(), $($arg),*)
+                }
+            }
+        }
+    };
+    (
+        $(#[$attr:meta])*
+        unsafe fn $name:ident($context:ident $(, $arg:ident: $ty:ty)* $(,)?) $(-> $ret:ty)?
+        $body:block
+    ) => {
+        paste::paste! {
+            #[doc = " Variant of mist_" $name " operating on the context"]
+            #[doc = " # Safety"]
+            #[doc = " context must come from mist_context_create and not be destroyed yet"]
+            #[doc = " The other arguments have the requirements of mist_" $name]
+            #[no_mangle]
+            pub unsafe extern "C" fn [<mist_context_ $name>](
+                $context: *mut crate::lib_subprocess::MistContext,
+                $($arg: $ty),*
+            ) $(-> $ret)? $body
+
+            $(#[$attr])*
+            #[no_mangle]
+            pub unsafe extern "C" fn [<mist_ $name>]($($arg: $ty),*) $(-> $ret)? {
+                [<mist_context_ $name>](crate::lib_subprocess::default_context(), $($arg),*)
+            }
+        }
+    };
+}
+
+/// Value returned by an exported function which failed before producing a result
+pub(crate) trait MistExportResult {
+    fn from_error(err: Error) -> Self;
+}
+
+impl MistExportResult for MistResult {
+    fn from_error(err: Error) -> Self {
+        err.into()
+    }
+}
+
+impl MistExportResult for bool {
+    fn from_error(_err: Error) -> Self {
+        false
+    }
+}
+
+impl MistExportResult for () {
+    fn from_error(_err: Error) -> Self {}
+}
+
+impl<T> MistExportResult for *const T {
+    fn from_error(_err: Error) -> Self {
+        std::ptr::null()
+    }
+}
+
+impl MistExportResult for MistInputAnalogActionData {
+    fn from_error(_err: Error) -> Self {
+        Self::default()
+    }
+}
+
+impl MistExportResult for MistInputDigitalActionData {
+    fn from_error(_err: Error) -> Self {
+        Self::default()
+    }
+}
+
+impl MistExportResult for MistInputMotionData {
+    fn from_error(_err: Error) -> Self {
+        Self::default()
+    }
+}
+
+/// Runs the body of an exported function, a panic is logged and returned as MistError::Panic
+pub(crate) fn mist_catch_panic<R: MistExportResult>(body: impl FnOnce() -> R) -> R {
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(body)) {
+        Ok(res) => res,
+        Err(panic) => {
+            let msg = panic
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| panic.downcast_ref::<String>().map(|msg| msg.as_str()))
+                .unwrap_or("unknown panic");
+            mist_log_error(&format!("Internal panic: {}", msg));
+
+            R::from_error(Error::Mist(MistError::Panic))
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR_MESSAGE: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Log callback, msg is only valid for the duration of the call
+/// It can be called from any thread which uses mist as well as the mist reader thread
+pub type MistLogCallback =
+    Option<extern "C" fn(level: MistLogLevel, msg: *const c_char, user_data: *mut c_void)>;
+
+#[derive(Clone, Copy)]
+struct MistLogSink {
+    callback: MistLogCallback,
+    user_data: *mut c_void,
+}
+
+// The user data is only passed back to the log callback
+unsafe impl Send for MistLogSink {}
+
+lazy_static::lazy_static! {
+    static ref LOG_SINK: Mutex<Option<MistLogSink>> = Mutex::new(None);
+}
+
+// Interior NULs are escaped, so the message is never replaced by an empty one
+fn log_message(msg: &str) -> CString {
+    CString::new(msg.replace('\0', "\\0")).unwrap_or_default()
+}
+
+pub fn mist_log(level: MistLogLevel, msg: &str) {
+    // Copy the sink out so the callback is free to change it
+    let sink = *LOG_SINK.lock();
+
+    match sink.and_then(|sink| sink.callback.map(|callback| (callback, sink.user_data))) {
+        Some((callback, user_data)) => {
+            let msg = log_message(msg);
+            callback(level, msg.as_ptr(), user_data);
+        }
+        None => eprintln!("[mist] {}", msg),
+    }
+}
+
 pub fn mist_log_error(err: &str) {
-    eprintln!("[mist] {}", err);
+    mist_log(MistLogLevel::Error, err);
+
+    LAST_ERROR_MESSAGE.with(|msg| *msg.borrow_mut() = Some(log_message(err)));
+}
+
+// Called on entry of every export, so the message always belongs to the last call which failed
+pub(crate) fn mist_clear_last_error() {
+    LAST_ERROR_MESSAGE.with(|msg| *msg.borrow_mut() = None);
 }
 
 pub unsafe fn copy_string_out(string: &CString, out: *mut c_char, size: usize) -> usize {
@@ -37,73 +250,416 @@ pub unsafe fn copy_string_out(string: &CString, out: *mut c_char, size: usize) -
     copied
 }
 
-/// Init mist, this is throwns an error if it was already initialised
+/// Sets the callback which receives all log messages from mist and the subprocess
+/// Passing NULL as callback restores logging to stderr
+/// This can be called before mist_subprocess_init
+#[no_mangle]
+pub extern "C" fn mist_set_log_callback(callback: MistLogCallback, user_data: *mut c_void) {
+    mist_export!({
+        *LOG_SINK.lock() = callback.is_some().then_some(MistLogSink {
+            callback,
+            user_data,
+        });
+    })
+}
+
+/// Sets the timeout in ms for calls to the subprocess which have no interface timeout
+/// Calls known to be slow are never given less than their built-in timeout
+/// Passing 0 restores the default timeouts, this can be called before mist_subprocess_init
 /// Returns MistResult
 #[no_mangle]
-pub extern "C" fn mist_subprocess_init() -> MistResult {
-    let result = std::panic::catch_unwind(lib_subprocess::mist_init_subprocess);
-
-    match result {
-        Ok(res) => unwrap_client_result!(res),
-        Err(_) => {
-            mist_log_error("Internal panic during initialization");
-            return Error::Mist(MistError::SubprocessNotInitialized).into();
-        }
-    }
+pub extern "C" fn mist_set_call_timeout(timeout_ms: u32) -> MistResult {
+    mist_export!({
+        service::set_call_timeout(timeout_ms as u64);
 
-    Success
+        Success
+    })
 }
 
-/// Polls the subprocess
+/// Sets the timeout in ms for calls to the subprocess for a single interface, interface is a MistInterface value
+/// Passing 0 makes the interface use the timeout from mist_set_call_timeout again
 /// Returns MistResult
 #[no_mangle]
-pub extern "C" fn mist_poll() -> MistResult {
-    let mut subprocess = get_subprocess!();
+pub extern "C" fn mist_set_interface_call_timeout(interface: c_int, timeout_ms: u32) -> MistResult {
+    mist_export!({
+        let interface = match MistInterface::try_from(interface) {
+            Ok(interface) => interface,
+            Err(()) => {
+                mist_log_error(&format!("Invalid interface {}", interface));
+                return Error::Mist(MistError::InvalidInterface).into();
+            }
+        };
 
-    unwrap_client_result!(subprocess.client().poll());
+        service::set_interface_call_timeout(interface, timeout_ms as u64);
 
-    Success
+        Success
+    })
 }
 
-/// Attempts to return the next callback, if none are left it will set p_callback to NULL
-/// Safety: The pointer is only valid until the next call of this function
-/// Due to this it is not safe to simultaneously access callbacks from two different threads since they might race invalidate the other threads callback
+/// Sets the time in ms without any message from the subprocess after which it is considered hung
+/// Calls to a hung subprocess fail with MistError_SubprocessHung, mist_subprocess_restart replaces it
+/// Passing 0 restores the default of 15 seconds, this can be called before mist_subprocess_init
 /// Returns MistResult
 #[no_mangle]
-pub extern "C" fn mist_next_callback(
-    has_callback: *mut bool,
-    p_callback: *mut MistCallbackMsg,
-) -> MistResult {
-    let mut subprocess = get_subprocess!();
-    let has_processed_callback = subprocess.state().has_processed_callback;
-
-    // Get the callback queue
-    let queue = subprocess.client().callbacks();
-
-    if has_processed_callback {
-        queue.pop_front();
-    }
-
-    // Null the callback ptr ptr if the queue is empty
-    if let Some(front) = queue.front() {
-        unsafe {
-            *p_callback = MistCallbackMsg {
-                user: front.user,
-                callback: front.callback,
-                data: &front.data as *const _ as *const std::ffi::c_void,
-            };
-            *has_callback = true;
+pub extern "C" fn mist_set_hang_timeout(timeout_ms: u32) -> MistResult {
+    mist_export!({
+        service::set_hang_timeout(timeout_ms as u64);
+
+        Success
+    })
+}
+
+/// Creates a context with its own subprocess, the global functions use a separate default context
+/// The subprocess of the context is started with mist_context_subprocess_init
+/// # Safety
+/// context must be valid for writes
+/// Returns MistResult
+#[no_mangle]
+pub unsafe extern "C" fn mist_context_create(context: *mut *mut MistContext) -> MistResult {
+    mist_export!([context], {
+        *context = Box::into_raw(Box::new(MistContext::new()));
+
+        Success
+    })
+}
+
+/// Deinits the subprocess of the context if it is running and frees the context
+/// The default context can not be destroyed
+/// # Safety
+/// context must come from mist_context_create and is invalid afterwards
+/// Returns MistResult
+#[no_mangle]
+pub unsafe extern "C" fn mist_context_destroy(context: *mut MistContext) -> MistResult {
+    mist_export!([context], {
+        if context == default_context() {
+            mist_log_error("The default context can not be destroyed");
+            return Error::Mist(MistError::InvalidContext).into();
         }
 
-        subprocess.state_mut().has_processed_callback = true;
-    } else {
-        unsafe {
-            *has_callback = false;
+        let context = MistContext::from_raw(context);
+        if context.subprocess.lock().is_some() {
+            unwrap_client_result!(lib_subprocess::mist_deinit_subprocess(&context));
         }
-        subprocess.state_mut().has_processed_callback = false;
+
+        Success
+    })
+}
+
+mist_context_export! {
+    /// Init mist, this is throwns an error if it was already initialised
+    /// On Linux the subprocess is terminated when the thread which started it exits, so call it from a long-lived thread
+    /// Returns MistResult
+    fn subprocess_init(context) -> MistResult {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
+
+            unwrap_client_result!(lib_subprocess::mist_init_subprocess(context));
+
+            Success
+        })
+    }
+}
+
+mist_context_export! {
+    /// Kills the subprocess and starts a new one, meant for recovering from MistError_SubprocessHung
+    /// Registered callback handlers and callback subscriptions are kept, Steam Input has to be set up again
+    /// The previous state is kept if the new subprocess fails to start, so the restart can be retried
+    /// On Linux the new subprocess is terminated when the thread which restarted it exits
+    /// Returns MistResult
+    fn subprocess_restart(context) -> MistResult {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
+
+            unwrap_client_result!(lib_subprocess::mist_restart_subprocess(context));
+
+            Success
+        })
+    }
+}
+
+mist_context_export! {
+    /// Polls the subprocess
+    /// Callbacks with a registered handler are dispatched synchronously on the calling thread,
+    /// all other callbacks are queued for mist_next_callback
+    /// Returns MistResult
+    fn poll(context) -> MistResult {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
+
+            let dispatch = {
+                let mut subprocess = get_subprocess!(context);
+
+                unwrap_client_result!(subprocess.client().poll());
+
+                // Keep the callback currently handed out by mist_next_callback in the queue
+                let handed_out = subprocess.state().has_processed_callback as usize;
+                let queue = subprocess.client().callbacks();
+                let pending = queue.split_off(handed_out.min(queue.len()));
+
+                let mut dispatch = Vec::new();
+                for callback in pending {
+                    let state = subprocess.state();
+                    let registration = state
+                        .callback_handlers
+                        .get(&callback.callback)
+                        .or(state.catch_all_callback_handler.as_ref())
+                        .copied();
+
+                    match registration {
+                        Some(registration) => dispatch.push((registration, callback)),
+                        None => subprocess.client().callbacks().push_back(callback),
+                    }
+                }
+
+                dispatch
+            };
+
+            // The subprocess lock is released here so handlers can call into mist
+            for (registration, callback) in dispatch {
+                if let Some(handler) = registration.handler {
+                    let msg = callback.msg();
+                    handler(&msg, registration.user_data);
+                }
+            }
+
+            Success
+        })
+    }
+}
+
+mist_context_export! {
+    /// Registers a handler which mist_poll calls for every callback with the callback id
+    /// Passing NULL as handler unregisters the handler for the callback id
+    /// Callbacks passed to a handler are not returned by mist_next_callback
+    /// Safety: The MistCallbackMsg passed to the handler is only valid for the duration of the call
+    /// Returns MistResult
+    fn register_callback_handler(
+        context,
+        callback_id: u32,
+        handler: MistCallbackHandler,
+        user_data: *mut c_void,
+    ) -> MistResult {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            let handlers = &mut subprocess.state_mut().callback_handlers;
+            if handler.is_some() {
+                handlers.insert(
+                    callback_id,
+                    MistCallbackHandlerRegistration { handler, user_data },
+                );
+            } else {
+                handlers.remove(&callback_id);
+            }
+
+            Success
+        })
+    }
+}
+
+mist_context_export! {
+    /// Registers a handler which mist_poll calls for every callback without a handler for its callback id
+    /// Passing NULL as handler unregisters the catch-all handler
+    /// Callbacks passed to a handler are not returned by mist_next_callback
+    /// Safety: The MistCallbackMsg passed to the handler is only valid for the duration of the call
+    /// Returns MistResult
+    fn register_catch_all_callback_handler(
+        context,
+        handler: MistCallbackHandler,
+        user_data: *mut c_void,
+    ) -> MistResult {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+
+            subprocess.state_mut().catch_all_callback_handler = handler
+                .is_some()
+                .then_some(MistCallbackHandlerRegistration { handler, user_data });
+
+            Success
+        })
     }
+}
 
-    Success
+// NULL callback ids select all callbacks
+// Safety: callback_ids has to be NULL or valid for reads of count ids
+unsafe fn callback_ids_from_ptr(callback_ids: *const u32, count: u32) -> Option<Vec<u32>> {
+    (!callback_ids.is_null())
+        .then(|| std::slice::from_raw_parts(callback_ids, count as usize).to_vec())
+}
+
+mist_context_export! {
+    /// Subscribes to the count callback ids in callback_ids, passing NULL as callback_ids subscribes to all callbacks
+    /// All callbacks are subscribed to by default, the subprocess only sends subscribed callbacks
+    /// # Safety
+    /// callback_ids must be NULL or valid for reads of count ids
+    /// Returns MistResult
+    unsafe fn subscribe_callbacks(context, callback_ids: *const u32, count: u32) -> MistResult {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
+            let callback_ids = callback_ids_from_ptr(callback_ids, count);
+
+            let mut subprocess = get_subprocess!(context);
+
+            unwrap_client_result!(subprocess
+                .client()
+                .internal()
+                .subscribe_callbacks(callback_ids.clone()));
+            subprocess
+                .state_mut()
+                .callback_subscriptions
+                .set_subscribed(callback_ids, true);
+
+            Success
+        })
+    }
+}
+
+mist_context_export! {
+    /// Unsubscribes from the count callback ids in callback_ids, passing NULL as callback_ids unsubscribes from all callbacks
+    /// Unsubscribing from all callbacks and subscribing to the needed ones limits the subprocess to those callbacks
+    /// Callbacks already received by mist are still returned
+    /// # Safety
+    /// callback_ids must be NULL or valid for reads of count ids
+    /// Returns MistResult
+    unsafe fn unsubscribe_callbacks(context, callback_ids: *const u32, count: u32) -> MistResult {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
+            let callback_ids = callback_ids_from_ptr(callback_ids, count);
+
+            let mut subprocess = get_subprocess!(context);
+
+            unwrap_client_result!(subprocess
+                .client()
+                .internal()
+                .unsubscribe_callbacks(callback_ids.clone()));
+            subprocess
+                .state_mut()
+                .callback_subscriptions
+                .set_subscribed(callback_ids, false);
+
+            Success
+        })
+    }
+}
+
+mist_context_export! {
+    /// Attempts to return the next callback, if none are left it will set p_callback to NULL
+    /// # Safety
+    /// has_callback and p_callback must be valid for writes
+    /// The pointer is only valid until the next call of this function
+    /// Due to this it is not safe to simultaneously access callbacks from two different threads since they might race invalidate the other threads callback
+    /// Returns MistResult
+    unsafe fn next_callback(
+        context,
+        has_callback: *mut bool,
+        p_callback: *mut MistCallbackMsg,
+    ) -> MistResult {
+        mist_export!([context, has_callback, p_callback], {
+            let context = MistContext::from_ptr(context);
+
+            let mut subprocess = get_subprocess!(context);
+            let has_processed_callback = subprocess.state().has_processed_callback;
+
+            // Get the callback queue
+            let queue = subprocess.client().callbacks();
+
+            if has_processed_callback {
+                queue.pop_front();
+            }
+
+            // Null the callback ptr ptr if the queue is empty
+            if let Some(front) = queue.front() {
+                *p_callback = front.msg();
+                *has_callback = true;
+
+                subprocess.state_mut().has_processed_callback = true;
+            } else {
+                *has_callback = false;
+                subprocess.state_mut().has_processed_callback = false;
+            }
+
+            Success
+        })
+    }
+}
+
+mist_context_export! {
+    /// Blocks until a callback with the callback id is received, fails with MistError_Timeout if none arrives in time
+    /// Callbacks received while waiting stay queued for mist_poll and mist_next_callback
+    /// Other threads can call into mist while it waits, a callback they take from the queue meanwhile is not returned
+    /// # Safety
+    /// p_callback must be valid for writes
+    /// The pointer is only valid until the next call of this function
+    /// Returns MistResult
+    unsafe fn wait_for_callback(
+        context,
+        callback_id: u32,
+        timeout_ms: u32,
+        p_callback: *mut MistCallbackMsg,
+    ) -> MistResult {
+        mist_export!([context, p_callback], {
+            let context = MistContext::from_ptr(context);
+
+            let deadline = Instant::now() + Duration::from_millis(timeout_ms as u64);
+
+            loop {
+                let receiver = {
+                    let mut subprocess = get_subprocess!(context);
+
+                    // The callback handed out by mist_next_callback stays in the queue
+                    let handed_out = subprocess.state().has_processed_callback as usize;
+                    let callback = unwrap_client_result!(subprocess
+                        .client()
+                        .take_callback(callback_id, handed_out));
+
+                    if let Some(callback) = callback {
+                        let callback = subprocess.state_mut().waited_callback.insert(callback);
+                        *p_callback = callback.msg();
+
+                        return Success;
+                    }
+
+                    subprocess.client().receiver.clone()
+                };
+
+                // Waits for the next message without the subprocess lock, so other threads can call into mist meanwhile
+                // The message is left in the channel for whoever takes the lock next
+                let mut select = crossbeam_channel::Select::new();
+                select.recv(&receiver);
+                if select.ready_deadline(deadline).is_err() {
+                    return Error::Mist(MistError::Timeout).into();
+                }
+            }
+        })
+    }
+}
+
+/// Returns a static human-readable string describing the MistResult
+/// Returns const char *
+#[no_mangle]
+pub extern "C" fn mist_result_to_string(result: MistResult) -> *const c_char {
+    // Does not clear the last error message, so it can be used while reporting an error
+    mist_catch_panic(|| result::result_to_string(result).as_ptr() as *const c_char)
+}
+
+/// Returns the detailed message of the error logged by the last failed mist call on the calling thread
+/// Returns NULL if the last mist call on the calling thread did not log an error
+/// Safety: The string is only valid until the next mist call on the calling thread
+/// Returns const char *
+#[no_mangle]
+pub extern "C" fn mist_get_last_error_message() -> *const c_char {
+    mist_catch_panic(|| {
+        LAST_ERROR_MESSAGE.with(|msg| {
+            msg.borrow()
+                .as_ref()
+                .map(|msg| msg.as_ptr())
+                .unwrap_or(std::ptr::null())
+        })
+    })
 }
 
 #[path = "../lib/apps.rs"]
@@ -117,12 +673,17 @@ mod remote_storage;
 #[path = "../lib/utils.rs"]
 mod utils;
 
-/// Deinits the mist subprocess, returns false on error
-#[no_mangle]
-pub extern "C" fn mist_subprocess_deinit() -> MistResult {
-    unwrap_client_result!(lib_subprocess::mist_deinit_subprocess());
+mist_context_export! {
+    /// Deinits the mist subprocess, returns false on error
+    fn subprocess_deinit(context) -> MistResult {
+        mist_export!([context], {
+            let context = MistContext::from_ptr(context);
 
-    Success
+            unwrap_client_result!(lib_subprocess::mist_deinit_subprocess(context));
+
+            Success
+        })
+    }
 }
 
 // Workaround for build process
@@ -2,29 +2,122 @@ use parking_lot::Mutex;
 use std::{
     collections::HashMap,
     ffi::CString,
-    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+    sync::atomic::{AtomicPtr, AtomicUsize, Ordering},
 };
 
 use crate::{
-    input::MistSteamInputClient,
+    callbacks::{MistCallback, MistCallbackHandler, MistCallbackSubscriptions},
+    consts::MIST_MAX_GAMEPADS,
+    glyph_cache::MistGlyphCache,
+    input::{MistInputEventBacklog, MistSteamInputClient},
+    input_recorder::MistInputReplay,
+    motion::MistGyroPointer,
     result::{Error, MistError},
     service::{MistClient, MistServiceToLibrary},
+    transport::{MistPipeTransport, MistTransport, MistTransportReader, MistTransportWriter},
     types::*,
 };
 
+/// Env var overriding the path of the subprocess executable
+pub const MIST_SUBPROCESS_PATH_ENV: &str = "MIST_SUBPROCESS_PATH";
+
+/// Owns a subprocess and all of its state, opaque to C
+/// The global functions use a default context, further contexts are created with mist_context_create
+/// The log callback, call timeouts and last error message are shared by all contexts
+pub struct MistContext {
+    pub subprocess: Mutex<Option<MistSubprocess>>,
+    // The Steam Input shared memory is read without locking the subprocess
+    pub input_state: AtomicPtr<MistInputStateBuffered>,
+    pub input_buffer: AtomicUsize,
+    // Number of exports reading through input_state
+    pub input_readers: AtomicUsize,
+    // Memory input_state pointed to, freed once no export reads through input_state
+    retired_input_memory: Mutex<Vec<MistRetiredInputMemory>>,
+    // Replaces the shared memory while set, works without a subprocess
+    pub input_replay: Mutex<Option<MistInputReplay>>,
+    // Events left in the shared memory replaced by a resize
+    pub input_event_backlog: Mutex<MistInputEventBacklog>,
+    // Integrated from the motion data of the gamepad slots, which also works while replaying
+    pub gyro_pointers: Mutex<[MistGyroPointer; MIST_MAX_GAMEPADS]>,
+}
+
+impl MistContext {
+    pub fn new() -> MistContext {
+        MistContext {
+            subprocess: Mutex::new(None),
+            input_state: AtomicPtr::new(std::ptr::null_mut()),
+            input_buffer: AtomicUsize::new(0),
+            input_readers: AtomicUsize::new(0),
+            retired_input_memory: Mutex::new(Vec::new()),
+            input_replay: Mutex::new(None),
+            input_event_backlog: Mutex::new(MistInputEventBacklog::default()),
+            gyro_pointers: Mutex::new([MistGyroPointer::default(); MIST_MAX_GAMEPADS]),
+        }
+    }
+
+    /// Borrows the context behind a pointer passed to an export
+    ///
+    /// # Safety
+    /// The pointer has to come from mist_context_create or be the default context, and outlive the returned reference
+    pub(crate) unsafe fn from_ptr<'a>(context: *mut MistContext) -> &'a MistContext {
+        &*context
+    }
+
+    /// Takes back ownership of a context created by mist_context_create
+    ///
+    /// # Safety
+    /// The pointer has to come from mist_context_create and must not be used afterwards
+    pub(crate) unsafe fn from_raw(context: *mut MistContext) -> Box<MistContext> {
+        Box::from_raw(context)
+    }
+
+    // Keeps memory input_state pointed to alive while an export might still read it
+    // input_state has to point elsewhere with SeqCst ordering before the memory is retired
+    pub(crate) fn retire_input_memory(&self, memory: impl std::any::Any) {
+        self.retired_input_memory
+            .lock()
+            .push(MistRetiredInputMemory(Box::new(memory)));
+
+        self.free_retired_input_memory();
+    }
+
+    // Readers that started after the memory was retired can not see it, so it is free once there are no readers
+    pub(crate) fn free_retired_input_memory(&self) {
+        let mut retired = self.retired_input_memory.lock();
+
+        if !retired.is_empty() && self.input_readers.load(Ordering::SeqCst) == 0 {
+            retired.clear();
+        }
+    }
+}
+
+struct MistRetiredInputMemory(#[allow(dead_code)] Box<dyn std::any::Any>);
+
+// The memory is only dropped, never accessed
+unsafe impl Send for MistRetiredInputMemory {}
+
 lazy_static::lazy_static! {
-    pub static ref SUBPROCESS: Mutex<Option<MistSubprocess>> = Mutex::new(None);
+    static ref DEFAULT_CONTEXT: MistContext = MistContext::new();
+}
+
+/// The context used by the global functions, it lives for the whole process
+pub fn default_context() -> *mut MistContext {
+    &*DEFAULT_CONTEXT as *const MistContext as *mut MistContext
 }
 
 macro_rules! get_subprocess {
-    () => {{
-        let mut lock = $crate::lib_subprocess::SUBPROCESS.lock();
+    ($context:expr) => {{
+        let mut lock = $context.subprocess.lock();
 
         if let Some(inner) = lock.as_mut() {
-            if inner.is_alive() {
-                parking_lot::MutexGuard::map(lock, |inner| inner.as_mut().unwrap())
-            } else {
+            if !inner.is_alive() {
                 return crate::result::Error::Mist(crate::result::MistError::SubprocessLost).into();
+            } else if inner.is_hung() {
+                return crate::result::Error::Mist(crate::result::MistError::SubprocessHung).into();
+            } else {
+                parking_lot::MutexGuard::map(lock, |inner| inner.as_mut().unwrap())
             }
         } else {
             crate::mist_log_error("Subprocess has not been initialized");
@@ -34,6 +127,15 @@ macro_rules! get_subprocess {
     }};
 }
 
+#[derive(Clone, Copy)]
+pub struct MistCallbackHandlerRegistration {
+    pub handler: MistCallbackHandler,
+    pub user_data: *mut std::ffi::c_void,
+}
+
+// The user data is only passed back to the handler on the polling thread
+unsafe impl Send for MistCallbackHandlerRegistration {}
+
 #[derive(Default)]
 pub struct SubprocessState {
     pub avaliable_languages: Option<CString>,
@@ -41,28 +143,27 @@ pub struct SubprocessState {
     pub current_language: Option<CString>,
     pub entered_gamepad_text: Option<CString>,
     pub launch_query_params: HashMap<String, CString>,
-    pub glpyh_png: HashMap<
-        (
-            MistInputActionOrigin,
-            MistSteamInputGlyphSize,
-            MistSteamInputGlyphStyle,
-        ),
-        CString,
-    >,
-    pub glpyh_svg: HashMap<(MistInputActionOrigin, MistSteamInputGlyphStyle), CString>,
+    pub glyphs: MistGlyphCache,
     pub origin_strings: HashMap<MistInputActionOrigin, CString>,
+    pub xbox_origin_strings: HashMap<MistXboxOrigin, CString>,
     pub input_client: Option<MistSteamInputClient>,
     pub has_processed_callback: bool,
+    // Returned by mist_wait_for_callback, kept alive until the next wait
+    pub waited_callback: Option<MistCallback>,
+    pub callback_handlers: HashMap<u32, MistCallbackHandlerRegistration>,
+    pub catch_all_callback_handler: Option<MistCallbackHandlerRegistration>,
+    // Restored when the subprocess is restarted
+    pub callback_subscriptions: MistCallbackSubscriptions,
 }
 
 pub struct MistSubprocess {
-    client: MistClient<ChildStdout, ChildStdin>,
+    client: MistClient<MistTransportReader, MistTransportWriter>,
     proc: Child,
     state: SubprocessState,
 }
 
 impl MistSubprocess {
-    pub fn client(&mut self) -> &mut MistClient<ChildStdout, ChildStdin> {
+    pub fn client(&mut self) -> &mut MistClient<MistTransportReader, MistTransportWriter> {
         &mut self.client
     }
 
@@ -73,6 +174,11 @@ impl MistSubprocess {
             .unwrap_or(false)
     }
 
+    /// Returns true if the subprocess main loop stopped sending heartbeats
+    pub fn is_hung(&self) -> bool {
+        self.client.is_hung()
+    }
+
     pub fn state(&self) -> &SubprocessState {
         &self.state
     }
@@ -82,14 +188,8 @@ impl MistSubprocess {
     }
 }
 
-pub fn mist_init_subprocess() -> Result<(), Error> {
-    {
-        if SUBPROCESS.lock().is_some() {
-            crate::mist_log_error("The subprocess has already been initialized");
-            return Err(Error::Mist(MistError::SubprocessAlreadyInitialized));
-        }
-    }
-
+/// Spawns the subprocess and returns the transport connected to it
+pub fn spawn_process() -> Result<(Child, Box<dyn MistTransport>), Error> {
     let exe = if cfg!(unix) {
         "mist"
     } else if cfg!(windows) {
@@ -124,86 +224,137 @@ pub fn mist_init_subprocess() -> Result<(), Error> {
         }
     };
 
+    // Allows running a different subprocess, like mist-replay
+    let exe_path = std::env::var_os(MIST_SUBPROCESS_PATH_ENV)
+        .map(PathBuf::from)
+        .unwrap_or(exe_path);
+
     let exe_cwd_str = exe_cwd.to_string_lossy().to_string();
     let ld_library_path = std::env::var("LD_LIBRARY_PATH")
         .map(|p| p + ":" + &exe_cwd_str)
         .unwrap_or_else(|_| exe_cwd_str);
 
-    let mut proc = match Command::new(exe_path)
+    let mut command = Command::new(exe_path);
+    command
         .current_dir(exe_cwd)
         .arg(crate::consts::PROCESS_INIT_SECRET)
+        .env(
+            crate::consts::MIST_PARENT_PID_ENV,
+            std::process::id().to_string(),
+        )
         .env("LD_LIBRARY_PATH", ld_library_path.as_str())
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::inherit())
-        .spawn()
-    {
-        Ok(child) => child,
+        .stderr(Stdio::inherit());
+
+    #[cfg(target_os = "linux")]
+    set_parent_death_signal(&mut command);
+
+    #[cfg(unix)]
+    match socket_transport(&mut command) {
+        Ok((socket, child_socket)) => {
+            let child = command
+                .stdin(Stdio::null())
+                .stdout(Stdio::inherit())
+                .spawn();
+            // Only the subprocess keeps the other end open, so either side sees the stream close
+            drop(child_socket);
+
+            return match child {
+                Ok(child) => Ok((child, Box::new(socket))),
+                Err(err) => {
+                    crate::mist_log_error(&format!("Error spawning subprocess: {}", err));
+                    Err(Error::Mist(MistError::SubprocessSpawnError))
+                }
+            };
+        }
+        Err(err) => {
+            crate::mist_log_error(&format!(
+                "Error creating the IPC socket, falling back to pipes: {}",
+                err
+            ));
+        }
+    }
+
+    match command.stdin(Stdio::piped()).stdout(Stdio::piped()).spawn() {
+        Ok(mut child) => {
+            let transport = MistPipeTransport {
+                read: child.stdout.take().unwrap(),
+                write: child.stdin.take().unwrap(),
+            };
+            Ok((child, Box::new(transport)))
+        }
         Err(err) => {
             crate::mist_log_error(&format!("Error spawning subprocess: {}", err));
-            return Err(Error::Mist(MistError::SubprocessSpawnError));
+            Err(Error::Mist(MistError::SubprocessSpawnError))
         }
-    };
+    }
+}
 
-    let client = MistClient::create(proc.stdout.take().unwrap(), proc.stdin.take().unwrap());
-    let subprocess = MistSubprocess {
-        client,
-        proc,
-        state: SubprocessState::default(),
-    };
+// Creates a socketpair and sets up the command to pass one end to the subprocess as MIST_IPC_FD
+#[cfg(unix)]
+fn socket_transport(
+    command: &mut Command,
+) -> std::io::Result<(
+    std::os::unix::net::UnixStream,
+    std::os::unix::net::UnixStream,
+)> {
+    use crate::transport::{MIST_IPC_FD, MIST_IPC_FD_ENV};
+    use std::os::unix::{io::AsRawFd, process::CommandExt};
 
-    // Set the subprocess
-    *SUBPROCESS.lock() = Some(subprocess);
-
-    // Now let's wait for the init
-    let subprocess = get_subprocess!();
-
-    // Wait for the subprocess to initialize
-    match subprocess
-        .client
-        .receiver
-        .recv_timeout(std::time::Duration::from_secs(4))
-    {
-        Ok(msg) => match msg {
-            MistServiceToLibrary::Initialized => (),
-            MistServiceToLibrary::InitError(err) => {
-                crate::mist_log_error(&format!("Subprocess initialization error: {}", err));
-                return Err(Error::Mist(MistError::SubprocessInitializationError));
+    let (socket, child_socket) = std::os::unix::net::UnixStream::pair()?;
+    let child_fd = child_socket.as_raw_fd();
+
+    command.env(MIST_IPC_FD_ENV, MIST_IPC_FD.to_string());
+    // Runs in the forked child before exec, only async-signal-safe calls are allowed
+    unsafe {
+        command.pre_exec(move || {
+            // Both ends are created close-on-exec, dup2 clears the flag on the new fd
+            let res = if child_fd == MIST_IPC_FD {
+                let flags = libc::fcntl(child_fd, libc::F_GETFD);
+                libc::fcntl(child_fd, libc::F_SETFD, flags & !libc::FD_CLOEXEC)
+            } else {
+                libc::dup2(child_fd, MIST_IPC_FD)
+            };
+
+            if res == -1 {
+                Err(std::io::Error::last_os_error())
+            } else {
+                Ok(())
             }
-            _ => unreachable!(),
-        },
-        Err(err) => {
-            crate::mist_log_error(&format!("Subprocess initialization error: {}", err));
-            return Err(Error::Mist(MistError::SubprocessInitializationError));
-        }
+        });
     }
 
-    Ok(())
+    Ok((socket, child_socket))
 }
 
-pub fn mist_deinit_subprocess() -> Result<(), Error> {
-    let mut subprocess = match SUBPROCESS.lock().take() {
-        Some(s) => s,
-        None => {
-            crate::mist_log_error(
-                "The subprocess cannot be deinitialized when it has not been initialized.",
-            );
-            return Err(Error::Mist(MistError::SubprocessNotInitialized));
-        }
-    };
+// Terminates the subprocess when the library process dies, even if it is stuck in a Steam call
+// Linux sends the signal once the thread which spawned the subprocess exits, not the whole process
+#[cfg(target_os = "linux")]
+fn set_parent_death_signal(command: &mut Command) {
+    use std::os::unix::process::CommandExt;
 
-    // Tell the subprocess to terminate
-    subprocess.client().internal().exit()?;
+    let parent_pid = std::process::id() as libc::pid_t;
+    // Runs in the forked child before exec, only async-signal-safe calls are allowed
+    unsafe {
+        command.pre_exec(move || {
+            if libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGTERM) == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+
+            // The parent might have exited before the signal was set
+            if libc::getppid() != parent_pid {
+                return Err(std::io::Error::from_raw_os_error(libc::ESRCH));
+            }
+
+            Ok(())
+        });
+    }
+}
 
-    // Give it 500ms to terminate before killing the process
+/// Waits 500ms for the subprocess to exit before killing it
+pub fn wait_or_kill_process(proc: &mut Child) -> Result<(), Error> {
     let mut exited = false;
     for _ in 0..10 {
-        if subprocess
-            .proc
-            .try_wait()
-            .map(|e| e.is_some())
-            .unwrap_or(false)
-        {
+        if proc.try_wait().map(|e| e.is_some()).unwrap_or(false) {
             exited = true;
             break;
         }
@@ -212,7 +363,7 @@ pub fn mist_deinit_subprocess() -> Result<(), Error> {
     }
 
     if !exited {
-        match subprocess.proc.kill() {
+        match proc.kill() {
             Ok(_) => (),
             Err(err) => {
                 crate::mist_log_error(&format!("Error killing the subprocess: {}", err));
@@ -223,3 +374,196 @@ pub fn mist_deinit_subprocess() -> Result<(), Error> {
 
     Ok(())
 }
+
+impl MistSubprocess {
+    /// Spawns the subprocess and waits for it to initialize
+    pub fn spawn() -> Result<MistSubprocess, Error> {
+        let (mut proc, transport) = spawn_process()?;
+
+        let (read, write) = match transport.split() {
+            Ok(halves) => halves,
+            Err(err) => {
+                crate::mist_log_error(&format!("Error setting up the IPC transport: {}", err));
+                let _ = proc.kill();
+                return Err(Error::Mist(MistError::SubprocessSpawnError));
+            }
+        };
+        let client = MistClient::create(read, write);
+        let mut subprocess = MistSubprocess {
+            client,
+            proc,
+            state: SubprocessState::default(),
+        };
+
+        // Wait for the subprocess to initialize
+        let deadline = std::time::Instant::now() + crate::service::INIT_TIMEOUT;
+        loop {
+            match subprocess.client.receiver.recv_deadline(deadline) {
+                Ok(msg) => match msg {
+                    MistServiceToLibrary::Initialized => break,
+                    MistServiceToLibrary::InitError(err) => {
+                        crate::mist_log_error(&format!("Subprocess initialization error: {}", err));
+                        let _ = subprocess.proc.kill();
+                        return Err(Error::Mist(MistError::SubprocessInitializationError));
+                    }
+                    MistServiceToLibrary::Log(level, msg) => crate::mist_log(level, &msg),
+                    _ => unreachable!(),
+                },
+                Err(err) => {
+                    crate::mist_log_error(&format!("Subprocess initialization error: {}", err));
+                    let _ = subprocess.proc.kill();
+                    return Err(Error::Mist(MistError::SubprocessInitializationError));
+                }
+            }
+        }
+
+        Ok(subprocess)
+    }
+
+    /// Tells the subprocess to exit and kills it if it does not exit in time
+    pub fn shutdown(&mut self) -> Result<(), Error> {
+        // Tell the subprocess to terminate
+        self.client().internal().exit()?;
+
+        // Give it 500ms to terminate before killing the process
+        wait_or_kill_process(&mut self.proc)
+    }
+
+    /// Kills the subprocess without asking it to exit, used when it is hung
+    pub fn kill(&mut self) -> Result<(), Error> {
+        if let Err(err) = self.proc.kill() {
+            crate::mist_log_error(&format!("Error killing the subprocess: {}", err));
+            return Err(Error::Mist(MistError::SubprocessUnkillable));
+        }
+        let _ = self.proc.wait();
+
+        Ok(())
+    }
+}
+
+pub fn mist_init_subprocess(context: &MistContext) -> Result<(), Error> {
+    let mut lock = context.subprocess.lock();
+
+    if lock.is_some() {
+        crate::mist_log_error("The subprocess has already been initialized");
+        return Err(Error::Mist(MistError::SubprocessAlreadyInitialized));
+    }
+
+    *lock = Some(MistSubprocess::spawn()?);
+
+    Ok(())
+}
+
+/// Kills the subprocess and spawns a new one, the callback handlers and glyph cache are kept
+pub fn mist_restart_subprocess(context: &MistContext) -> Result<(), Error> {
+    let mut lock = context.subprocess.lock();
+
+    let mut old = match lock.take() {
+        Some(s) => s,
+        None => {
+            crate::mist_log_error(
+                "The subprocess cannot be restarted when it has not been initialized.",
+            );
+            return Err(Error::Mist(MistError::SubprocessNotInitialized));
+        }
+    };
+
+    // An exited subprocess can not be killed but is restarted all the same
+    if old.is_alive() {
+        if let Err(err) = old.kill() {
+            *lock = Some(old);
+            return Err(err);
+        }
+    }
+
+    let mut subprocess = match MistSubprocess::spawn() {
+        Ok(subprocess) => subprocess,
+        Err(err) => {
+            // Keeps the state of the exited subprocess so the restart can be retried
+            *lock = Some(old);
+            return Err(err);
+        }
+    };
+    subprocess.state.callback_handlers = std::mem::take(&mut old.state.callback_handlers);
+    subprocess.state.catch_all_callback_handler = old.state.catch_all_callback_handler.take();
+    subprocess.state.glyphs = std::mem::take(&mut old.state.glyphs);
+    subprocess.state.callback_subscriptions = std::mem::take(&mut old.state.callback_subscriptions);
+    // The old shared memory stays readable until the new subprocess is running, a replay keeps working without it
+    if context.input_replay.lock().is_none() {
+        context
+            .input_state
+            .store(std::ptr::null_mut(), Ordering::SeqCst);
+    }
+    if let Some(input_client) = old.state.input_client.take() {
+        input_client.retire(context);
+    }
+
+    let res = restore_callback_subscriptions(&mut subprocess);
+    *lock = Some(subprocess);
+
+    res
+}
+
+// The new subprocess starts with all callbacks subscribed
+fn restore_callback_subscriptions(subprocess: &mut MistSubprocess) -> Result<(), Error> {
+    let unsubscribed_by_default = subprocess
+        .state
+        .callback_subscriptions
+        .unsubscribed_by_default;
+    let exceptions: Vec<u32> = subprocess
+        .state
+        .callback_subscriptions
+        .exceptions
+        .iter()
+        .copied()
+        .collect();
+
+    if unsubscribed_by_default {
+        subprocess.client().internal().unsubscribe_callbacks(None)?;
+    }
+
+    if !exceptions.is_empty() {
+        if unsubscribed_by_default {
+            subprocess
+                .client()
+                .internal()
+                .subscribe_callbacks(Some(exceptions))?;
+        } else {
+            subprocess
+                .client()
+                .internal()
+                .unsubscribe_callbacks(Some(exceptions))?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn mist_deinit_subprocess(context: &MistContext) -> Result<(), Error> {
+    let mut lock = context.subprocess.lock();
+    // A replay keeps working without the shared memory
+    if context.input_replay.lock().is_none() {
+        context
+            .input_state
+            .store(std::ptr::null_mut(), Ordering::SeqCst);
+    }
+
+    let mut subprocess = match lock.take() {
+        Some(s) => s,
+        None => {
+            crate::mist_log_error(
+                "The subprocess cannot be deinitialized when it has not been initialized.",
+            );
+            return Err(Error::Mist(MistError::SubprocessNotInitialized));
+        }
+    };
+
+    let res = subprocess.shutdown();
+    if let Some(input_client) = subprocess.state.input_client.take() {
+        input_client.retire(context);
+    }
+    // Glyphs resolved one at a time are only written on request or here
+    crate::input::flush_glyph_cache(&mut subprocess.state.glyphs);
+
+    res
+}
@@ -4,14 +4,31 @@
 mod codegen;
 mod callbacks;
 mod consts;
+mod framing;
+mod haptic_pattern;
+mod manifest;
+#[allow(dead_code)] // Only used by the library
+mod recorder;
 mod result;
 mod service;
+#[allow(dead_code)] // The library side is unused
+mod transport;
 mod types;
 
 use consts::PROCESS_INIT_SECRET;
+use types::MistLogLevel;
 
 mod subprocess;
 
+// Log records are forwarded to the library
+pub fn mist_log(level: MistLogLevel, msg: &str) {
+    subprocess::log(level, msg);
+}
+
+pub fn mist_log_error(err: &str) {
+    mist_log(MistLogLevel::Error, err);
+}
+
 fn main() {
     // Keep users away
     if std::env::args()
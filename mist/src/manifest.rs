@@ -0,0 +1,231 @@
+//! Reads the input action manifest (vdf) so the Steam Input shared memory has room for all of its actions
+
+use std::{collections::HashSet, ffi::CStr};
+
+use crate::types::{MistInputStateLayout, MistLogLevel};
+
+// Counts the distinct analog and digital actions of an input action manifest (vdf)
+pub fn count_manifest_actions(manifest: &str) -> (usize, usize) {
+    let mut analog_actions = HashSet::new();
+    let mut digital_actions = HashSet::new();
+
+    let mut blocks: Vec<String> = Vec::new();
+    let mut key: Option<String> = None;
+    let mut chars = manifest.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let token = match c {
+            '{' => {
+                blocks.push(key.take().unwrap_or_default());
+                continue;
+            }
+            '}' => {
+                blocks.pop();
+                key = None;
+                continue;
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                chars.find(|c| *c == '\n');
+                continue;
+            }
+            '"' => {
+                let mut token = String::new();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => token.extend(chars.next()),
+                        c => token.push(c),
+                    }
+                }
+                token
+            }
+            c if c.is_whitespace() => continue,
+            c => {
+                let mut token = c.to_string();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && !"{}\"".contains(*c)) {
+                    token.push(c);
+                }
+                token
+            }
+        };
+
+        // Tokens alternate between keys and values, blocks take the place of a value
+        if key.take().is_none() {
+            match blocks
+                .last()
+                .map(|block| block.to_ascii_lowercase())
+                .as_deref()
+            {
+                Some("stickpadgyro") | Some("analogtrigger") => {
+                    analog_actions.insert(token.clone());
+                }
+                Some("button") => {
+                    digital_actions.insert(token.clone());
+                }
+                _ => (),
+            }
+            key = Some(token);
+        }
+    }
+
+    (analog_actions.len(), digital_actions.len())
+}
+
+// Layout with room for all actions of the manifest, None if it could not be read
+pub fn manifest_layout(path: &CStr) -> Option<MistInputStateLayout> {
+    let manifest = match std::fs::read_to_string(path.to_string_lossy().as_ref()) {
+        Ok(manifest) => manifest,
+        Err(err) => {
+            crate::mist_log(
+                MistLogLevel::Warning,
+                &format!("Error reading the input action manifest: {}", err),
+            );
+            return None;
+        }
+    };
+
+    Some(layout_for_manifest(&manifest))
+}
+
+// Never smaller than the default layout, so action handles requested without a manifest still fit
+fn layout_for_manifest(manifest: &str) -> MistInputStateLayout {
+    let (analog_actions, digital_actions) = count_manifest_actions(manifest);
+    let default_layout = MistInputStateLayout::default();
+
+    MistInputStateLayout {
+        analog_action_capacity: analog_actions.max(default_layout.analog_action_capacity),
+        digital_action_capacity: digital_actions.max(default_layout.digital_action_capacity),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_actions_of_nested_sets() {
+        let manifest = r##"
+            "In Game Actions"
+            {
+                "actions"
+                {
+                    "InGameControls"
+                    {
+                        "title" "#Set_Ingame"
+                        "StickPadGyro"
+                        {
+                            "Move"
+                            {
+                                "title" "#Action_Move"
+                                "input_mode" "joystick_move"
+                            }
+                            "Camera"
+                            {
+                                "title" "#Action_Camera"
+                                "input_mode" "absolute_mouse"
+                            }
+                        }
+                        "AnalogTrigger"
+                        {
+                            "Throttle" "#Action_Throttle"
+                        }
+                        "Button"
+                        {
+                            "fire" "#Action_Fire"
+                            "Jump" "#Action_Jump"
+                        }
+                    }
+                    "MenuControls"
+                    {
+                        "title" "#Set_Menu"
+                        "Button"
+                        {
+                            "fire" "#Menu_Select"
+                            "menu_up" "#Menu_Up"
+                        }
+                    }
+                }
+                "localization"
+                {
+                    "english"
+                    {
+                        "Action_Move" "Move"
+                    }
+                }
+            }
+        "##;
+
+        // Actions shared by sets are counted once
+        assert_eq!(count_manifest_actions(manifest), (3, 3));
+    }
+
+    #[test]
+    fn ignores_braces_in_quotes() {
+        let manifest = r##"
+            "Button"
+            {
+                "open{" "#Action_Open"
+                "close" "#Action_} \"Close\""
+            }
+        "##;
+
+        assert_eq!(count_manifest_actions(manifest), (0, 2));
+    }
+
+    #[test]
+    fn ignores_comments() {
+        let manifest = r##"
+            "Button"
+            {
+                // "commented" "#Action_Commented"
+                "fire" "#Action_Fire" // "trailing" "#Action_Trailing"
+            }
+            // "AnalogTrigger" { "throttle" "#Action_Throttle" }
+        "##;
+
+        assert_eq!(count_manifest_actions(manifest), (0, 1));
+    }
+
+    #[test]
+    fn empty_manifest() {
+        assert_eq!(count_manifest_actions(""), (0, 0));
+        assert_eq!(count_manifest_actions("  \n\t"), (0, 0));
+    }
+
+    #[test]
+    fn malformed_manifest() {
+        // Unbalanced blocks and dangling keys are counted as far as they can be read
+        assert_eq!(count_manifest_actions(r##""Button" { "fire" "##), (0, 1));
+        assert_eq!(
+            count_manifest_actions(r##"} } "Button" { "fire" "x" } }"##),
+            (0, 1)
+        );
+        assert_eq!(
+            count_manifest_actions(r##""Button" { "unterminated"##),
+            (0, 1)
+        );
+        assert_eq!(count_manifest_actions("{{{"), (0, 0));
+    }
+
+    #[test]
+    fn layout_fits_all_actions() {
+        let default_layout = MistInputStateLayout::default();
+        assert_eq!(layout_for_manifest(""), default_layout);
+
+        let digital_actions = default_layout.digital_action_capacity + 10;
+        let manifest = format!(
+            "\"Button\" {{ {} }}",
+            (0..digital_actions)
+                .map(|i| format!("\"action_{}\" \"#Action\"", i))
+                .collect::<Vec<_>>()
+                .join(" ")
+        );
+
+        let layout = layout_for_manifest(&manifest);
+        assert_eq!(layout.digital_action_capacity, digital_actions);
+        assert_eq!(
+            layout.analog_action_capacity,
+            default_layout.analog_action_capacity
+        );
+    }
+}
@@ -0,0 +1,290 @@
+//! Helpers turning the motion data of a gamepad into pointer and lightgun input
+//! Rotation velocities are in degrees per second, pitch is around the x axis and yaw around the z axis
+//! The orientation quaternion is relative to a world where z points up
+
+use crate::types::MistInputHandle;
+pub use crate::types::{
+    MistGyroPointerConfig, MistGyroPointerState, MistInputMotionData, MistVector3,
+};
+
+// Snapshots further apart are treated as a pause, so the pointer does not jump
+const MAX_DELTA_TIME: f32 = 0.1;
+
+impl MistInputMotionData {
+    /// Direction of gravity relative to the gamepad as a unit vector
+    pub fn gravity(&self) -> MistVector3 {
+        let (w, x, y, z) = (
+            self.rot_quat_w,
+            self.rot_quat_x,
+            self.rot_quat_y,
+            self.rot_quat_z,
+        );
+
+        // The world up axis rotated into the frame of the gamepad, gravity points the other way
+        let up = MistVector3 {
+            x: 2.0 * (x * z - w * y),
+            y: 2.0 * (y * z + w * x),
+            z: 1.0 - 2.0 * (x * x + y * y),
+        };
+
+        let len = (up.x * up.x + up.y * up.y + up.z * up.z).sqrt();
+        if len == 0.0 {
+            return MistVector3::default();
+        }
+
+        MistVector3 {
+            x: -up.x / len,
+            y: -up.y / len,
+            z: -up.z / len,
+        }
+    }
+}
+
+/// Integrates the gyro of one gamepad into a pointer position, learning the gyro drift while the gamepad is still
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MistGyroPointer {
+    pub config: MistGyroPointerConfig,
+    state: MistGyroPointerState,
+    // Learned drift of the pitch and yaw velocity
+    bias: (f32, f32),
+    last_timestamp_us: Option<u64>,
+    input_handle: MistInputHandle,
+}
+
+impl MistGyroPointer {
+    pub fn new(config: MistGyroPointerConfig) -> MistGyroPointer {
+        MistGyroPointer {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Advances the pointer to the snapshot taken at timestamp_us
+    /// Updating again with the same snapshot returns the same position without movement
+    /// The drift and position are reset when a different controller is in the slot
+    pub fn update(
+        &mut self,
+        input_handle: MistInputHandle,
+        motion: &MistInputMotionData,
+        timestamp_us: u64,
+    ) -> MistGyroPointerState {
+        if input_handle != self.input_handle {
+            *self = MistGyroPointer {
+                input_handle,
+                ..MistGyroPointer::new(self.config)
+            };
+        }
+
+        let dt = match self.last_timestamp_us {
+            Some(last) if timestamp_us > last => {
+                ((timestamp_us - last) as f32 / 1_000_000.0).min(MAX_DELTA_TIME)
+            }
+            Some(last) if timestamp_us == last => {
+                return MistGyroPointerState {
+                    delta_x: 0.0,
+                    delta_y: 0.0,
+                    ..self.state
+                }
+            }
+            // The clock starts over with a new subprocess
+            Some(_) | None => 0.0,
+        };
+        self.last_timestamp_us = Some(timestamp_us);
+
+        let pitch = motion.rot_vel_x - self.bias.0;
+        let yaw = motion.rot_vel_z - self.bias.1;
+        let speed = (pitch * pitch + yaw * yaw).sqrt();
+
+        if speed < self.config.recenter_threshold {
+            let rate = (self.config.recenter_rate * dt).min(1.0);
+            self.bias.0 += (motion.rot_vel_x - self.bias.0) * rate;
+            self.bias.1 += (motion.rot_vel_z - self.bias.1) * rate;
+        }
+
+        // Turning left or tilting up moves the pointer left or up
+        let gain = self.config.sensitivity * (1.0 + self.config.acceleration * speed / 100.0);
+        let delta_x = -yaw * dt * gain;
+        let delta_y = -pitch * dt * gain;
+
+        let limit = 1.0 + self.config.offscreen_margin.max(0.0);
+        let x = (self.state.x + delta_x).clamp(-limit, limit);
+        let y = (self.state.y + delta_y).clamp(-limit, limit);
+
+        self.state = MistGyroPointerState {
+            delta_x: x - self.state.x,
+            delta_y: y - self.state.y,
+            x,
+            y,
+        };
+
+        self.state
+    }
+
+    /// Moves the pointer back to the center of the screen
+    pub fn recenter(&mut self) {
+        self.state = MistGyroPointerState::default();
+    }
+}
+
+/// Converts a pointer position to libretro pointer coordinates, positions past the edges are clamped
+pub fn to_libretro_pointer(x: f32, y: f32) -> (i16, i16) {
+    let to_coord = |pos: f32| (pos.clamp(-1.0, 1.0) * 0x7fff as f32).round() as i16;
+
+    (to_coord(x), to_coord(y))
+}
+
+/// Converts a pointer position to libretro lightgun coordinates and whether it is offscreen
+pub fn to_libretro_lightgun(x: f32, y: f32) -> (i16, i16, bool) {
+    let (screen_x, screen_y) = to_libretro_pointer(x, y);
+
+    (screen_x, screen_y, x.abs() > 1.0 || y.abs() > 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-4, "{} != {}", a, b);
+    }
+
+    fn rotating(rot_vel_x: f32, rot_vel_z: f32) -> MistInputMotionData {
+        MistInputMotionData {
+            rot_quat_w: 1.0,
+            rot_vel_x,
+            rot_vel_z,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn gravity_of_flat_and_tilted_gamepad() {
+        let gravity = rotating(0.0, 0.0).gravity();
+        assert_close(gravity.x, 0.0);
+        assert_close(gravity.y, 0.0);
+        assert_close(gravity.z, -1.0);
+
+        // Rotated 90 degrees around the x axis
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+        let gravity = MistInputMotionData {
+            rot_quat_w: half,
+            rot_quat_x: half,
+            ..Default::default()
+        }
+        .gravity();
+        assert_close(gravity.x, 0.0);
+        assert_close(gravity.y, -1.0);
+        assert_close(gravity.z, 0.0);
+
+        // A quaternion which is not normalized still gives a unit vector
+        let gravity = MistInputMotionData {
+            rot_quat_w: 2.0,
+            ..Default::default()
+        }
+        .gravity();
+        assert_close(gravity.z, -1.0);
+    }
+
+    #[test]
+    fn gyro_pointer_moves_with_rotation() {
+        let config = MistGyroPointerConfig::default();
+        let mut pointer = MistGyroPointer::new(config);
+
+        // The first snapshot only sets the time
+        let state = pointer.update(1, &rotating(0.0, 30.0), 1_000_000);
+        assert_eq!(state, MistGyroPointerState::default());
+
+        // Turning left and tilting up moves the pointer left and up
+        let state = pointer.update(1, &rotating(30.0, 30.0), 1_100_000);
+        assert_close(state.x, -30.0 * 0.1 * config.sensitivity);
+        assert_close(state.y, -30.0 * 0.1 * config.sensitivity);
+        assert_eq!((state.delta_x, state.delta_y), (state.x, state.y));
+
+        // The same snapshot again keeps the position without movement
+        let again = pointer.update(1, &rotating(30.0, 30.0), 1_100_000);
+        assert_eq!((again.x, again.y), (state.x, state.y));
+        assert_eq!((again.delta_x, again.delta_y), (0.0, 0.0));
+
+        // A pause is treated as the longest frame
+        let paused = pointer.update(1, &rotating(0.0, -30.0), 11_100_000);
+        assert_close(
+            paused.x - state.x,
+            30.0 * MAX_DELTA_TIME * config.sensitivity,
+        );
+
+        // An earlier timestamp comes from a restarted clock and only sets the time
+        let restarted = pointer.update(1, &rotating(0.0, -30.0), 1_000);
+        assert_eq!((restarted.x, restarted.y), (paused.x, paused.y));
+        assert_eq!((restarted.delta_x, restarted.delta_y), (0.0, 0.0));
+
+        // Another controller in the slot starts from the center
+        let other = pointer.update(2, &rotating(0.0, 30.0), 11_200_000);
+        assert_eq!(other, MistGyroPointerState::default());
+
+        pointer.update(2, &rotating(0.0, 30.0), 11_300_000);
+        pointer.recenter();
+        let recentered = pointer.update(2, &rotating(0.0, 0.0), 11_400_000);
+        assert_eq!((recentered.x, recentered.y), (0.0, 0.0));
+    }
+
+    #[test]
+    fn gyro_pointer_is_clamped_to_the_margin() {
+        let config = MistGyroPointerConfig::default();
+        let mut pointer = MistGyroPointer::new(config);
+        let limit = 1.0 + config.offscreen_margin;
+
+        pointer.update(1, &rotating(0.0, 0.0), 0);
+        let state = pointer.update(1, &rotating(-1000.0, -1000.0), 100_000);
+        assert_eq!((state.x, state.y), (limit, limit));
+        assert_eq!((state.delta_x, state.delta_y), (limit, limit));
+
+        // Only the movement within the margin is reported
+        let state = pointer.update(1, &rotating(-1000.0, -1000.0), 200_000);
+        assert_eq!((state.x, state.y), (limit, limit));
+        assert_eq!((state.delta_x, state.delta_y), (0.0, 0.0));
+    }
+
+    #[test]
+    fn gyro_pointer_learns_drift_while_still() {
+        let drift = 1.0;
+        let config = MistGyroPointerConfig::default();
+        let mut pointer = MistGyroPointer::new(config);
+        let mut without_recenter = MistGyroPointer::new(MistGyroPointerConfig {
+            recenter_rate: 0.0,
+            ..config
+        });
+
+        let mut state = MistGyroPointerState::default();
+        let mut drifted = MistGyroPointerState::default();
+        for frame in 0..2000 {
+            state = pointer.update(1, &rotating(drift, drift), frame * 10_000);
+            drifted = without_recenter.update(1, &rotating(drift, drift), frame * 10_000);
+        }
+
+        // The drift is learned and no longer moves the pointer
+        assert_close(state.delta_x, 0.0);
+        assert_close(state.delta_y, 0.0);
+        assert!(state.x.abs() < 0.5 && state.y.abs() < 0.5);
+
+        // Without learning the drift the pointer ends up at the edge of the margin
+        let limit = 1.0 + config.offscreen_margin;
+        assert_eq!((drifted.x, drifted.y), (-limit, -limit));
+
+        // Rotating faster than the threshold is movement, not drift
+        let before = state;
+        let state = pointer.update(1, &rotating(drift, 30.0 + drift), 20_000_000);
+        assert_close(state.x - before.x, -30.0 * 0.01 * config.sensitivity);
+    }
+
+    #[test]
+    fn libretro_coordinates() {
+        assert_eq!(to_libretro_pointer(0.0, 0.0), (0, 0));
+        assert_eq!(to_libretro_pointer(-1.0, 1.0), (-0x7fff, 0x7fff));
+        assert_eq!(to_libretro_pointer(0.5, -0.5), (0x4000, -0x4000));
+        assert_eq!(to_libretro_pointer(3.0, -3.0), (0x7fff, -0x7fff));
+
+        assert_eq!(to_libretro_lightgun(1.0, -1.0), (0x7fff, -0x7fff, false));
+        assert_eq!(to_libretro_lightgun(1.1, 0.0), (0x7fff, 0, true));
+        assert_eq!(to_libretro_lightgun(0.0, -1.2), (0, -0x7fff, true));
+    }
+}
@@ -0,0 +1,208 @@
+//! IPC traffic recorder used for debugging
+//! Setting MIST_IPC_RECORD to a file path makes the library record every framed message
+//! sent to and received from the subprocess, the recording can be decoded and replayed by mist-replay
+//! To replay a recording against the library point MIST_SUBPROCESS_PATH at mist-replay and MIST_REPLAY_FILE at the recording
+//!
+//! Recording format: the magic bytes followed by records of
+//! direction (u8), microseconds since the recording started (u64 le), payload length (u32 le) and the bincode payload
+
+use parking_lot::Mutex;
+use std::{
+    fs::File,
+    io::{Read, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    framing::{decode, MistFrameReader},
+    service::{MistLibraryToService, MistServiceToLibrary},
+};
+
+pub use crate::consts::PROCESS_INIT_SECRET;
+
+/// Env var holding the path of the file to record to
+pub const MIST_IPC_RECORD_ENV: &str = "MIST_IPC_RECORD";
+/// Env var holding the path of the recording mist-replay plays back when spawned as the subprocess
+pub const MIST_REPLAY_FILE_ENV: &str = "MIST_REPLAY_FILE";
+
+const MIST_IPC_RECORD_MAGIC: &[u8; 8] = b"MISTIPC1";
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum MistIpcDirection {
+    LibraryToService = 0,
+    ServiceToLibrary = 1,
+}
+
+pub struct MistIpcRecord {
+    pub direction: MistIpcDirection,
+    /// Time since the recording started
+    pub time: Duration,
+    /// The bincode payload without the length prefix
+    pub payload: Vec<u8>,
+}
+
+impl MistIpcRecord {
+    /// Decodes the payload into a readable message
+    pub fn describe(&self) -> String {
+        let msg = match self.direction {
+            MistIpcDirection::LibraryToService => {
+                decode::<MistLibraryToService>(&self.payload).map(|msg| format!("{:?}", msg))
+            }
+            MistIpcDirection::ServiceToLibrary => {
+                decode::<MistServiceToLibrary>(&self.payload).map(|msg| format!("{:?}", msg))
+            }
+        };
+
+        msg.unwrap_or_else(|err| format!("<undecodable payload: {}>", err))
+    }
+
+    /// Writes the payload with its length prefix, as it was sent over the transport
+    pub fn write_frame<W: Write>(&self, write: &mut W) -> std::io::Result<()> {
+        write.write_all(&(self.payload.len() as u32).to_le_bytes())?;
+        write.write_all(&self.payload)?;
+        write.flush()
+    }
+}
+
+struct MistIpcRecorder {
+    file: File,
+    start: Instant,
+}
+
+lazy_static::lazy_static! {
+    static ref RECORDER: Option<Mutex<MistIpcRecorder>> = open_recorder();
+}
+
+fn open_recorder() -> Option<Mutex<MistIpcRecorder>> {
+    let path = std::env::var_os(MIST_IPC_RECORD_ENV)?;
+
+    let file = File::create(&path).and_then(|mut file| {
+        file.write_all(MIST_IPC_RECORD_MAGIC)?;
+        Ok(file)
+    });
+
+    match file {
+        Ok(file) => Some(Mutex::new(MistIpcRecorder {
+            file,
+            start: Instant::now(),
+        })),
+        Err(err) => {
+            crate::mist_log_error(&format!(
+                "Error creating IPC recording {}: {}",
+                path.to_string_lossy(),
+                err
+            ));
+            None
+        }
+    }
+}
+
+/// Records a message payload if recording is enabled
+pub fn record(direction: MistIpcDirection, payload: &[u8]) {
+    if let Some(recorder) = RECORDER.as_ref() {
+        let mut recorder = recorder.lock();
+        let time = recorder.start.elapsed().as_micros() as u64;
+
+        // Written in one go so a crash never leaves half a record header
+        let mut record = Vec::with_capacity(1 + 8 + 4 + payload.len());
+        record.push(direction as u8);
+        record.extend_from_slice(&time.to_le_bytes());
+        record.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        record.extend_from_slice(payload);
+
+        if let Err(err) = recorder.file.write_all(&record) {
+            crate::mist_log_error(&format!("Error writing IPC recording: {}", err));
+        }
+    }
+}
+
+/// Reads all records from a recording, a truncated last record is ignored
+pub fn read_recording<P: AsRef<Path>>(path: P) -> std::io::Result<Vec<MistIpcRecord>> {
+    let mut data = Vec::new();
+    File::open(path)?.read_to_end(&mut data)?;
+
+    if !data.starts_with(MIST_IPC_RECORD_MAGIC) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "Not a mist IPC recording",
+        ));
+    }
+
+    let mut records = Vec::new();
+    let mut rest = &data[MIST_IPC_RECORD_MAGIC.len()..];
+
+    while rest.len() >= 1 + 8 + 4 {
+        let direction = match rest[0] {
+            0 => MistIpcDirection::LibraryToService,
+            1 => MistIpcDirection::ServiceToLibrary,
+            direction => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Invalid record direction {}", direction),
+                ))
+            }
+        };
+        let time = u64::from_le_bytes(rest[1..9].try_into().unwrap());
+        let len = u32::from_le_bytes(rest[9..13].try_into().unwrap()) as usize;
+        rest = &rest[13..];
+
+        if rest.len() < len {
+            break;
+        }
+
+        records.push(MistIpcRecord {
+            direction,
+            time: Duration::from_micros(time),
+            payload: rest[..len].to_vec(),
+        });
+        rest = &rest[len..];
+    }
+
+    Ok(records)
+}
+
+/// Plays back the subprocess side of a recording
+/// Every recorded library message is read from read and compared, differences are reported to stderr
+/// Recorded subprocess messages are written to write with the recorded timing
+pub fn replay<R: Read, W: Write>(
+    records: &[MistIpcRecord],
+    read: R,
+    mut write: W,
+) -> std::io::Result<()> {
+    let start = Instant::now();
+    let mut reader = MistFrameReader::new(read);
+
+    for (i, record) in records.iter().enumerate() {
+        match record.direction {
+            MistIpcDirection::LibraryToService => {
+                let payload = reader.read_frame().map_err(std::io::Error::other)?;
+
+                if payload != record.payload {
+                    let received = MistIpcRecord {
+                        direction: MistIpcDirection::LibraryToService,
+                        time: start.elapsed(),
+                        payload,
+                    };
+
+                    eprintln!(
+                        "[mist-replay] Record {} differs, expected {} received {}",
+                        i,
+                        record.describe(),
+                        received.describe()
+                    );
+                }
+            }
+            MistIpcDirection::ServiceToLibrary => {
+                if let Some(wait) = record.time.checked_sub(start.elapsed()) {
+                    std::thread::sleep(wait);
+                }
+
+                record.write_frame(&mut write)?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -10,32 +10,44 @@ pub const Success: MistResult = 0;
 mist_errors! {
     // Mist errors
     Mist: 1 {
-        InternalError = 0,
-        Timeout,
-        SubprocessLost = 10,
-        SubprocessNotInitialized,
-        SubprocessAlreadyInitialized,
-        SubprocessSpawnError,
-        SubprocessInitializationError,
-        SubprocessUnkillable,
-        SubprocessNotFound,
-        InvalidString = 20
+        InternalError = 0 => "Internal mist error",
+        Timeout => "Timed out waiting for the subprocess to reply",
+        Panic => "Internal panic inside mist",
+        SubprocessLost = 10 => "Lost the connection to the subprocess",
+        SubprocessNotInitialized => "The subprocess has not been initialized",
+        SubprocessAlreadyInitialized => "The subprocess has already been initialized",
+        SubprocessSpawnError => "Error spawning the subprocess",
+        SubprocessInitializationError => "The subprocess failed to initialize",
+        SubprocessUnkillable => "The subprocess could not be killed",
+        SubprocessNotFound => "The subprocess executable could not be found",
+        SubprocessHung => "The subprocess stopped responding",
+        InvalidString = 20 => "Invalid string",
+        NullPointer => "A required pointer argument was NULL",
+        InvalidIndex => "Index out of range",
+        InvalidContext => "Invalid context",
+        InvalidInterface => "Invalid interface"
     },
     SteamApps: 100 {
-        InvalidDlcIndex = 0
+        InvalidDlcIndex = 0 => "Invalid DLC index"
     },
     SteamFriends: 105 {
-        InvalidRichPresence = 0
+        InvalidRichPresence = 0 => "Invalid rich presence key or value"
     },
     SteamInput: 111 {
-        NotInitialized = 0,
-        ShmemError
+        NotInitialized = 0 => "Steam Input has not been initialized",
+        ShmemError => "Error setting up the Steam Input shared memory",
+        ShmemVersionMismatch => "The Steam Input shared memory was created by a different version of mist",
+        TooManyActions => "There are no slots left in the Steam Input shared memory for the action",
+        RecordingError => "Error reading or writing the Steam Input recording",
+        GlyphError => "Error loading or caching the glyph",
+        InvalidHapticPattern => "The haptic pattern has no duration or its keyframes are not in order",
+        SnapshotUnavailable => "The Steam Input snapshot could not be read, the subprocess stopped while writing it"
     },
     SteamRemoteStorage: 123 {
-        FileWriteBatchAlreadyInProgress = 0,
-        FileWriteBatchNotInProgress
+        FileWriteBatchAlreadyInProgress = 0 => "A file write batch is already in progress",
+        FileWriteBatchNotInProgress => "No file write batch is in progress"
     },
     SteamUtils: 128 {
-        NoGamepadTextEntered = 0
+        NoGamepadTextEntered = 0 => "No gamepad text has been entered"
     }
 }
@@ -1,11 +1,76 @@
 use std::{
     ffi::CString,
     os::raw::{c_char, c_int, c_ushort},
+    sync::atomic::{AtomicU64, Ordering},
 };
 
 use crate::types::*;
 
 const DEFAULT_TIMEOUT: u64 = 100;
+// Above the longest built-in call timeout, as the subprocess loop is blocked while handling a call
+const DEFAULT_HANG_TIMEOUT: u64 = 15_000;
+// Time given to the subprocess to initialize Steamworks
+pub(crate) const INIT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(4);
+
+// Timeouts configured at runtime in ms, 0 when not set
+static CALL_TIMEOUT: AtomicU64 = AtomicU64::new(0);
+static INTERFACE_CALL_TIMEOUTS: [AtomicU64; MistInterface::COUNT] =
+    [const { AtomicU64::new(0) }; MistInterface::COUNT];
+static HANG_TIMEOUT: AtomicU64 = AtomicU64::new(0);
+
+/// Sets the timeout of all calls without an interface timeout, 0 restores the default
+pub fn set_call_timeout(timeout_ms: u64) {
+    CALL_TIMEOUT.store(timeout_ms, Ordering::Relaxed);
+}
+
+/// Sets the timeout of all calls to the interface, 0 falls back to the call timeout
+pub fn set_interface_call_timeout(interface: MistInterface, timeout_ms: u64) {
+    INTERFACE_CALL_TIMEOUTS[interface as usize].store(timeout_ms, Ordering::Relaxed);
+}
+
+/// Sets the time without messages after which the subprocess is considered hung, 0 restores the default
+pub fn set_hang_timeout(timeout_ms: u64) {
+    HANG_TIMEOUT.store(timeout_ms, Ordering::Relaxed);
+}
+
+fn hang_timeout() -> std::time::Duration {
+    match HANG_TIMEOUT.load(Ordering::Relaxed) {
+        0 => std::time::Duration::from_millis(DEFAULT_HANG_TIMEOUT),
+        timeout => std::time::Duration::from_millis(timeout),
+    }
+}
+
+// Calls known to be slow never get less than their built-in timeout
+fn call_timeout(interface: Option<MistInterface>, builtin_timeout_ms: u64) -> std::time::Duration {
+    let configured = interface
+        .map(|interface| INTERFACE_CALL_TIMEOUTS[interface as usize].load(Ordering::Relaxed))
+        .filter(|timeout| *timeout != 0)
+        .unwrap_or_else(|| CALL_TIMEOUT.load(Ordering::Relaxed));
+
+    std::time::Duration::from_millis(if configured == 0 {
+        builtin_timeout_ms
+    } else if builtin_timeout_ms > DEFAULT_TIMEOUT {
+        configured.max(builtin_timeout_ms)
+    } else {
+        configured
+    })
+}
+
+// Resolves to a timeout error if the future has not resolved before the timeout
+#[cfg(feature = "async")]
+async fn with_timeout<T>(
+    future: impl std::future::Future<Output = Result<T, crate::result::Error>>,
+    timeout: std::time::Duration,
+) -> Result<T, crate::result::Error> {
+    let future = std::pin::pin!(future);
+
+    match futures::future::select(future, futures_timer::Delay::new(timeout)).await {
+        futures::future::Either::Left((res, _)) => res,
+        futures::future::Either::Right(_) => Err(crate::result::Error::Mist(
+            crate::result::MistError::Timeout,
+        )),
+    }
+}
 
 // Service calls for the subprocess
 mist_service!(
@@ -51,27 +116,41 @@ mist_service!(
         fn activate_action_set_layer(input_handle: MistInputHandle, action_set_layer_handle: MistInputActionSetHandle);
         fn deactivate_action_set_layer(input_handle: MistInputHandle, action_set_layer_handle: MistInputActionSetHandle);
         fn deactivate_all_action_set_layers(input_handle: MistInputHandle);
+        fn get_action_origin_from_xbox_origin(input_handle: MistInputHandle, origin: MistXboxOrigin) -> MistInputActionOrigin;
         fn get_active_action_set_layers(input_handle: MistInputHandle) -> Vec<MistInputActionSetHandle>;
         fn get_action_set_handle(action_set_name: String) -> MistInputActionSetHandle;
+        // Returns the primary origin of the action with its string and png glyph, None if they are not avaliable
+        fn get_action_prompt(input_handle: MistInputHandle, action_set_handle: MistInputActionSetHandle, action_type: MistInputActionType, action_handle: u64, size: MistSteamInputGlyphSize, flags: MistSteamInputGlyphStyle) -> (MistInputActionOrigin, Option<CString>, Option<CString>);
         // fn get_analog_action_data(input_handle: MistInputHandle, analog_action_handle: MistInputAnalogActionHandle) -> (); - Stored in shared memory.
         fn get_analog_action_handle(name: String) -> MistInputAnalogActionHandle;
         fn get_analog_action_origins(input_handle: MistInputHandle, action_set_handle: MistInputActionSetHandle, analog_action_handle: MistInputAnalogActionHandle) -> Vec<MistInputActionOrigin>;
         fn get_connected_controllers() -> Vec<MistInputHandle>;
         fn get_controller_for_gamepad_index(index: c_int) -> MistInputHandle;
         fn get_current_action_set(input_handle: MistInputHandle) -> MistInputActionSetHandle;
+        // Returns the major and minor revision, None if the controller has no binding
+        fn get_device_binding_revision(input_handle: MistInputHandle) -> Option<(c_int, c_int)>;
         // fn get_digital_action_data(input_handle: MistInputHandle, digital_action_handle: MistInputDigitalActionHandle) -> -> (); - Stored in shared memory.
         fn get_digital_action_handle(name: String) -> MistInputDigitalActionHandle;
         fn get_digital_action_origins(input_handle: MistInputHandle, action_set_handle: MistInputActionSetHandle, digital_action_handle: MistInputDigitalActionHandle) -> Vec<MistInputActionOrigin>;
         fn get_gamepad_index_for_controller(controller_handle: MistInputHandle) -> c_int;
         fn get_glyph_png_for_action_origin(origin: MistInputActionOrigin, size: MistSteamInputGlyphSize,  flags: MistSteamInputGlyphStyle) -> CString;
         fn get_glyph_svg_for_action_origin(origin: MistInputActionOrigin, flags: MistSteamInputGlyphStyle) -> CString;
+        // Resolves many glyphs in one call, None for origins without a glyph
+        #[timeout(10_000)]
+        fn get_glyph_paths(png: Vec<MistGlyphPngKey>, svg: Vec<MistGlyphSvgKey>) -> (Vec<Option<CString>>, Vec<Option<CString>>);
         fn get_input_type_for_handle(input_handle: MistInputHandle) -> MistSteamInputType;
         // fn get_motion_data(input_handle: MistInputHandle) -> (); - Stored in shared memory.
+        fn get_remote_play_session_id(input_handle: MistInputHandle) -> u32;
+        fn get_session_input_configuration_settings() -> u16;
         fn get_string_for_action_origin(origin: MistInputActionOrigin) -> CString;
+        fn get_string_for_xbox_origin(origin: MistXboxOrigin) -> CString;
+        // Played by the subprocess, 0 loops plays the pattern until it is stopped
+        fn play_haptic_pattern(input_handle: MistInputHandle, keyframes: Vec<MistHapticKeyframe>, loops: u32);
         #[timeout(10_000)]
         fn init() -> (String, bool);
+        // Returns the os id of the new shared memory if it was resized to fit the actions of the manifest
         #[timeout(10_000)]
-        fn set_input_action_manifest_file_path(path: CString) -> bool;
+        fn set_input_action_manifest_file_path(path: CString) -> (bool, Option<String>);
         fn set_led_color(input_handle: MistInputHandle, color_r: u8, color_g: u8, color_b: u8, flags: MistSteamControllerLEDFlag);
         // fn show_analog_action_origins... Deprecated so not implemented
         fn show_binding_panel(input_handle: MistInputHandle) -> bool;
@@ -79,6 +158,7 @@ mist_service!(
         #[timeout(10_000)]
         fn shutdown() -> bool;
         fn stop_analog_action_momentum(input_handle: MistInputHandle, action: MistInputAnalogActionHandle);
+        fn stop_haptic_pattern(input_handle: MistInputHandle);
         fn trigger_vibration(input_handle: MistInputHandle, left_speed: c_ushort, right_speed: c_ushort);
         fn trigger_vibration_extended(input_handle: MistInputHandle, left_speed: c_ushort, right_speed: c_ushort, left_trigger_speed: c_ushort, right_trigger_speed: c_ushort);
         fn trigger_simple_haptic_event(input_handle: MistInputHandle, haptic_location: MistControllerHapticLocation, intensity: u8, gain_db: c_char, other_intensity: u8,other_gain_db: c_char);
@@ -123,5 +203,10 @@ mist_service!(
     // Internal
     Internal {
         fn exit();
+        // Version from the manifest of the Steam client install, None if it could not be read
+        fn get_steam_client_version() -> Option<String>;
+        // None changes the subscription of all callbacks
+        fn subscribe_callbacks(callback_ids: Option<Vec<u32>>);
+        fn unsubscribe_callbacks(callback_ids: Option<Vec<u32>>);
     }
 );
@@ -1,13 +1,44 @@
 use anyhow::Result;
-use std::time::Duration;
+use std::{
+    ffi::CStr,
+    path::Path,
+    time::{Duration, Instant},
+};
 
-use crate::{result::Error, service::*};
+use crate::{
+    callbacks::MistCallbackSubscriptions,
+    result::Error,
+    service::*,
+    transport::{subprocess_transport, MistTransportReader, MistTransportWriter},
+    types::MistLogLevel,
+};
 
-pub type Server = MistServer<MistServerService, std::io::Stdin, std::io::Stdout>;
+pub type Server = MistServer<MistServerService, MistTransportReader, MistTransportWriter>;
 
 const DEFAULT_TIMEOUT: u64 = 1000 / 120; // 120 Hz
 
+// Lets the library detect a hung main loop
+const HEARTBEAT_INTERVAL: Duration = Duration::from_millis(500);
+
+type LogRecord = (MistLogLevel, String);
+
+lazy_static::lazy_static! {
+    static ref LOG_CHANNEL: (
+        crossbeam_channel::Sender<LogRecord>,
+        crossbeam_channel::Receiver<LogRecord>
+    ) = crossbeam_channel::unbounded();
+}
+
+/// Queues a log record which is forwarded to the library from the main loop
+#[allow(dead_code)] // Only used by the binary
+pub fn log(level: MistLogLevel, msg: &str) {
+    let _ = LOG_CHANNEL.0.send((level, msg.to_string()));
+}
+
 pub fn run() -> Result<()> {
+    // Exit when the library process dies, even if the transport stays open
+    parent::watch();
+
     // Setup the service context which is avaliable to all the service calls
     let service = MistServerService {
         steam_apps: unsafe { steamworks_sys::SteamAPI_SteamApps_v008() },
@@ -19,23 +50,28 @@ pub fn run() -> Result<()> {
         steam_utils: unsafe { steamworks_sys::SteamAPI_SteamUtils_v010() },
         entered_gamepad_text: None,
         steam_input_data: None,
+        callback_subscriptions: MistCallbackSubscriptions::default(),
         should_exit: false,
     };
 
-    // Create the server using stdin/stdout as transport for IPC
-    let mut server = MistServer::create(service, std::io::stdin(), std::io::stdout());
+    // Create the server using the transport passed by the library
+    let (read, write) = subprocess_transport().split()?;
+    let mut server = MistServer::create(service, read, write);
     // Tell the library that we have initialized
     if let Err(err) = server.write_data(&MistServiceToLibrary::Initialized) {
-        eprintln!(
-            "[mist] Error writing intialized message to library: {}",
+        crate::mist_log_error(&format!(
+            "Error writing intialized message to library: {}",
             err
-        );
+        ));
+        flush_logs(&mut server);
         std::process::exit(1);
     }
 
     let poll_duration = Duration::from_millis(DEFAULT_TIMEOUT); // 120 Hz
+    let mut last_heartbeat = Instant::now();
 
-    while !server.service().should_exit {
+    // Leaving the loop drops the service which shuts down the Steam API
+    'main: while !server.service().should_exit && !parent::parent_exited() {
         let steam_input = server.service().steam_input;
         if let Some(input_data) = &mut server.service().steam_input_data {
             input_data.run_frame(steam_input);
@@ -56,6 +92,20 @@ pub fn run() -> Result<()> {
             }
         }
 
+        if server.is_disconnected() {
+            crate::mist_log(
+                MistLogLevel::Info,
+                "The library closed the transport, shutting down",
+            );
+            break;
+        }
+
+        // Patterns submitted while waiting start right away
+        let steam_input = server.service().steam_input;
+        if let Some(input_data) = &mut server.service().steam_input_data {
+            input_data.haptics.run(steam_input, Instant::now());
+        }
+
         let steam_pipe = server.service().steam_pipe;
         let steam_user = server.service().steam_user;
 
@@ -79,19 +129,58 @@ pub fn run() -> Result<()> {
                 steam_user,
                 &callback,
             ) {
+                if !server
+                    .service()
+                    .callback_subscriptions
+                    .is_subscribed(callback.callback)
+                {
+                    unsafe { steamworks_sys::SteamAPI_ManualDispatch_FreeLastCallback(steam_pipe) }
+                    continue;
+                }
+
                 if let Err(err) = server.write_data(&MistServiceToLibrary::Callback(callback)) {
-                    eprintln!("[mist] Error writing callback message to library: {}", err);
-                    std::process::exit(1);
+                    crate::mist_log_error(&format!(
+                        "Error writing callback message to library: {}",
+                        err
+                    ));
+                    unsafe { steamworks_sys::SteamAPI_ManualDispatch_FreeLastCallback(steam_pipe) }
+                    break 'main;
                 }
             }
 
             unsafe { steamworks_sys::SteamAPI_ManualDispatch_FreeLastCallback(steam_pipe) }
         }
+
+        flush_logs(&mut server);
+
+        if last_heartbeat.elapsed() >= HEARTBEAT_INTERVAL {
+            if let Err(err) = server.write_data(&MistServiceToLibrary::Heartbeat) {
+                crate::mist_log_error(&format!(
+                    "Error writing heartbeat message to library: {}",
+                    err
+                ));
+                break;
+            }
+            last_heartbeat = Instant::now();
+        }
     }
 
+    // Records queued while leaving the loop would be lost otherwise
+    flush_logs(&mut server);
+
     Ok(())
 }
 
+// Forwards the queued log records to the library
+fn flush_logs(server: &mut Server) {
+    while let Ok((level, msg)) = LOG_CHANNEL.1.try_recv() {
+        // The library can not receive the record, stderr is the only place left for it
+        if let Err(err) = server.write_data(&MistServiceToLibrary::Log(level, msg.clone())) {
+            eprintln!("[mist] {} (Error forwarding log to library: {})", msg, err);
+        }
+    }
+}
+
 pub struct MistServerService {
     steam_apps: *mut steamworks_sys::ISteamApps,
     steam_pipe: steamworks_sys::HSteamPipe,
@@ -102,21 +191,72 @@ pub struct MistServerService {
     steam_utils: *mut steamworks_sys::ISteamUtils,
     pub entered_gamepad_text: Option<String>,
     pub steam_input_data: Option<input::SteamInputData>,
+    callback_subscriptions: MistCallbackSubscriptions,
     should_exit: bool,
 }
 
 mod apps;
 mod friends;
+mod haptics;
 mod input;
+#[allow(dead_code)] // Only used by the binary
+mod parent;
 mod remote_storage;
 mod utils;
 
+// Reads the version from the client manifest in the package dir of the install, named after the platform
+fn steam_client_version(install_path: &Path) -> Option<String> {
+    std::fs::read_dir(install_path.join("package"))
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with("steam_client_") && name.ends_with(".manifest")
+        })
+        .find_map(|entry| {
+            let manifest = std::fs::read_to_string(entry.path()).ok()?;
+            manifest.lines().find_map(|line| {
+                let mut tokens = line.split('"').filter(|token| !token.trim().is_empty());
+                match (tokens.next(), tokens.next()) {
+                    (Some("version"), Some(version)) => Some(version.to_string()),
+                    _ => None,
+                }
+            })
+        })
+}
+
 impl MistServiceInternal for MistServerService {
     fn exit(&mut self) -> Result<(), Error> {
         self.should_exit = true;
 
         Ok(())
     }
+
+    fn get_steam_client_version(&mut self) -> Result<Option<String>, Error> {
+        let install_path = unsafe { steamworks_sys::SteamAPI_GetSteamInstallPath() };
+        if install_path.is_null() {
+            return Ok(None);
+        }
+
+        let install_path = unsafe { CStr::from_ptr(install_path) }.to_string_lossy();
+
+        Ok(steam_client_version(Path::new(install_path.as_ref())))
+    }
+
+    fn subscribe_callbacks(&mut self, callback_ids: Option<Vec<u32>>) -> Result<(), Error> {
+        self.callback_subscriptions
+            .set_subscribed(callback_ids, true);
+
+        Ok(())
+    }
+
+    fn unsubscribe_callbacks(&mut self, callback_ids: Option<Vec<u32>>) -> Result<(), Error> {
+        self.callback_subscriptions
+            .set_subscribed(callback_ids, false);
+
+        Ok(())
+    }
 }
 
 impl MistService for MistServerService {}
@@ -0,0 +1,70 @@
+//! Plays haptic patterns submitted by the library from the main loop, so the library does not need a call per change
+
+use std::time::Instant;
+
+use crate::{
+    haptic_pattern::{HapticPatterns, MotorSpeeds},
+    result::Error,
+    types::*,
+};
+
+fn set_speeds(
+    steam_input: *mut steamworks_sys::ISteamInput,
+    input_handle: MistInputHandle,
+    speeds: MotorSpeeds,
+) {
+    unsafe {
+        steamworks_sys::SteamAPI_ISteamInput_TriggerVibrationExtended(
+            steam_input,
+            input_handle,
+            speeds[0],
+            speeds[1],
+            speeds[2],
+            speeds[3],
+        );
+    }
+}
+
+#[derive(Default)]
+pub struct HapticScheduler {
+    patterns: HapticPatterns,
+}
+
+impl HapticScheduler {
+    /// Replaces the pattern playing on the controller
+    pub fn play(
+        &mut self,
+        input_handle: MistInputHandle,
+        keyframes: Vec<MistHapticKeyframe>,
+        loops: u32,
+    ) -> Result<(), Error> {
+        self.patterns
+            .play(input_handle, keyframes, loops, Instant::now())
+    }
+
+    /// Stops the pattern playing on the controller, stopping the motors if stop_motors is set
+    pub fn stop(
+        &mut self,
+        steam_input: *mut steamworks_sys::ISteamInput,
+        input_handle: MistInputHandle,
+        stop_motors: bool,
+    ) {
+        if self.patterns.stop(input_handle) && stop_motors {
+            set_speeds(steam_input, input_handle, MotorSpeeds::default());
+        }
+    }
+
+    /// Stops all patterns and their motors
+    pub fn stop_all(&mut self, steam_input: *mut steamworks_sys::ISteamInput) {
+        for input_handle in self.patterns.stop_all() {
+            set_speeds(steam_input, input_handle, MotorSpeeds::default());
+        }
+    }
+
+    /// Sends the speeds which changed since the last run, called every iteration of the main loop
+    pub fn run(&mut self, steam_input: *mut steamworks_sys::ISteamInput, now: Instant) {
+        for (input_handle, speeds) in self.patterns.advance(now) {
+            set_speeds(steam_input, input_handle, speeds);
+        }
+    }
+}
@@ -1,64 +1,283 @@
 use shared_memory::{Shmem, ShmemConf};
 use std::{
+    collections::HashMap,
     ffi::{CStr, CString},
     os::raw::{c_char, c_int, c_ushort},
-    sync::atomic::Ordering,
+    sync::atomic::{fence, Ordering},
+    time::{Instant, SystemTime, UNIX_EPOCH},
 };
 
-use super::MistServerService;
+use super::{haptics::HapticScheduler, MistServerService};
 use crate::{
     consts::*,
+    manifest::manifest_layout,
     result::{Error, SteamInputError},
     service::MistServiceSteamInput,
     types::*,
 };
 
+type DigitalActionChange = (MistInputHandle, MistInputDigitalActionHandle, bool);
+
+// Filled by the action event callback, which Steam Input calls from RunFrame
+static ACTION_EVENT_CALLBACK_CHANGES: parking_lot::Mutex<Vec<DigitalActionChange>> =
+    parking_lot::const_mutex(Vec::new());
+
+unsafe extern "C" fn action_event_callback(event: *mut steamworks_sys::SteamInputActionEvent_t) {
+    let event = &*event;
+
+    if event.eEventType
+        == steamworks_sys::ESteamInputActionEventType_ESteamInputActionEventType_DigitalAction
+    {
+        let digital_action = event.__bindgen_anon_1.digitalAction;
+        let digital_action_data = digital_action.digitalActionData;
+
+        ACTION_EVENT_CALLBACK_CHANGES.lock().push((
+            event.controllerHandle,
+            digital_action.actionHandle,
+            digital_action_data.bState,
+        ));
+    }
+}
+
+lazy_static::lazy_static! {
+    // Start of the timestamp clock and the unix time it started at, shared by resized shared memory
+    static ref CLOCK_START: (Instant, u64) = (
+        Instant::now(),
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_micros() as u64)
+            .unwrap_or(0),
+    );
+}
+
+fn timestamp_us() -> u64 {
+    CLOCK_START.0.elapsed().as_micros() as u64
+}
+
+// Turns digital action states into press and release events
+#[derive(Default)]
+struct ActionEventRecorder {
+    states: HashMap<(MistInputHandle, MistInputDigitalActionHandle), bool>,
+    overflowed: bool,
+}
+
+impl ActionEventRecorder {
+    fn record(
+        &mut self,
+        events: &mut MistInputEventRing<MistInputActionEvent>,
+        input_handle: MistInputHandle,
+        digital_action_handle: MistInputDigitalActionHandle,
+        pressed: bool,
+    ) {
+        let previous = self
+            .states
+            .insert((input_handle, digital_action_handle), pressed)
+            .unwrap_or(false);
+
+        if previous == pressed {
+            return;
+        }
+
+        let queued = events.push(MistInputActionEvent {
+            input_handle,
+            digital_action_handle,
+            pressed,
+            timestamp_us: timestamp_us(),
+        });
+
+        // Only log once per overflow
+        if !queued && !self.overflowed {
+            crate::mist_log(
+                MistLogLevel::Warning,
+                "Steam Input action event queue is full, events are dropped until it is drained",
+            );
+        }
+        self.overflowed = !queued;
+    }
+}
+
+fn push_slot_event(
+    events: &mut MistInputEventRing<MistInputSlotEvent>,
+    slot: usize,
+    input_handle: MistInputHandle,
+    previous_input_handle: MistInputHandle,
+) {
+    let queued = events.push(MistInputSlotEvent {
+        slot: slot as u32,
+        input_handle,
+        previous_input_handle,
+        timestamp_us: timestamp_us(),
+    });
+
+    if !queued {
+        crate::mist_log(
+            MistLogLevel::Warning,
+            "Steam Input slot event queue is full, the event is dropped",
+        );
+    }
+}
+
 pub struct SteamInputData {
     shmem: Shmem,
+    layout: MistInputStateLayout,
+    // The slot of an action is its index, same as in the handle tables of the shared memory
     analog_actions: Vec<MistInputAnalogActionHandle>,
     digital_actions: Vec<MistInputDigitalActionHandle>,
+    action_events: ActionEventRecorder,
+    // Copied into every buffer so slot changes are only reported once
+    gamepad_mapping: [MistInputHandle; MIST_STEAM_INPUT_MAX_COUNT],
+    // The buffer written next, never the one the library reads from
+    back_buffer: u8,
+    frame: u64,
+    // Run by the main loop, patterns advance every iteration independent of the snapshots
+    pub haptics: HapticScheduler,
 }
 
 impl SteamInputData {
-    pub fn new() -> Result<Self, Error> {
-        let shmem = match ShmemConf::new()
-            .size(std::mem::size_of::<MistInputStateBuffered>())
-            .create()
-        {
+    pub fn new(layout: MistInputStateLayout) -> Result<Self, Error> {
+        let shmem = match ShmemConf::new().size(layout.size()).create() {
             Ok(shmem) => shmem,
             Err(err) => {
-                eprintln!("[mist] Error setting up shmem: {}", err);
+                crate::mist_log_error(&format!("Error setting up shmem: {}", err));
                 return Err(Error::SteamInput(SteamInputError::ShmemError));
             }
         };
 
         let state_ptr = shmem.as_ptr() as *mut MistInputStateBuffered;
-        unsafe { *state_ptr = MistInputStateBuffered::default() };
+        unsafe {
+            state_ptr.write(MistInputStateBuffered::new(layout));
+            (*state_ptr).clock_start_us = CLOCK_START.1;
+
+            // The action data is zeroed, which is the default of all its fields
+            let header_size = std::mem::size_of::<MistInputStateBuffered>();
+            std::ptr::write_bytes(
+                shmem.as_ptr().add(header_size),
+                0,
+                layout.size() - header_size,
+            );
+        }
 
         Ok(SteamInputData {
             shmem,
+            layout,
             analog_actions: Vec::new(),
             digital_actions: Vec::new(),
+            action_events: ActionEventRecorder::default(),
+            gamepad_mapping: [0; MIST_STEAM_INPUT_MAX_COUNT],
+            back_buffer: MIST_INPUT_STATE_BUFFER_SIZE - 1,
+            frame: 0,
+            haptics: HapticScheduler::default(),
         })
     }
 
+    // Moves the registered actions and the gamepad mapping into new shared memory
+    // The rings of the old memory are left untouched, the library drains them when it switches to the new memory
+    fn resize(&mut self, layout: MistInputStateLayout) -> Result<(), Error> {
+        let mut input_data = SteamInputData::new(layout)?;
+
+        for handle in &self.analog_actions {
+            input_data.register_analog_action(*handle)?;
+        }
+        for handle in &self.digital_actions {
+            input_data.register_digital_action(*handle)?;
+        }
+
+        input_data.action_events = std::mem::take(&mut self.action_events);
+        input_data.gamepad_mapping = self.gamepad_mapping;
+        input_data.frame = self.frame;
+        input_data.haptics = std::mem::take(&mut self.haptics);
+
+        *self = input_data;
+
+        Ok(())
+    }
+
+    /// Lets the library know cached origins might have changed
+    pub fn configuration_loaded(&self) {
+        unsafe { &*self.memory().header() }
+            .configuration_generation
+            .fetch_add(1, Ordering::Release);
+    }
+
+    fn memory(&self) -> MistInputStateMemory {
+        unsafe { MistInputStateMemory::from_ptr(self.shmem.as_ptr() as *mut _) }
+    }
+
+    fn register_analog_action(&mut self, handle: MistInputAnalogActionHandle) -> Result<(), Error> {
+        // 0 is returned for unknown action names
+        if handle == 0 || self.analog_actions.contains(&handle) {
+            return Ok(());
+        }
+
+        if self.memory().register_analog_action(handle).is_none() {
+            crate::mist_log_error(&format!(
+                "Can not register more than {} analog actions",
+                self.layout.analog_action_capacity
+            ));
+            return Err(Error::SteamInput(SteamInputError::TooManyActions));
+        }
+
+        self.analog_actions.push(handle);
+
+        Ok(())
+    }
+
+    fn register_digital_action(
+        &mut self,
+        handle: MistInputDigitalActionHandle,
+    ) -> Result<(), Error> {
+        // 0 is returned for unknown action names
+        if handle == 0 || self.digital_actions.contains(&handle) {
+            return Ok(());
+        }
+
+        if self.memory().register_digital_action(handle).is_none() {
+            crate::mist_log_error(&format!(
+                "Can not register more than {} digital actions",
+                self.layout.digital_action_capacity
+            ));
+            return Err(Error::SteamInput(SteamInputError::TooManyActions));
+        }
+
+        self.digital_actions.push(handle);
+
+        Ok(())
+    }
+
     pub fn os_id(&self) -> String {
         self.shmem.get_os_id().to_owned()
     }
 
     pub fn run_frame(&mut self, steam_input: *mut steamworks_sys::ISteamInput) {
-        let state = unsafe { &mut *(self.shmem.as_ptr() as *mut MistInputStateBuffered) };
+        let memory = self.memory();
+        let state = unsafe { &mut *memory.header() };
 
-        let next_subprocess_cursor =
-            (state.subprocess_cursor.load(Ordering::Relaxed) + 1) % MIST_INPUT_STATE_BUFFER_SIZE;
+        unsafe { steamworks_sys::SteamAPI_ISteamInput_RunFrame(steam_input, true) };
 
-        if next_subprocess_cursor == state.library_cursor.load(Ordering::Relaxed) {
-            return;
+        for (input_handle, digital_action_handle, pressed) in
+            ACTION_EVENT_CALLBACK_CHANGES.lock().drain(..)
+        {
+            self.action_events.record(
+                &mut state.action_events,
+                input_handle,
+                digital_action_handle,
+                pressed,
+            );
         }
 
-        unsafe { steamworks_sys::SteamAPI_ISteamInput_RunFrame(steam_input, true) };
+        let back_buffer = self.back_buffer as usize;
+        let input_state = &mut state.buffer[back_buffer];
 
-        let input_state = &mut state.buffer[next_subprocess_cursor as usize];
+        // Seqlock, the buffer only changes while the sequence is odd
+        let sequence = input_state.sequence.load(Ordering::Relaxed);
+        input_state
+            .sequence
+            .store(sequence.wrapping_add(1), Ordering::Relaxed);
+        fence(Ordering::Release);
+
+        self.frame += 1;
+        input_state.frame = self.frame;
+        input_state.timestamp_us = timestamp_us();
 
         input_state.input_handle_count = unsafe {
             steamworks_sys::SteamAPI_ISteamInput_GetConnectedControllers(
@@ -69,22 +288,30 @@ impl SteamInputData {
 
         let input_handles = &input_state.input_handles[..input_state.input_handle_count as usize];
 
+        self.action_events
+            .states
+            .retain(|(input_handle, _), _| input_handles.contains(input_handle));
+
         // Remove gamepads no longer connected
-        for handle in input_state.gamepad_mapping.iter_mut() {
+        for (slot, handle) in self.gamepad_mapping.iter_mut().enumerate() {
             if *handle != 0 && !input_handles.contains(handle) {
+                push_slot_event(&mut state.slot_events, slot, 0, *handle);
                 *handle = 0;
             }
         }
 
         // Add gamepads not mapped
         for handle in input_handles {
-            if !input_state.gamepad_mapping.contains(handle) {
-                if let Some(free_pos) = input_state.gamepad_mapping.iter().position(|h| *h == 0) {
-                    input_state.gamepad_mapping[free_pos] = *handle;
+            if !self.gamepad_mapping.contains(handle) {
+                if let Some(free_pos) = self.gamepad_mapping.iter().position(|h| *h == 0) {
+                    self.gamepad_mapping[free_pos] = *handle;
+                    push_slot_event(&mut state.slot_events, free_pos, *handle, 0);
                 }
             }
         }
 
+        input_state.gamepad_mapping = self.gamepad_mapping;
+
         for i in 0..MIST_STEAM_INPUT_MAX_COUNT {
             let input_handle = input_state.gamepad_mapping[i];
 
@@ -93,6 +320,8 @@ impl SteamInputData {
             }
 
             let pad = &mut input_state.gamepads[i];
+            let analog_action_data = unsafe { &mut *memory.analog_action_data(back_buffer, i) };
+            let digital_action_data = unsafe { &mut *memory.digital_action_data(back_buffer, i) };
 
             let input_type = unsafe {
                 std::mem::transmute::<_, MistSteamInputType>(
@@ -105,12 +334,17 @@ impl SteamInputData {
 
             pad.input_handle = input_handle;
             pad.input_type = input_type;
+            pad.remote_play_session_id = unsafe {
+                steamworks_sys::SteamAPI_ISteamInput_GetRemotePlaySessionID(
+                    steam_input,
+                    input_handle,
+                )
+            };
 
             // Only update actions if the controller is valid
             if pad.input_type != MistSteamInputType::Unknown {
-                for analog_handle in &self.analog_actions {
-                    let idx = *analog_handle as usize;
-                    let analog_action_data = unsafe {
+                for (slot, analog_handle) in self.analog_actions.iter().enumerate() {
+                    let data = unsafe {
                         steamworks_sys::SteamAPI_ISteamInput_GetAnalogActionData(
                             steam_input,
                             input_handle,
@@ -118,21 +352,18 @@ impl SteamInputData {
                         )
                     };
 
-                    pad.analog_action_data[idx] = MistInputAnalogActionData {
+                    analog_action_data[slot] = MistInputAnalogActionData {
                         mode: unsafe {
-                            std::mem::transmute::<_, MistControllerSourceMode>(
-                                analog_action_data.eMode,
-                            )
+                            std::mem::transmute::<_, MistControllerSourceMode>(data.eMode)
                         },
-                        x: analog_action_data.x,
-                        y: analog_action_data.y,
-                        active: analog_action_data.bActive,
+                        x: data.x,
+                        y: data.y,
+                        active: data.bActive,
                     };
                 }
 
-                for digital_handle in &self.digital_actions {
-                    let idx = *digital_handle as usize;
-                    let digital_action_data = unsafe {
+                for (slot, digital_handle) in self.digital_actions.iter().enumerate() {
+                    let data = unsafe {
                         steamworks_sys::SteamAPI_ISteamInput_GetDigitalActionData(
                             steam_input,
                             input_handle,
@@ -140,10 +371,18 @@ impl SteamInputData {
                         )
                     };
 
-                    pad.digital_action_data[idx] = MistInputDigitalActionData {
-                        state: digital_action_data.bState,
-                        active: digital_action_data.bActive,
+                    digital_action_data[slot] = MistInputDigitalActionData {
+                        state: data.bState,
+                        active: data.bActive,
                     };
+
+                    // Catches changes not reported by the action event callback
+                    self.action_events.record(
+                        &mut state.action_events,
+                        input_handle,
+                        *digital_handle,
+                        data.bState,
+                    );
                 }
 
                 let motion_data = unsafe {
@@ -155,9 +394,15 @@ impl SteamInputData {
             }
         }
 
-        state
-            .subprocess_cursor
-            .store(next_subprocess_cursor, Ordering::Relaxed);
+        input_state
+            .sequence
+            .store(sequence.wrapping_add(2), Ordering::Release);
+
+        // Publish the snapshot and continue with the buffer published before, unless the library took it
+        self.back_buffer = state.latest.swap(
+            self.back_buffer | MIST_INPUT_STATE_LATEST_FRESH,
+            Ordering::AcqRel,
+        ) & !MIST_INPUT_STATE_LATEST_FRESH;
     }
 }
 
@@ -218,6 +463,21 @@ impl MistServiceSteamInput for MistServerService {
         }
         Ok(())
     }
+    fn get_action_origin_from_xbox_origin(
+        &mut self,
+        input_handle: MistInputHandle,
+        origin: MistXboxOrigin,
+    ) -> Result<MistInputActionOrigin, Error> {
+        Ok(unsafe {
+            std::mem::transmute::<u32, MistInputActionOrigin>(
+                steamworks_sys::SteamAPI_ISteamInput_GetActionOriginFromXboxOrigin(
+                    self.steam_input,
+                    input_handle,
+                    origin as _,
+                ) as u32,
+            )
+        })
+    }
     fn get_active_action_set_layers(
         &mut self,
         input_handle: MistInputHandle,
@@ -248,6 +508,50 @@ impl MistServiceSteamInput for MistServerService {
             )
         })
     }
+    fn get_action_prompt(
+        &mut self,
+        input_handle: MistInputHandle,
+        action_set_handle: MistInputActionSetHandle,
+        action_type: MistInputActionType,
+        action_handle: u64,
+        size: MistSteamInputGlyphSize,
+        flags: MistSteamInputGlyphStyle,
+    ) -> Result<(MistInputActionOrigin, Option<CString>, Option<CString>), Error> {
+        let origins = match action_type {
+            MistInputActionType::Analog => {
+                self.get_analog_action_origins(input_handle, action_set_handle, action_handle)?
+            }
+            MistInputActionType::Digital => {
+                self.get_digital_action_origins(input_handle, action_set_handle, action_handle)?
+            }
+        };
+
+        let origin = match origins.first() {
+            Some(origin) => *origin,
+            None => return Ok((MistInputActionOrigin::None, None, None)),
+        };
+
+        let origin_string_ptr = unsafe {
+            steamworks_sys::SteamAPI_ISteamInput_GetStringForActionOrigin(
+                self.steam_input,
+                origin as _,
+            )
+        };
+        let glyph_ptr = unsafe {
+            steamworks_sys::SteamAPI_ISteamInput_GetGlyphPNGForActionOrigin(
+                self.steam_input,
+                origin as _,
+                size as _,
+                flags as _,
+            )
+        };
+
+        let to_owned = |ptr: *const c_char| {
+            (!ptr.is_null()).then(|| unsafe { CStr::from_ptr(ptr) }.to_owned())
+        };
+
+        Ok((origin, to_owned(origin_string_ptr), to_owned(glyph_ptr)))
+    }
     fn get_analog_action_handle(
         &mut self,
         name: String,
@@ -262,9 +566,7 @@ impl MistServiceSteamInput for MistServerService {
         };
 
         if let Some(input_data) = &mut self.steam_input_data {
-            if !input_data.analog_actions.contains(&handle) {
-                input_data.analog_actions.push(handle);
-            }
+            input_data.register_analog_action(handle)?;
         }
 
         Ok(handle)
@@ -325,6 +627,24 @@ impl MistServiceSteamInput for MistServerService {
             steamworks_sys::SteamAPI_ISteamInput_GetCurrentActionSet(self.steam_input, input_handle)
         })
     }
+    fn get_device_binding_revision(
+        &mut self,
+        input_handle: MistInputHandle,
+    ) -> Result<Option<(c_int, c_int)>, Error> {
+        let mut major = 0;
+        let mut minor = 0;
+
+        let has_binding = unsafe {
+            steamworks_sys::SteamAPI_ISteamInput_GetDeviceBindingRevision(
+                self.steam_input,
+                input_handle,
+                &mut major,
+                &mut minor,
+            )
+        };
+
+        Ok(has_binding.then_some((major, minor)))
+    }
     fn get_digital_action_handle(
         &mut self,
         name: String,
@@ -339,9 +659,7 @@ impl MistServiceSteamInput for MistServerService {
         };
 
         if let Some(input_data) = &mut self.steam_input_data {
-            if !input_data.digital_actions.contains(&handle) {
-                input_data.digital_actions.push(handle);
-            }
+            input_data.register_digital_action(handle)?;
         }
 
         Ok(handle)
@@ -422,6 +740,44 @@ impl MistServiceSteamInput for MistServerService {
         let glyph_path = unsafe { CStr::from_ptr(glyph_ptr) }.to_owned();
         Ok(glyph_path)
     }
+    fn get_glyph_paths(
+        &mut self,
+        png: Vec<MistGlyphPngKey>,
+        svg: Vec<MistGlyphSvgKey>,
+    ) -> Result<(Vec<Option<CString>>, Vec<Option<CString>>), Error> {
+        let glyph_path = |glyph_ptr: *const c_char| {
+            (!glyph_ptr.is_null()).then(|| unsafe { CStr::from_ptr(glyph_ptr) }.to_owned())
+        };
+
+        let png_paths = png
+            .into_iter()
+            .map(|(origin, size, flags)| {
+                glyph_path(unsafe {
+                    steamworks_sys::SteamAPI_ISteamInput_GetGlyphPNGForActionOrigin(
+                        self.steam_input,
+                        origin as _,
+                        size as _,
+                        flags as _,
+                    )
+                })
+            })
+            .collect();
+
+        let svg_paths = svg
+            .into_iter()
+            .map(|(origin, flags)| {
+                glyph_path(unsafe {
+                    steamworks_sys::SteamAPI_ISteamInput_GetGlyphSVGForActionOrigin(
+                        self.steam_input,
+                        origin as _,
+                        flags as _,
+                    )
+                })
+            })
+            .collect();
+
+        Ok((png_paths, svg_paths))
+    }
     fn get_input_type_for_handle(
         &mut self,
         input_handle: MistInputHandle,
@@ -435,6 +791,21 @@ impl MistServiceSteamInput for MistServerService {
             )
         })
     }
+    fn get_remote_play_session_id(&mut self, input_handle: MistInputHandle) -> Result<u32, Error> {
+        Ok(unsafe {
+            steamworks_sys::SteamAPI_ISteamInput_GetRemotePlaySessionID(
+                self.steam_input,
+                input_handle,
+            )
+        })
+    }
+    fn get_session_input_configuration_settings(&mut self) -> Result<u16, Error> {
+        Ok(unsafe {
+            steamworks_sys::SteamAPI_ISteamInput_GetSessionInputConfigurationSettings(
+                self.steam_input,
+            )
+        })
+    }
     fn get_string_for_action_origin(
         &mut self,
         origin: MistInputActionOrigin,
@@ -452,25 +823,80 @@ impl MistServiceSteamInput for MistServerService {
 
         Ok(action_origin_string)
     }
+    fn get_string_for_xbox_origin(&mut self, origin: MistXboxOrigin) -> Result<CString, Error> {
+        let xbox_origin_string_ptr = unsafe {
+            steamworks_sys::SteamAPI_ISteamInput_GetStringForXboxOrigin(
+                self.steam_input,
+                origin as _,
+            )
+        };
+
+        assert!(!xbox_origin_string_ptr.is_null());
+
+        Ok(unsafe { CStr::from_ptr(xbox_origin_string_ptr) }.to_owned())
+    }
+    fn play_haptic_pattern(
+        &mut self,
+        input_handle: MistInputHandle,
+        keyframes: Vec<MistHapticKeyframe>,
+        loops: u32,
+    ) -> Result<(), Error> {
+        match &mut self.steam_input_data {
+            Some(input_data) => input_data.haptics.play(input_handle, keyframes, loops),
+            None => Err(Error::SteamInput(SteamInputError::NotInitialized)),
+        }
+    }
     fn init(&mut self) -> Result<(String, bool), Error> {
-        let input_data = SteamInputData::new()?;
+        let input_data = SteamInputData::new(MistInputStateLayout::default())?;
         let os_id = input_data.os_id();
 
         let succ = unsafe { steamworks_sys::SteamAPI_ISteamInput_Init(self.steam_input, true) };
 
         if succ {
             self.steam_input_data = Some(input_data);
+
+            unsafe {
+                steamworks_sys::SteamAPI_ISteamInput_EnableDeviceCallbacks(self.steam_input);
+                steamworks_sys::SteamAPI_ISteamInput_EnableActionEventCallbacks(
+                    self.steam_input,
+                    Some(action_event_callback),
+                )
+            };
         }
 
         Ok((os_id, succ))
     }
-    fn set_input_action_manifest_file_path(&mut self, path: CString) -> Result<bool, Error> {
-        Ok(unsafe {
+    fn set_input_action_manifest_file_path(
+        &mut self,
+        path: CString,
+    ) -> Result<(bool, Option<String>), Error> {
+        let set = unsafe {
             steamworks_sys::SteamAPI_ISteamInput_SetInputActionManifestFilePath(
                 self.steam_input,
                 path.as_ptr(),
             )
-        })
+        };
+
+        // Grow the shared memory if the manifest has more actions than there are slots for
+        if let Some(input_data) = &mut self.steam_input_data {
+            if let Some(manifest_layout) = manifest_layout(&path) {
+                let layout = MistInputStateLayout {
+                    analog_action_capacity: manifest_layout
+                        .analog_action_capacity
+                        .max(input_data.layout.analog_action_capacity),
+                    digital_action_capacity: manifest_layout
+                        .digital_action_capacity
+                        .max(input_data.layout.digital_action_capacity),
+                };
+
+                if layout != input_data.layout {
+                    input_data.resize(layout)?;
+                    return Ok((set, Some(input_data.os_id())));
+                }
+            }
+        }
+
+        Ok((set, None))
     }
     fn set_led_color(
         &mut self,
@@ -501,6 +927,14 @@ impl MistServiceSteamInput for MistServerService {
     }
     // fn show_digital_action_origins... Deprecated so not implemented
     fn shutdown(&mut self) -> Result<bool, Error> {
+        if let Some(input_data) = &mut self.steam_input_data {
+            input_data.haptics.stop_all(self.steam_input);
+        }
+
+        unsafe {
+            steamworks_sys::SteamAPI_ISteamInput_EnableActionEventCallbacks(self.steam_input, None)
+        };
+
         Ok(unsafe { steamworks_sys::SteamAPI_ISteamInput_Shutdown(self.steam_input) })
     }
     fn stop_analog_action_momentum(
@@ -517,12 +951,28 @@ impl MistServiceSteamInput for MistServerService {
         }
         Ok(())
     }
+    fn stop_haptic_pattern(&mut self, input_handle: MistInputHandle) -> Result<(), Error> {
+        if let Some(input_data) = &mut self.steam_input_data {
+            input_data
+                .haptics
+                .stop(self.steam_input, input_handle, true);
+        }
+
+        Ok(())
+    }
     fn trigger_vibration(
         &mut self,
         input_handle: MistInputHandle,
         left_speed: c_ushort,
         right_speed: c_ushort,
     ) -> Result<(), Error> {
+        // The pattern would override the speeds on the next frame
+        if let Some(input_data) = &mut self.steam_input_data {
+            input_data
+                .haptics
+                .stop(self.steam_input, input_handle, false);
+        }
+
         unsafe {
             steamworks_sys::SteamAPI_ISteamInput_TriggerVibration(
                 self.steam_input,
@@ -541,6 +991,12 @@ impl MistServiceSteamInput for MistServerService {
         left_trigger_speed: c_ushort,
         right_trigger_speed: c_ushort,
     ) -> Result<(), Error> {
+        if let Some(input_data) = &mut self.steam_input_data {
+            input_data
+                .haptics
+                .stop(self.steam_input, input_handle, false);
+        }
+
         unsafe {
             steamworks_sys::SteamAPI_ISteamInput_TriggerVibrationExtended(
                 self.steam_input,
@@ -0,0 +1,140 @@
+//! Watches the library process so the subprocess never outlives it and keeps the Steam session alive
+//! The library passes its pid in MIST_PARENT_PID
+//! Linux waits on a pidfd of the parent, other unix platforms poll the parent pid
+//! Windows waits on the process handle of the parent
+
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+// Time the main loop gets to shutdown the Steam API before the process is exited
+const EXIT_GRACE_PERIOD: Duration = Duration::from_secs(2);
+#[cfg(unix)]
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+static PARENT_EXITED: AtomicBool = AtomicBool::new(false);
+
+/// Returns true once the library process has exited
+pub fn parent_exited() -> bool {
+    PARENT_EXITED.load(Ordering::Acquire)
+}
+
+/// Starts a thread watching the library process
+/// The main loop is expected to exit once parent_exited returns true, the process is exited if it does not
+pub fn watch() {
+    std::thread::spawn(|| {
+        if !wait_for_parent_exit() {
+            return;
+        }
+
+        crate::mist_log(
+            crate::types::MistLogLevel::Info,
+            "The parent process exited, shutting down",
+        );
+        PARENT_EXITED.store(true, Ordering::Release);
+
+        // The main loop might be stuck in a Steam call
+        std::thread::sleep(EXIT_GRACE_PERIOD);
+        std::process::exit(1);
+    });
+}
+
+// Returns the pid of the library process, None if it was not passed
+fn library_pid() -> Option<u32> {
+    std::env::var(crate::consts::MIST_PARENT_PID_ENV)
+        .ok()
+        .and_then(|pid| pid.parse::<u32>().ok())
+}
+
+// Blocks until the parent exits, returns false if the parent can not be watched
+#[cfg(unix)]
+fn wait_for_parent_exit() -> bool {
+    let parent_pid = std::os::unix::process::parent_id();
+
+    // The library exited before the watch started and the subprocess was reparented
+    if library_pid().is_some_and(|library_pid| library_pid != parent_pid) {
+        return true;
+    }
+
+    #[cfg(target_os = "linux")]
+    if let Some(exited) = wait_for_pidfd(parent_pid) {
+        return exited;
+    }
+
+    // Orphaned processes are reparented, so the parent pid changes once the parent exits
+    while std::os::unix::process::parent_id() == parent_pid {
+        std::thread::sleep(POLL_INTERVAL);
+    }
+
+    true
+}
+
+// Returns None if pidfds are not supported, which needs Linux 5.3
+#[cfg(target_os = "linux")]
+fn wait_for_pidfd(parent_pid: u32) -> Option<bool> {
+    let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, parent_pid as libc::pid_t, 0) };
+    if fd < 0 {
+        return None;
+    }
+    let fd = fd as libc::c_int;
+
+    // The parent might have exited and its pid been reused before the pidfd was opened
+    let exited = if std::os::unix::process::parent_id() != parent_pid {
+        true
+    } else {
+        let mut pollfd = libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+
+        // The pidfd becomes readable once the process exits
+        loop {
+            if unsafe { libc::poll(&mut pollfd, 1, -1) } > 0 {
+                break true;
+            }
+
+            let err = std::io::Error::last_os_error();
+            if err.kind() != std::io::ErrorKind::Interrupted {
+                crate::mist_log_error(&format!("Error waiting for the parent process: {}", err));
+                break false;
+            }
+        }
+    };
+
+    unsafe { libc::close(fd) };
+
+    Some(exited)
+}
+
+#[cfg(windows)]
+fn wait_for_parent_exit() -> bool {
+    use windows_sys::Win32::{
+        Foundation::CloseHandle,
+        System::Threading::{OpenProcess, WaitForSingleObject, PROCESS_SYNCHRONIZE, WAIT_OBJECT_0},
+    };
+
+    let parent_pid = match library_pid() {
+        Some(pid) => pid,
+        None => return false,
+    };
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_SYNCHRONIZE, 0, parent_pid);
+        if handle == 0 {
+            return false;
+        }
+
+        // Waits without a timeout (INFINITE)
+        let res = WaitForSingleObject(handle, u32::MAX);
+        CloseHandle(handle);
+
+        res == WAIT_OBJECT_0
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn wait_for_parent_exit() -> bool {
+    false
+}
@@ -0,0 +1,62 @@
+//! Transports carrying the framed IPC messages between the library and the subprocess
+//! On unix a socketpair is passed to the subprocess as fd MIST_IPC_FD, leaving stdout/stderr free for logs
+//! The piped stdin/stdout of the subprocess is used as a fallback, and on other platforms
+
+use std::io::{Read, Write};
+
+/// Env var telling the subprocess which fd to use as the transport
+pub const MIST_IPC_FD_ENV: &str = "MIST_IPC_FD";
+/// The fd the socketpair is passed to the subprocess as
+/// cbindgen:ignore
+#[cfg(unix)]
+pub const MIST_IPC_FD: i32 = 3;
+
+pub type MistTransportReader = Box<dyn Read + Send>;
+pub type MistTransportWriter = Box<dyn Write + Send>;
+
+/// A bidirectional byte stream between the library and the subprocess
+pub trait MistTransport: Send {
+    /// Splits the transport into the read half used by the reader thread and the write half
+    fn split(self: Box<Self>) -> std::io::Result<(MistTransportReader, MistTransportWriter)>;
+}
+
+/// A pair of pipes, the stdio of the subprocess
+pub struct MistPipeTransport<R: Read + Send + 'static, W: Write + Send + 'static> {
+    pub read: R,
+    pub write: W,
+}
+
+impl<R: Read + Send + 'static, W: Write + Send + 'static> MistTransport
+    for MistPipeTransport<R, W>
+{
+    fn split(self: Box<Self>) -> std::io::Result<(MistTransportReader, MistTransportWriter)> {
+        Ok((Box::new(self.read), Box::new(self.write)))
+    }
+}
+
+#[cfg(unix)]
+impl MistTransport for std::os::unix::net::UnixStream {
+    fn split(self: Box<Self>) -> std::io::Result<(MistTransportReader, MistTransportWriter)> {
+        Ok((Box::new(self.try_clone()?), self))
+    }
+}
+
+/// Returns the transport the library passed to the subprocess
+/// Used by the subprocess and by mist-replay
+pub fn subprocess_transport() -> Box<dyn MistTransport> {
+    #[cfg(unix)]
+    if let Some(fd) = std::env::var(MIST_IPC_FD_ENV)
+        .ok()
+        .and_then(|fd| fd.parse::<i32>().ok())
+    {
+        use std::os::unix::io::FromRawFd;
+
+        // The library passes the socket end of the socketpair as fd, owned by nothing else in this process
+        return Box::new(unsafe { std::os::unix::net::UnixStream::from_raw_fd(fd) });
+    }
+
+    Box::new(MistPipeTransport {
+        read: std::io::stdin(),
+        write: std::io::stdout(),
+    })
+}
@@ -1,5 +1,8 @@
 use serde_derive::{Deserialize, Serialize};
-use std::{os::raw::c_float, sync::atomic::AtomicU8};
+use std::{
+    os::raw::{c_char, c_float, c_int, c_ushort},
+    sync::atomic::{AtomicU32, AtomicU8, Ordering},
+};
 
 use crate::consts::*;
 
@@ -15,7 +18,48 @@ pub type MistInputActionSetHandle = u64;
 pub type MistInputAnalogActionHandle = u64;
 pub type MistInputDigitalActionHandle = u64;
 
-#[derive(Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(C)]
+pub enum MistLogLevel {
+    Error = 0,
+    Warning = 1,
+    Info = 2,
+    Debug = 3,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(C)]
+pub enum MistInterface {
+    SteamApps = 0,
+    SteamFriends = 1,
+    SteamInput = 2,
+    SteamRemoteStorage = 3,
+    SteamUtils = 4,
+}
+
+impl MistInterface {
+    /// cbindgen:ignore
+    #[allow(dead_code)] // Only used by the library
+    pub const COUNT: usize = MistInterface::SteamUtils as usize + 1;
+}
+
+// Interfaces are passed as int, as an out of range enum value is undefined behaviour
+impl TryFrom<c_int> for MistInterface {
+    type Error = ();
+
+    fn try_from(interface: c_int) -> Result<Self, Self::Error> {
+        match interface {
+            0 => Ok(MistInterface::SteamApps),
+            1 => Ok(MistInterface::SteamFriends),
+            2 => Ok(MistInterface::SteamInput),
+            3 => Ok(MistInterface::SteamRemoteStorage),
+            4 => Ok(MistInterface::SteamUtils),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub struct DlcData {
     pub app_id: AppId,
     pub avaliable: bool,
@@ -24,7 +68,7 @@ pub struct DlcData {
 
 // Steam Input
 #[allow(non_camel_case_types)]
-#[derive(Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
 #[repr(u32)]
 pub enum MistInputActionOrigin {
     None = 0,
@@ -267,7 +311,7 @@ pub enum MistInputActionOrigin {
     MaximumPossibleValue = 32767,
 }
 
-#[derive(Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
 #[repr(C)]
 pub enum MistControllerSource {
     None = 0,
@@ -287,7 +331,7 @@ pub enum MistControllerSource {
     Count = 14,
 }
 
-#[derive(Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
 #[repr(C)]
 pub enum MistControllerSourceMode {
     None = 0,
@@ -315,14 +359,60 @@ impl Default for MistControllerSourceMode {
     }
 }
 
-#[derive(Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+/// Flags of mist_steam_input_get_session_input_configuration_settings
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[repr(C)]
+pub enum MistSteamInputConfigurationEnableType {
+    None = 0x0,
+    Playstation = 0x1,
+    Xbox = 0x2,
+    Generic = 0x4,
+    Switch = 0x8,
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
 #[repr(C)]
 pub enum MistSteamControllerLEDFlag {
     SetColor = 0,
     RestoreUserDefault = 1,
 }
 
-#[derive(Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[allow(non_camel_case_types)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[repr(C)]
+pub enum MistXboxOrigin {
+    A = 0,
+    B,
+    X,
+    Y,
+    LeftBumper,
+    RightBumper,
+    Menu,
+    View,
+    LeftTrigger_Pull,
+    LeftTrigger_Click,
+    RightTrigger_Pull,
+    RightTrigger_Click,
+    LeftStick_Move,
+    LeftStick_Click,
+    LeftStick_DPadNorth,
+    LeftStick_DPadSouth,
+    LeftStick_DPadWest,
+    LeftStick_DPadEast,
+    RightStick_Move,
+    RightStick_Click,
+    RightStick_DPadNorth,
+    RightStick_DPadSouth,
+    RightStick_DPadWest,
+    RightStick_DPadEast,
+    DPad_North,
+    DPad_South,
+    DPad_West,
+    DPad_East,
+    Count,
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
 #[repr(C)]
 pub enum MistSteamInputGlyphSize {
     Small = 0,
@@ -331,7 +421,7 @@ pub enum MistSteamInputGlyphSize {
     Count,
 }
 
-#[derive(Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
 #[repr(C)]
 pub enum MistSteamInputGlyphStyle {
     // Styles, one of
@@ -344,7 +434,14 @@ pub enum MistSteamInputGlyphStyle {
     SolidABXY = 0x20,
 }
 
-#[derive(Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+pub type MistGlyphPngKey = (
+    MistInputActionOrigin,
+    MistSteamInputGlyphSize,
+    MistSteamInputGlyphStyle,
+);
+pub type MistGlyphSvgKey = (MistInputActionOrigin, MistSteamInputGlyphStyle);
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
 #[repr(C)]
 pub enum MistSteamInputType {
     Unknown = 0,
@@ -367,14 +464,14 @@ pub enum MistSteamInputType {
 }
 
 #[allow(non_camel_case_types)]
-#[derive(Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
 #[repr(C)]
 pub enum MistSteamControllerPad {
     SteamControllerPad_Left = 0,
     SteamControllerPad_Right = 1,
 }
 
-#[derive(Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
 #[repr(C)]
 pub enum MistControllerHapticLocation {
     Left = 1,
@@ -384,21 +481,21 @@ pub enum MistControllerHapticLocation {
 
 // Steam Utils
 
-#[derive(Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
 #[repr(C)]
 pub enum MistGamepadTextInputLineMode {
     SingleLine = 0,
     MultipleLines = 1,
 }
 
-#[derive(Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
 #[repr(C)]
 pub enum MistGamepadTextInputMode {
     Normal = 0,
     Password = 1,
 }
 
-#[derive(Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
 #[repr(C)]
 pub enum MistFloatingGamepadTextInputMode {
     SingleLine = 0,
@@ -407,7 +504,7 @@ pub enum MistFloatingGamepadTextInputMode {
     Numeric = 3,
 }
 
-#[derive(Default, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
 #[repr(C)]
 pub struct MistInputAnalogActionData {
     pub mode: MistControllerSourceMode,
@@ -416,14 +513,14 @@ pub struct MistInputAnalogActionData {
     pub active: bool,
 }
 
-#[derive(Default, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
 #[repr(C)]
 pub struct MistInputDigitalActionData {
     pub state: bool,
     pub active: bool,
 }
 
-#[derive(Default, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
 #[repr(C)]
 pub struct MistInputMotionData {
     pub rot_quat_x: c_float,
@@ -438,13 +535,62 @@ pub struct MistInputMotionData {
     pub rot_vel_z: c_float,
 }
 
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct MistVector3 {
+    pub x: c_float,
+    pub y: c_float,
+    pub z: c_float,
+}
+
+/// Settings turning the gyro of a gamepad into a pointer
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct MistGyroPointerConfig {
+    /// Pointer movement per degree of rotation, the screen spans -1 to 1 so 2 / sensitivity is the degrees to cross it
+    pub sensitivity: c_float,
+    /// Extra gain per 100 degrees per second of rotation, 0 disables acceleration
+    pub acceleration: c_float,
+    /// Rotation in degrees per second below which the gamepad is considered still and the gyro drift is learned
+    pub recenter_threshold: c_float,
+    /// Fraction of the drift learned per second while the gamepad is still
+    pub recenter_rate: c_float,
+    /// How far the pointer can move past the edges of the screen, where a lightgun is offscreen
+    pub offscreen_margin: c_float,
+}
+
+impl Default for MistGyroPointerConfig {
+    fn default() -> MistGyroPointerConfig {
+        MistGyroPointerConfig {
+            sensitivity: 2.0 / 30.0,
+            acceleration: 0.0,
+            recenter_threshold: 2.0,
+            recenter_rate: 0.5,
+            offscreen_margin: 0.25,
+        }
+    }
+}
+
+/// Pointer driven by the gyro, -1 is the left or top edge of the screen and 1 the right or bottom edge
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct MistGyroPointerState {
+    pub x: c_float,
+    pub y: c_float,
+    /// Movement since the previous snapshot
+    pub delta_x: c_float,
+    pub delta_y: c_float,
+}
+
+// The action data of the gamepads is stored after the MistInputStateBuffered header, see MistInputStateMemory
 #[derive(Clone, Copy)]
+#[repr(C)]
 pub struct MistInputStateGamepad {
     pub input_type: MistSteamInputType,
     pub input_handle: MistInputHandle,
-    pub analog_action_data: [MistInputAnalogActionData; MIST_STEAM_INPUT_MAX_ANALOG_ACTIONS + 1],
-    pub digital_action_data: [MistInputDigitalActionData; MIST_STEAM_INPUT_MAX_DIGITAL_ACTIONS + 1],
     pub motion_data: MistInputMotionData,
+    // 0 for controllers which are not used through Remote Play
+    pub remote_play_session_id: u32,
 }
 
 impl Default for MistInputStateGamepad {
@@ -452,26 +598,394 @@ impl Default for MistInputStateGamepad {
         MistInputStateGamepad {
             input_type: MistSteamInputType::Unknown,
             input_handle: 0,
-            analog_action_data: Default::default(),
-            digital_action_data: [MistInputDigitalActionData::default();
-                MIST_STEAM_INPUT_MAX_DIGITAL_ACTIONS + 1],
             motion_data: MistInputMotionData::default(),
+            remote_play_session_id: 0,
         }
     }
 }
 
-#[derive(Default, Clone, Copy)]
+#[derive(Default)]
+#[repr(C)]
 pub struct MistInputState {
+    // Odd while the subprocess writes to the buffer, readers retry if it changed while reading
+    pub sequence: AtomicU32,
+    /// Counts up with every snapshot written by the subprocess, 0 before the first one
+    pub frame: u64,
+    /// Microseconds of a monotonic clock that starts with the subprocess when the snapshot was taken
+    pub timestamp_us: u64,
     pub input_handles: [MistInputHandle; MIST_STEAM_INPUT_MAX_COUNT],
     pub input_handle_count: i32,
     pub gamepad_mapping: [MistInputHandle; MIST_STEAM_INPUT_MAX_COUNT],
     pub gamepads: [MistInputStateGamepad; MIST_MAX_GAMEPADS],
 }
 
+/// A press or release of a digital action
+#[derive(Debug, Default, Clone, Copy)]
+#[repr(C)]
+pub struct MistInputActionEvent {
+    pub input_handle: MistInputHandle,
+    pub digital_action_handle: MistInputDigitalActionHandle,
+    /// True when the action was pressed, false when it was released
+    pub pressed: bool,
+    /// Microseconds of a monotonic clock that starts with the subprocess
+    pub timestamp_us: u64,
+}
+
+/// Motor speeds of a haptic pattern at a point in time, speeds between keyframes are interpolated linearly
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[repr(C)]
+pub struct MistHapticKeyframe {
+    /// Time since the start of the pattern, increasing with every keyframe
+    pub time_ms: u32,
+    pub left_speed: c_ushort,
+    pub right_speed: c_ushort,
+    pub left_trigger_speed: c_ushort,
+    pub right_trigger_speed: c_ushort,
+}
+
+/// Kind of the action handle passed to mist_steam_input_get_action_prompt
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[repr(C)]
+pub enum MistInputActionType {
+    Analog,
+    Digital,
+}
+
+/// What to show the user for an action, the strings stay valid until the subprocess is deinitialized
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct MistInputActionPrompt {
+    /// The primary origin of the action, None if the action is not bound
+    pub origin: MistInputActionOrigin,
+    /// Localized name of the origin, NULL if the action is not bound
+    pub origin_string: *const c_char,
+    /// Path of the png glyph of the origin, NULL if the action is not bound or the origin has no glyph
+    pub glyph_path: *const c_char,
+}
+
+/// A controller was assigned to or removed from a slot of the gamepad mapping
+#[derive(Debug, Default, Clone, Copy)]
+#[repr(C)]
+pub struct MistInputSlotEvent {
+    /// Index into the gamepad mapping
+    pub slot: u32,
+    /// The controller now in the slot, 0 if the slot was freed
+    pub input_handle: MistInputHandle,
+    /// The controller previously in the slot, 0 if the slot was free
+    pub previous_input_handle: MistInputHandle,
+    /// Microseconds of a monotonic clock that starts with the subprocess
+    pub timestamp_us: u64,
+}
+
+/// Ring of events written by the subprocess and read by the library
 /// cbindgen:ignore
-#[derive(Default)]
+#[repr(C)]
+pub struct MistInputEventRing<T: Copy + Default> {
+    // The indices count up and wrap, the slot is the index modulo the ring size
+    pub write_index: AtomicU32,
+    pub read_index: AtomicU32,
+    pub events: [T; MIST_INPUT_EVENT_RING_SIZE],
+}
+
+impl<T: Copy + Default> Default for MistInputEventRing<T> {
+    fn default() -> MistInputEventRing<T> {
+        MistInputEventRing {
+            write_index: AtomicU32::new(0),
+            read_index: AtomicU32::new(0),
+            events: [T::default(); MIST_INPUT_EVENT_RING_SIZE],
+        }
+    }
+}
+
+#[allow(dead_code)] // The subprocess only pushes and the library only pops
+impl<T: Copy + Default> MistInputEventRing<T> {
+    /// Queues the event, returns false if the ring is full
+    pub fn push(&mut self, event: T) -> bool {
+        let write = self.write_index.load(Ordering::Relaxed);
+        if write.wrapping_sub(self.read_index.load(Ordering::Acquire)) as usize
+            >= MIST_INPUT_EVENT_RING_SIZE
+        {
+            return false;
+        }
+
+        self.events[write as usize % MIST_INPUT_EVENT_RING_SIZE] = event;
+        self.write_index
+            .store(write.wrapping_add(1), Ordering::Release);
+
+        true
+    }
+
+    /// Takes the oldest event
+    pub fn pop(&self) -> Option<T> {
+        let read = self.read_index.load(Ordering::Relaxed);
+        if read == self.write_index.load(Ordering::Acquire) {
+            return None;
+        }
+
+        let event = self.events[read as usize % MIST_INPUT_EVENT_RING_SIZE];
+        self.read_index
+            .store(read.wrapping_add(1), Ordering::Release);
+
+        Some(event)
+    }
+}
+
+/// Header at the start of the Steam Input shared memory
+/// cbindgen:ignore
+#[repr(C)]
 pub struct MistInputStateBuffered {
-    pub library_cursor: AtomicU8,
-    pub subprocess_cursor: AtomicU8,
+    /// Size of the shared memory in bytes
+    pub size: u64,
+    /// MIST_INPUT_STATE_VERSION of the subprocess that created the shared memory
+    pub version: u32,
+    pub analog_action_capacity: u32,
+    pub digital_action_capacity: u32,
+    // Number of registered actions, the slot of an action is its index in the handle table
+    pub analog_action_count: AtomicU32,
+    pub digital_action_count: AtomicU32,
+    // Index of the most recently published buffer, or'd with MIST_INPUT_STATE_LATEST_FRESH until the library takes it
+    // The subprocess and the library swap their buffer with it, so neither writes to the buffer of the other
+    pub latest: AtomicU8,
+    // Bumped by the subprocess whenever Steam Input loads the configuration of a controller
+    pub configuration_generation: AtomicU32,
+    /// Microseconds since the unix epoch when the timestamp clock started
+    /// The snapshot and event timestamps are microseconds since that start, taken from a monotonic clock
+    /// The clock is kept when the shared memory is resized and starts over with a new subprocess
+    pub clock_start_us: u64,
     pub buffer: [MistInputState; MIST_INPUT_STATE_BUFFER_SIZE as usize],
+    pub action_events: MistInputEventRing<MistInputActionEvent>,
+    pub slot_events: MistInputEventRing<MistInputSlotEvent>,
+}
+
+impl MistInputStateBuffered {
+    pub fn new(layout: MistInputStateLayout) -> MistInputStateBuffered {
+        MistInputStateBuffered {
+            size: layout.size() as u64,
+            version: MIST_INPUT_STATE_VERSION,
+            analog_action_capacity: layout.analog_action_capacity as u32,
+            digital_action_capacity: layout.digital_action_capacity as u32,
+            analog_action_count: AtomicU32::new(0),
+            digital_action_count: AtomicU32::new(0),
+            // The library starts with buffer 0 and the subprocess with the last one
+            latest: AtomicU8::new(1),
+            configuration_generation: AtomicU32::new(0),
+            clock_start_us: 0,
+            buffer: Default::default(),
+            action_events: Default::default(),
+            slot_events: Default::default(),
+        }
+    }
+
+    pub fn layout(&self) -> MistInputStateLayout {
+        MistInputStateLayout {
+            analog_action_capacity: self.analog_action_capacity as usize,
+            digital_action_capacity: self.digital_action_capacity as usize,
+        }
+    }
+}
+
+/// Number of action slots in the Steam Input shared memory
+/// The header is followed by the analog and digital handle tables,
+/// then the analog and digital action data of every gamepad in every buffer
+/// cbindgen:ignore
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MistInputStateLayout {
+    pub analog_action_capacity: usize,
+    pub digital_action_capacity: usize,
+}
+
+impl Default for MistInputStateLayout {
+    fn default() -> MistInputStateLayout {
+        MistInputStateLayout {
+            analog_action_capacity: MIST_STEAM_INPUT_DEFAULT_ANALOG_ACTIONS,
+            digital_action_capacity: MIST_STEAM_INPUT_DEFAULT_DIGITAL_ACTIONS,
+        }
+    }
+}
+
+impl MistInputStateLayout {
+    const PADS: usize = MIST_INPUT_STATE_BUFFER_SIZE as usize * MIST_MAX_GAMEPADS;
+
+    // The header is 8 byte aligned, so all offsets are aligned for the types stored at them
+    fn analog_action_handles_offset(&self) -> usize {
+        std::mem::size_of::<MistInputStateBuffered>()
+    }
+
+    fn digital_action_handles_offset(&self) -> usize {
+        self.analog_action_handles_offset()
+            + self.analog_action_capacity * std::mem::size_of::<MistInputAnalogActionHandle>()
+    }
+
+    fn analog_action_data_offset(&self) -> usize {
+        self.digital_action_handles_offset()
+            + self.digital_action_capacity * std::mem::size_of::<MistInputDigitalActionHandle>()
+    }
+
+    fn digital_action_data_offset(&self) -> usize {
+        self.analog_action_data_offset()
+            + Self::PADS
+                * self.analog_action_capacity
+                * std::mem::size_of::<MistInputAnalogActionData>()
+    }
+
+    /// Size of the shared memory in bytes
+    pub fn size(&self) -> usize {
+        self.digital_action_data_offset()
+            + Self::PADS
+                * self.digital_action_capacity
+                * std::mem::size_of::<MistInputDigitalActionData>()
+    }
+}
+
+/// Pointers into the Steam Input shared memory
+/// cbindgen:ignore
+#[derive(Clone, Copy)]
+pub struct MistInputStateMemory {
+    ptr: *mut u8,
+    layout: MistInputStateLayout,
+}
+
+impl MistInputStateMemory {
+    /// Views the shared memory starting at the header
+    ///
+    /// # Safety
+    /// The pointer needs to point to a mapping of at least the size in its header, which has to outlive the returned value
+    pub unsafe fn from_ptr(ptr: *mut MistInputStateBuffered) -> MistInputStateMemory {
+        MistInputStateMemory {
+            ptr: ptr as *mut u8,
+            layout: (*ptr).layout(),
+        }
+    }
+
+    pub fn header(self) -> *mut MistInputStateBuffered {
+        self.ptr as *mut MistInputStateBuffered
+    }
+
+    /// The requested actions, indexed by slot
+    pub fn analog_action_handles(&self) -> &[MistInputAnalogActionHandle] {
+        let count = unsafe { &*self.header() }
+            .analog_action_count
+            .load(Ordering::Acquire) as usize;
+
+        unsafe {
+            std::slice::from_raw_parts(
+                self.ptr.add(self.layout.analog_action_handles_offset()) as *const _,
+                count.min(self.layout.analog_action_capacity),
+            )
+        }
+    }
+
+    /// The requested actions, indexed by slot
+    pub fn digital_action_handles(&self) -> &[MistInputDigitalActionHandle] {
+        let count = unsafe { &*self.header() }
+            .digital_action_count
+            .load(Ordering::Acquire) as usize;
+
+        unsafe {
+            std::slice::from_raw_parts(
+                self.ptr.add(self.layout.digital_action_handles_offset()) as *const _,
+                count.min(self.layout.digital_action_capacity),
+            )
+        }
+    }
+
+    /// Slot of the action in the action data, None if the handle was never requested
+    pub fn analog_action_slot(self, handle: MistInputAnalogActionHandle) -> Option<usize> {
+        self.analog_action_handles()
+            .iter()
+            .position(|registered| *registered == handle)
+    }
+
+    /// Slot of the action in the action data, None if the handle was never requested
+    pub fn digital_action_slot(self, handle: MistInputDigitalActionHandle) -> Option<usize> {
+        self.digital_action_handles()
+            .iter()
+            .position(|registered| *registered == handle)
+    }
+
+    /// Adds the action to the handle table, returns the slot or None if the table is full
+    pub fn register_analog_action(self, handle: MistInputAnalogActionHandle) -> Option<usize> {
+        if let Some(slot) = self.analog_action_slot(handle) {
+            return Some(slot);
+        }
+
+        let header = unsafe { &*self.header() };
+        let slot = header.analog_action_count.load(Ordering::Relaxed) as usize;
+        if slot >= self.layout.analog_action_capacity {
+            return None;
+        }
+
+        unsafe {
+            *(self.ptr.add(self.layout.analog_action_handles_offset())
+                as *mut MistInputAnalogActionHandle)
+                .add(slot) = handle;
+        }
+        header
+            .analog_action_count
+            .store(slot as u32 + 1, Ordering::Release);
+
+        Some(slot)
+    }
+
+    /// Adds the action to the handle table, returns the slot or None if the table is full
+    pub fn register_digital_action(self, handle: MistInputDigitalActionHandle) -> Option<usize> {
+        if let Some(slot) = self.digital_action_slot(handle) {
+            return Some(slot);
+        }
+
+        let header = unsafe { &*self.header() };
+        let slot = header.digital_action_count.load(Ordering::Relaxed) as usize;
+        if slot >= self.layout.digital_action_capacity {
+            return None;
+        }
+
+        unsafe {
+            *(self.ptr.add(self.layout.digital_action_handles_offset())
+                as *mut MistInputDigitalActionHandle)
+                .add(slot) = handle;
+        }
+        header
+            .digital_action_count
+            .store(slot as u32 + 1, Ordering::Release);
+
+        Some(slot)
+    }
+
+    /// Analog action data of the gamepad, indexed by slot
+    pub fn analog_action_data(
+        self,
+        buffer: usize,
+        gamepad: usize,
+    ) -> *mut [MistInputAnalogActionData] {
+        let capacity = self.layout.analog_action_capacity;
+        let pad = buffer * MIST_MAX_GAMEPADS + gamepad;
+
+        std::ptr::slice_from_raw_parts_mut(
+            unsafe {
+                (self.ptr.add(self.layout.analog_action_data_offset())
+                    as *mut MistInputAnalogActionData)
+                    .add(pad * capacity)
+            },
+            capacity,
+        )
+    }
+
+    /// Digital action data of the gamepad, indexed by slot
+    pub fn digital_action_data(
+        self,
+        buffer: usize,
+        gamepad: usize,
+    ) -> *mut [MistInputDigitalActionData] {
+        let capacity = self.layout.digital_action_capacity;
+        let pad = buffer * MIST_MAX_GAMEPADS + gamepad;
+
+        std::ptr::slice_from_raw_parts_mut(
+            unsafe {
+                (self.ptr.add(self.layout.digital_action_data_offset())
+                    as *mut MistInputDigitalActionData)
+                    .add(pad * capacity)
+            },
+            capacity,
+        )
+    }
 }
@@ -0,0 +1,49 @@
+use mist::{
+    recorder::{
+        read_recording, replay, MistIpcDirection, MIST_REPLAY_FILE_ENV, PROCESS_INIT_SECRET,
+    },
+    transport::subprocess_transport,
+};
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+
+    match args.get(1).map(|arg| arg.as_str()) {
+        // Spawned by the library through MIST_SUBPROCESS_PATH
+        Some(PROCESS_INIT_SECRET) => {
+            let path = std::env::var_os(MIST_REPLAY_FILE_ENV).expect("MIST_REPLAY_FILE is not set");
+            let records = read_recording(path).expect("Error reading recording");
+
+            let (read, write) = subprocess_transport()
+                .split()
+                .expect("Error setting up the IPC transport");
+
+            if let Err(err) = replay(&records, read, write) {
+                eprintln!("[mist-replay] Replay stopped: {}", err);
+                std::process::exit(1);
+            }
+        }
+        Some("dump") if args.len() == 3 => {
+            let records = read_recording(&args[2]).expect("Error reading recording");
+
+            for record in records {
+                let direction = match record.direction {
+                    MistIpcDirection::LibraryToService => "library -> service",
+                    MistIpcDirection::ServiceToLibrary => "service -> library",
+                };
+
+                println!(
+                    "{:>12.3}ms {} {}",
+                    record.time.as_secs_f64() * 1000.0,
+                    direction,
+                    record.describe()
+                );
+            }
+        }
+        _ => {
+            eprintln!("Usage: mist-replay dump <recording>");
+            eprintln!("To replay a recording run the application with MIST_SUBPROCESS_PATH set to this executable and {} set to the recording", MIST_REPLAY_FILE_ENV);
+            std::process::exit(1);
+        }
+    }
+}